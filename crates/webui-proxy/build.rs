@@ -0,0 +1,88 @@
+//! Embeds the output of `trunk build` together with precompressed variants.
+//!
+//! Every file of `webui/dist` is served under its own name. A gzip variant is generated here,
+//! a brotli variant is picked up from a `<file>.br` sibling when the release build produced one.
+
+use flate2::{Compression, write::GzEncoder};
+use std::{
+    env,
+    fmt::Write as _,
+    fs,
+    hash::{DefaultHasher, Hasher},
+    io::Write as _,
+    path::{Path, PathBuf},
+};
+
+fn main() {
+    let out_dir = PathBuf::from(env::var("OUT_DIR").expect("OUT_DIR is set by cargo"));
+    let assets_rs = out_dir.join("assets.rs");
+    let dist = Path::new(env!("CARGO_MANIFEST_DIR")).join("../webui/dist");
+    println!("cargo:rerun-if-changed={}", dist.display());
+
+    if env::var_os("CARGO_CFG_DEBUG_ASSERTIONS").is_some() {
+        // debug: embedding is skipped
+        fs::write(&assets_rs, "static ASSETS: &[Asset] = &[];\n").unwrap();
+        return;
+    }
+
+    let mut files: Vec<PathBuf> = fs::read_dir(&dist)
+        .unwrap_or_else(|err| panic!("cannot read {dist:?}, run `trunk build` first - {err}"))
+        .map(|entry| entry.expect("dist entry must be readable").path())
+        .filter(|path| {
+            path.is_file()
+                && !path
+                    .extension()
+                    .is_some_and(|extension| extension == "gz" || extension == "br")
+        })
+        .collect();
+    files.sort();
+
+    let mut code = String::from("static ASSETS: &[Asset] = &[\n");
+    for path in files {
+        let name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .expect("dist file names must be UTF-8");
+        let content = fs::read(&path).expect("dist file must be readable");
+
+        let mut hasher = DefaultHasher::new();
+        hasher.write(&content);
+        let hash = hasher.finish();
+
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
+        encoder.write_all(&content).unwrap();
+        let gzipped = encoder.finish().unwrap();
+        // Not worth it for tiny or already compressed files.
+        let gzip = if gzipped.len() < content.len() {
+            let gzip_path = out_dir.join(format!("{name}.gz"));
+            fs::write(&gzip_path, gzipped).unwrap();
+            format!(
+                "Some(include_bytes!({:?}))",
+                gzip_path.display().to_string()
+            )
+        } else {
+            "None".to_string()
+        };
+
+        let brotli_path = path.with_file_name(format!("{name}.br"));
+        let brotli = if brotli_path.exists() {
+            println!("cargo:rerun-if-changed={}", brotli_path.display());
+            format!(
+                "Some(include_bytes!({:?}))",
+                brotli_path.display().to_string()
+            )
+        } else {
+            "None".to_string()
+        };
+
+        writeln!(
+            code,
+            "    Asset {{\n        path: \"/{name}\",\n        hash: \"{hash:016x}\",\n        \
+             identity: include_bytes!({:?}),\n        gzip: {gzip},\n        brotli: {brotli},\n    }},",
+            path.display().to_string()
+        )
+        .unwrap();
+    }
+    code.push_str("];\n");
+    fs::write(&assets_rs, code).unwrap();
+}
@@ -0,0 +1,413 @@
+//! JSON access log lines on stdout and optional Prometheus metrics at [`METRICS_PATH`].
+//!
+//! Streamed gRPC-web responses are logged once their body is sent, the `grpc-status` is read
+//! from the trailer frame at the end of the body.
+
+use std::{collections::BTreeMap, fmt::Write as _, sync::Mutex, time::Instant};
+use wstd::http::{Body, BodyExt as _, Error, HeaderMap, Request, Response, StatusCode};
+
+pub(crate) const METRICS_PATH: &str = "/metrics";
+const LATENCY_BUCKETS_SECS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+/// Route of requests that are not gRPC calls, e.g. the web UI assets.
+const OTHER_ROUTE: &str = "other";
+/// Flag of the gRPC-web frame carrying the trailers.
+const TRAILER_FRAME_FLAG: u8 = 0x80;
+
+/// `/metrics` is only served with `METRICS=true`.
+pub(crate) fn metrics_enabled() -> bool {
+    std::env::var("METRICS").is_ok_and(|metrics| metrics == "true")
+}
+
+#[derive(Default)]
+struct Histogram {
+    /// Counts per bucket of [`LATENCY_BUCKETS_SECS`], not cumulative.
+    buckets: [u64; LATENCY_BUCKETS_SECS.len()],
+    count: u64,
+    sum: f64,
+}
+
+/// Key: environment, route - the gRPC method or [`OTHER_ROUTE`].
+type RouteKey = (String, String);
+
+struct Metrics {
+    /// Per route, HTTP status and `grpc-status`.
+    requests: BTreeMap<(RouteKey, u16, String), u64>,
+    latency: BTreeMap<RouteKey, Histogram>,
+}
+
+/// Counters of this component instance.
+static METRICS: Mutex<Metrics> = Mutex::new(Metrics {
+    requests: BTreeMap::new(),
+    latency: BTreeMap::new(),
+});
+
+/// Split `/api[/<environment>]/<service>/<method>` into the environment and `<service>/<method>`.
+fn parse_grpc_path(path: &str) -> Option<(Option<&str>, String)> {
+    let segments: Vec<&str> = path.strip_prefix("/api/")?.split('/').collect();
+    match segments.as_slice() {
+        [service, method] if service.contains('.') => Some((None, format!("{service}/{method}"))),
+        [environment, service, method] if service.contains('.') => {
+            Some((Some(*environment), format!("{service}/{method}")))
+        }
+        _ => None,
+    }
+}
+
+/// Reads the `grpc-status` of gRPC-web frames split arbitrarily across data chunks.
+#[derive(Default)]
+struct FrameScanner {
+    header: Vec<u8>,
+    remaining: usize,
+    trailer: Vec<u8>,
+    grpc_status: Option<String>,
+}
+
+impl FrameScanner {
+    fn feed(&mut self, mut data: &[u8]) {
+        while !data.is_empty() {
+            if self.header.len() < 5 {
+                let take = (5 - self.header.len()).min(data.len());
+                self.header.extend_from_slice(&data[..take]);
+                data = &data[take..];
+                if self.header.len() == 5 {
+                    let length: [u8; 4] = self.header[1..5].try_into().expect("4 bytes");
+                    self.remaining = u32::from_be_bytes(length) as usize;
+                    if self.remaining == 0 {
+                        self.finish_frame();
+                    }
+                }
+                continue;
+            }
+            let take = self.remaining.min(data.len());
+            if self.header[0] & TRAILER_FRAME_FLAG != 0 {
+                self.trailer.extend_from_slice(&data[..take]);
+            }
+            data = &data[take..];
+            self.remaining -= take;
+            if self.remaining == 0 {
+                self.finish_frame();
+            }
+        }
+    }
+
+    fn finish_frame(&mut self) {
+        if self.header[0] & TRAILER_FRAME_FLAG != 0 {
+            self.grpc_status = String::from_utf8_lossy(&self.trailer)
+                .split("\r\n")
+                .find_map(|line| {
+                    let (name, value) = line.split_once(':')?;
+                    name.trim()
+                        .eq_ignore_ascii_case("grpc-status")
+                        .then(|| value.trim().to_string())
+                });
+        }
+        self.header.clear();
+        self.trailer.clear();
+    }
+}
+
+/// The `grpc-status` of the trailer frame of a complete gRPC-web body.
+pub(crate) fn trailer_grpc_status(body: &[u8]) -> Option<String> {
+    let mut scanner = FrameScanner::default();
+    scanner.feed(body);
+    scanner.grpc_status
+}
+
+/// Written when dropped, which happens after the response body was sent.
+pub(crate) struct AccessLog {
+    started: Instant,
+    method: String,
+    path: String,
+    environment: Option<String>,
+    grpc_method: Option<String>,
+    user: Option<String>,
+    status: Option<u16>,
+    grpc_status: Option<String>,
+    scanner: FrameScanner,
+}
+
+impl AccessLog {
+    pub(crate) fn new(req: &Request<Body>) -> Self {
+        let path = req.uri().path().to_string();
+        let (environment, grpc_method) = match parse_grpc_path(&path) {
+            Some((environment, grpc_method)) => {
+                (environment.map(ToString::to_string), Some(grpc_method))
+            }
+            None => (None, None),
+        };
+        AccessLog {
+            started: Instant::now(),
+            method: req.method().to_string(),
+            path,
+            environment,
+            grpc_method,
+            user: None,
+            status: None,
+            grpc_status: None,
+            scanner: FrameScanner::default(),
+        }
+    }
+
+    /// Remember the user set by the authentication gate.
+    pub(crate) fn set_user(&mut self, headers: &HeaderMap) {
+        self.user = headers
+            .get(crate::auth::USER_HEADER)
+            .and_then(|user| user.to_str().ok())
+            .map(ToString::to_string);
+    }
+
+    /// Attach the log to the response, so that it is written after the body was sent.
+    pub(crate) fn observe(mut self, resp: Response<Body>) -> Response<Body> {
+        self.status = Some(resp.status().as_u16());
+        // Trailers-only responses carry the status in the headers.
+        self.grpc_status = resp
+            .headers()
+            .get("grpc-status")
+            .and_then(|status| status.to_str().ok())
+            .map(ToString::to_string);
+        let is_grpc_web = resp
+            .headers()
+            .get("content-type")
+            .and_then(|content_type| content_type.to_str().ok())
+            .is_some_and(|content_type| {
+                content_type.starts_with("application/grpc-web")
+                    && !content_type.starts_with("application/grpc-web-text")
+            });
+        if self.grpc_status.is_some() || !is_grpc_web {
+            return resp; // dropping `self` writes the log
+        }
+        resp.map(|body| {
+            Body::from_http_body(body.into_boxed_body().map_frame(move |frame| {
+                if let Some(data) = frame.data_ref() {
+                    self.scanner.feed(data);
+                } else if let Some(trailers) = frame.trailers_ref() {
+                    self.grpc_status = trailers
+                        .get("grpc-status")
+                        .and_then(|status| status.to_str().ok())
+                        .map(ToString::to_string);
+                }
+                frame
+            }))
+        })
+    }
+}
+
+impl Drop for AccessLog {
+    fn drop(&mut self) {
+        let latency = self.started.elapsed().as_secs_f64();
+        let grpc_status = self
+            .grpc_status
+            .take()
+            .or_else(|| self.scanner.grpc_status.take());
+        println!(
+            "{}",
+            serde_json::json!({
+                "time_ms": std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map(|since_epoch| since_epoch.as_millis())
+                    .unwrap_or_default(),
+                "method": self.method,
+                "path": self.path,
+                "environment": self.environment,
+                "grpc_method": self.grpc_method,
+                "user": self.user,
+                "status": self.status,
+                "grpc_status": grpc_status,
+                "latency_ms": latency * 1000.0,
+            })
+        );
+
+        let key = (
+            self.environment.take().unwrap_or_default(),
+            self.grpc_method
+                .take()
+                .unwrap_or_else(|| OTHER_ROUTE.to_string()),
+        );
+        let mut metrics = METRICS
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        *metrics
+            .requests
+            .entry((
+                key.clone(),
+                self.status.unwrap_or_default(),
+                grpc_status.unwrap_or_default(),
+            ))
+            .or_default() += 1;
+        let histogram = metrics.latency.entry(key).or_default();
+        if let Some(bucket) = LATENCY_BUCKETS_SECS
+            .iter()
+            .position(|upper_bound| latency <= *upper_bound)
+        {
+            histogram.buckets[bucket] += 1;
+        }
+        histogram.count += 1;
+        histogram.sum += latency;
+    }
+}
+
+/// Label values must escape backslashes, quotes and newlines.
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Prometheus text exposition of the counters.
+fn metrics_text() -> String {
+    let metrics = METRICS
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    let mut text = String::new();
+    text.push_str(
+        "# HELP webui_proxy_requests_total Requests served by the proxy.\n\
+         # TYPE webui_proxy_requests_total counter\n",
+    );
+    for (((environment, route), status, grpc_status), count) in &metrics.requests {
+        writeln!(
+            text,
+            "webui_proxy_requests_total{{environment=\"{}\",route=\"{}\",status=\"{status}\",grpc_status=\"{}\"}} {count}",
+            escape_label(environment),
+            escape_label(route),
+            escape_label(grpc_status),
+        )
+        .unwrap();
+    }
+    text.push_str(
+        "# HELP webui_proxy_request_duration_seconds Latency until the response body was sent.\n\
+         # TYPE webui_proxy_request_duration_seconds histogram\n",
+    );
+    for ((environment, route), histogram) in &metrics.latency {
+        let labels = format!(
+            "environment=\"{}\",route=\"{}\"",
+            escape_label(environment),
+            escape_label(route)
+        );
+        let mut cumulative = 0;
+        for (upper_bound, count) in LATENCY_BUCKETS_SECS.iter().zip(histogram.buckets) {
+            cumulative += count;
+            writeln!(
+                text,
+                "webui_proxy_request_duration_seconds_bucket{{{labels},le=\"{upper_bound}\"}} {cumulative}"
+            )
+            .unwrap();
+        }
+        writeln!(
+            text,
+            "webui_proxy_request_duration_seconds_bucket{{{labels},le=\"+Inf\"}} {}\n\
+             webui_proxy_request_duration_seconds_sum{{{labels}}} {}\n\
+             webui_proxy_request_duration_seconds_count{{{labels}}} {}",
+            histogram.count, histogram.sum, histogram.count
+        )
+        .unwrap();
+    }
+    text
+}
+
+/// Served before the session gate, scrapers authenticate with `METRICS_TOKEN` when it is set.
+pub(crate) fn metrics_response(req: &Request<Body>) -> Result<Response<Body>, Error> {
+    if let Ok(token) = std::env::var("METRICS_TOKEN")
+        && !token.is_empty()
+    {
+        let authorized = req
+            .headers()
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .is_some_and(|bearer| {
+                crate::auth::constant_time_eq(bearer.as_bytes(), token.as_bytes())
+            });
+        if !authorized {
+            return Response::builder()
+                .status(StatusCode::UNAUTHORIZED)
+                .body(Body::empty())
+                .map_err(Error::from);
+        }
+    }
+    Response::builder()
+        .header("content-type", "text/plain; version=0.0.4")
+        .header("cache-control", "no-store")
+        .body(Body::from(metrics_text()))
+        .map_err(Error::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn served(path: &str, resp: Response<Body>) {
+        let req = Request::post(path).body(Body::empty()).unwrap();
+        drop(AccessLog::new(&req).observe(resp));
+    }
+
+    #[test]
+    fn scrape_counts_requests_by_route_and_status() {
+        // The counters are shared by all tests, this one owns the `metrics-test` environment.
+        let denied = || {
+            Response::builder()
+                .header("content-type", "application/grpc-web+proto")
+                .header("grpc-status", "7")
+                .body(Body::empty())
+                .unwrap()
+        };
+        served(
+            "/api/metrics-test/obelisk.ExecutionRepository/Stub",
+            denied(),
+        );
+        served(
+            "/api/metrics-test/obelisk.ExecutionRepository/Stub",
+            denied(),
+        );
+        let req = Request::get("/metrics").body(Body::empty()).unwrap();
+        let resp = metrics_response(&req).unwrap();
+        assert_eq!(StatusCode::OK, resp.status());
+        assert_eq!(
+            "text/plain; version=0.0.4",
+            resp.headers().get("content-type").unwrap()
+        );
+
+        let text = metrics_text();
+        assert!(
+            text.contains(
+                "webui_proxy_requests_total{environment=\"metrics-test\",route=\"obelisk.ExecutionRepository/Stub\",status=\"200\",grpc_status=\"7\"} 2\n"
+            ),
+            "{text}"
+        );
+        assert!(
+            text.contains(
+                "webui_proxy_request_duration_seconds_count{environment=\"metrics-test\",route=\"obelisk.ExecutionRepository/Stub\"} 2\n"
+            ),
+            "{text}"
+        );
+        assert!(
+            text.contains(
+                "webui_proxy_request_duration_seconds_bucket{environment=\"metrics-test\",route=\"obelisk.ExecutionRepository/Stub\",le=\"+Inf\"} 2\n"
+            ),
+            "{text}"
+        );
+    }
+
+    #[test]
+    fn assets_are_counted_as_other_route() {
+        let not_found = Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(Body::empty())
+            .unwrap();
+        served("/missing-asset.js", not_found);
+        let text = metrics_text();
+        assert!(
+            text.contains(
+                "webui_proxy_requests_total{environment=\"\",route=\"other\",status=\"404\",grpc_status=\"\"}"
+            ),
+            "{text}"
+        );
+    }
+
+    #[test]
+    fn label_values_are_escaped() {
+        assert_eq!(r#"a\"b\\c\nd"#, escape_label("a\"b\\c\nd"));
+    }
+}
@@ -0,0 +1,618 @@
+//! Optional authentication of browsers, configured through environment variables.
+//!
+//! With `AUTH_MODE=basic` or `AUTH_MODE=oidc` every request needs a session cookie signed
+//! with `SESSION_SECRET`. Requests forwarded to `TARGET_URL` lose the browser's cookies and
+//! `Authorization` header, the proxy sends `UPSTREAM_TOKEN` as the bearer token instead.
+//!
+//! Read-only users, or everyone with `READ_ONLY=true`, cannot call mutating RPCs. Responses
+//! tell the web UI about it so that it disables the corresponding controls.
+
+mod oidc;
+mod session;
+
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest as _, Sha256};
+use wstd::http::{Body, Error, HeaderMap, HeaderValue, Request, Response, StatusCode};
+
+const SESSION_COOKIE: &str = "obelisk_webui_session";
+const LOGOUT_PATH: &str = "/auth/logout";
+/// Set on logout in `basic` mode, the cached credentials of the browser must not start a new
+/// session until it has been challenged again.
+const LOGGED_OUT_COOKIE: &str = "obelisk_webui_logged_out";
+const LOGGED_OUT_TTL_SECS: u64 = 10 * 60;
+const DEFAULT_SESSION_TTL_SECS: u64 = 8 * 60 * 60;
+/// Response header telling the web UI to reload the page instead of asking for a token.
+const LOGIN_REQUIRED_HEADER: &str = "x-obelisk-login";
+/// Request header carrying the authenticated user to the Obelisk server.
+pub(crate) const USER_HEADER: &str = "x-obelisk-user";
+/// Response header with the signed-in user, the web UI then offers to sign out.
+const SESSION_USER_HEADER: &str = "x-obelisk-session-user";
+/// Response header with the reason why mutating controls are disabled.
+const READ_ONLY_HEADER: &str = "x-obelisk-read-only";
+/// RPCs changing the state of the server, see `obelisk.proto`.
+const MUTATING_METHODS: &[&str] = &[
+    "obelisk.ExecutionRepository/Submit",
+    "obelisk.ExecutionRepository/Stub",
+    "obelisk.ExecutionRepository/CancelExecution",
+    "obelisk.ExecutionRepository/CancelDelay",
+    "obelisk.ExecutionRepository/PersistExecutionBacktraces",
+    "obelisk.ExecutionRepository/AdvanceExecution",
+    "obelisk.ExecutionRepository/UpgradeExecutionComponent",
+    "obelisk.ExecutionRepository/PauseExecution",
+    "obelisk.ExecutionRepository/UnpauseExecution",
+    "obelisk.ExecutionRepository/PauseDelay",
+    "obelisk.ExecutionRepository/UnpauseDelay",
+    "obelisk.DeploymentRepository/SwitchDeployment",
+    "obelisk.DeploymentRepository/SubmitDeployment",
+    "obelisk.DeploymentRepository/GcOrphanFiles",
+];
+
+struct Config {
+    secret: Vec<u8>,
+    session_ttl: u64,
+    secure_cookies: bool,
+    upstream_token: Option<String>,
+    /// Users with read-only access, `*` matches everyone.
+    read_only_users: Vec<String>,
+    mode: Mode,
+}
+
+enum Mode {
+    Basic(Vec<BasicUser>),
+    Oidc(oidc::OidcConfig),
+}
+
+struct BasicUser {
+    name: String,
+    password_sha256: Vec<u8>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Session {
+    /// Name of the user.
+    sub: String,
+    /// Expiry, seconds since the Unix epoch.
+    exp: u64,
+    /// Read-only access granted by the identity provider. [`Config::read_only`] adds the
+    /// configured users on every request, so changing them does not wait for a new session.
+    #[serde(default)]
+    read_only: bool,
+}
+
+fn env(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|value| !value.is_empty())
+}
+
+fn required_env(name: &str) -> Result<String, String> {
+    env(name).ok_or_else(|| format!("missing environment variable {name}"))
+}
+
+impl Config {
+    fn from_env() -> Result<Option<Self>, String> {
+        let mode = match env("AUTH_MODE").as_deref() {
+            None | Some("none") => return Ok(None),
+            Some("basic") => Mode::Basic(parse_basic_users(&required_env("AUTH_BASIC_USERS")?)?),
+            Some("oidc") => Mode::Oidc(oidc::OidcConfig::from_env()?),
+            Some(other) => {
+                return Err(format!(
+                    "AUTH_MODE must be one of `none`, `basic`, `oidc`, got `{other}`"
+                ));
+            }
+        };
+        let secret = required_env("SESSION_SECRET")?;
+        if secret.len() < 32 {
+            return Err("SESSION_SECRET must be at least 32 characters long".to_string());
+        }
+        let session_ttl = match env("SESSION_TTL_SECS") {
+            Some(ttl) => ttl
+                .parse()
+                .map_err(|_| format!("SESSION_TTL_SECS must be a number, got `{ttl}`"))?,
+            None => DEFAULT_SESSION_TTL_SECS,
+        };
+        Ok(Some(Config {
+            secret: secret.into_bytes(),
+            session_ttl,
+            secure_cookies: env("SESSION_COOKIE_SECURE").is_none_or(|secure| secure != "false"),
+            upstream_token: env("UPSTREAM_TOKEN"),
+            read_only_users: env("AUTH_READ_ONLY_USERS")
+                .map(|users| {
+                    users
+                        .split(',')
+                        .map(|user| user.trim().to_string())
+                        .filter(|user| !user.is_empty())
+                        .collect()
+                })
+                .unwrap_or_default(),
+            mode,
+        }))
+    }
+
+    fn session(&self, headers: &HeaderMap) -> Option<Session> {
+        session::cookie(headers, SESSION_COOKIE)
+            .and_then(|signed| session::verify::<Session>(&self.secret, signed))
+            .filter(|session| session.exp > session::now())
+    }
+
+    /// `read_only` is set by the identity provider.
+    fn new_session(&self, user: &str, read_only: bool) -> Session {
+        Session {
+            sub: user.to_string(),
+            exp: session::now() + self.session_ttl,
+            read_only,
+        }
+    }
+
+    fn read_only(&self, session: &Session) -> bool {
+        session.read_only
+            || self
+                .read_only_users
+                .iter()
+                .any(|read_only_user| *read_only_user == session.sub || read_only_user == "*")
+    }
+
+    fn session_cookie(&self, session: &Session) -> String {
+        let signed = session::sign(&self.secret, session);
+        session::set_cookie(
+            SESSION_COOKIE,
+            &signed,
+            self.session_ttl,
+            self.secure_cookies,
+        )
+    }
+}
+
+/// Parse `name:password` or `name:sha256:<hex digest of the password>` entries separated by commas.
+fn parse_basic_users(users: &str) -> Result<Vec<BasicUser>, String> {
+    users
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let (name, password) = entry.split_once(':').ok_or_else(|| {
+                format!("AUTH_BASIC_USERS entry `{entry}` must be `name:password`")
+            })?;
+            let password_sha256 = match password.strip_prefix("sha256:") {
+                Some(hex) => decode_hex(hex)
+                    .filter(|digest| digest.len() == 32)
+                    .ok_or_else(|| format!("invalid SHA-256 digest of user `{name}`"))?,
+                None => Sha256::digest(password).to_vec(),
+            };
+            Ok(BasicUser {
+                name: name.to_string(),
+                password_sha256,
+            })
+        })
+        .collect()
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    // `from_str_radix` would accept a leading `+`.
+    if !hex.len().is_multiple_of(2) || !hex.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|idx| u8::from_str_radix(hex.get(idx..idx + 2)?, 16).ok())
+        .collect()
+}
+
+/// Compare without leaking the position of the first difference.
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+/// The user matching the `Authorization: Basic` header.
+fn basic_user<'a>(users: &'a [BasicUser], headers: &HeaderMap) -> Option<&'a str> {
+    let credentials = headers
+        .get("authorization")?
+        .to_str()
+        .ok()?
+        .strip_prefix("Basic ")?;
+    let credentials = String::from_utf8(STANDARD.decode(credentials.trim()).ok()?).ok()?;
+    let (name, password) = credentials.split_once(':')?;
+    let digest = Sha256::digest(password);
+    users
+        .iter()
+        .find(|user| user.name == name && constant_time_eq(&user.password_sha256, &digest))
+        .map(|user| user.name.as_str())
+}
+
+pub(crate) enum Gate {
+    /// Serve the request, adding the headers to the response.
+    Pass {
+        response_headers: Vec<(&'static str, String)>,
+    },
+    Respond(Box<Response<Body>>),
+}
+
+impl Gate {
+    fn respond(response: Response<Body>) -> Self {
+        Gate::Respond(Box::new(response))
+    }
+}
+
+/// Authenticate the request and replace its credentials with the upstream ones.
+pub(crate) async fn gate(req: &mut Request<Body>) -> Result<Gate, Error> {
+    let read_only_everyone = env("READ_ONLY").is_some_and(|read_only| read_only != "false");
+    match Config::from_env() {
+        Ok(config) => authenticate(config.as_ref(), read_only_everyone, req).await,
+        Err(err) => {
+            eprintln!("invalid authentication configuration: {err}");
+            text_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Invalid authentication configuration of webui-proxy",
+            )
+            .map(Gate::respond)
+        }
+    }
+}
+
+/// [`gate`] with the configuration read from the environment, `None` without authentication.
+async fn authenticate(
+    config: Option<&Config>,
+    read_only_everyone: bool,
+    req: &mut Request<Body>,
+) -> Result<Gate, Error> {
+    // Only the proxy tells the upstream and the access log who the user is.
+    req.headers_mut().remove(USER_HEADER);
+    let Some(config) = config else {
+        let reason = read_only_everyone.then(|| "This web UI is read-only".to_string());
+        return read_only_gate(req, reason, Vec::new());
+    };
+    let path = req.uri().path();
+    if path == LOGOUT_PATH {
+        let mut builder = Response::builder()
+            .status(StatusCode::SEE_OTHER)
+            .header("location", crate::base_path::prefixed("/"))
+            .header(
+                "set-cookie",
+                session::set_cookie(SESSION_COOKIE, "", 0, config.secure_cookies),
+            );
+        if let Mode::Basic(_) = config.mode {
+            builder = builder.header(
+                "set-cookie",
+                session::set_cookie(
+                    LOGGED_OUT_COOKIE,
+                    "1",
+                    LOGGED_OUT_TTL_SECS,
+                    config.secure_cookies,
+                ),
+            );
+        }
+        return builder
+            .body(Body::empty())
+            .map(Gate::respond)
+            .map_err(Error::from);
+    }
+    if let Mode::Oidc(oidc) = &config.mode
+        && path == oidc.callback_path()
+    {
+        return oidc::callback(config, oidc, req).await.map(Gate::respond);
+    }
+
+    let mut response_headers = Vec::new();
+    let session = match (config.session(req.headers()), &config.mode) {
+        (Some(session), _) => Some(session),
+        (None, Mode::Basic(users))
+            if session::cookie(req.headers(), LOGGED_OUT_COOKIE).is_none() =>
+        {
+            basic_user(users, req.headers()).map(|user| {
+                let session = config.new_session(user, false);
+                response_headers.push(("set-cookie", config.session_cookie(&session)));
+                session
+            })
+        }
+        (None, _) => None,
+    };
+    let Some(session) = session else {
+        return unauthenticated(config, req).await.map(Gate::respond);
+    };
+    let read_only = config.read_only(&session);
+    let user = session.sub;
+
+    // Each environment can use its own token, e.g. `UPSTREAM_TOKEN_PROD`.
+    let environment_token = crate::environments::find(req.uri().path()).and_then(|environment| {
+        env(&format!(
+            "UPSTREAM_TOKEN_{}",
+            environment.name.to_ascii_uppercase().replace('-', "_")
+        ))
+    });
+    let headers = req.headers_mut();
+    headers.remove("cookie");
+    headers.remove("authorization");
+    if let Some(token) = environment_token
+        .as_ref()
+        .or(config.upstream_token.as_ref())
+    {
+        let mut value = HeaderValue::from_str(&format!("Bearer {token}"))?;
+        value.set_sensitive(true);
+        headers.insert("authorization", value);
+    }
+    if let Ok(value) = HeaderValue::from_str(&user) {
+        headers.insert(USER_HEADER, value);
+        response_headers.push((SESSION_USER_HEADER, user.clone()));
+    }
+    let reason = if read_only_everyone {
+        Some("This web UI is read-only".to_string())
+    } else {
+        read_only.then(|| format!("Signed in as {user} with read-only access"))
+    };
+    read_only_gate(req, reason, response_headers)
+}
+
+/// Reject mutations when `reason` is set, otherwise announce it to the web UI.
+fn read_only_gate(
+    req: &Request<Body>,
+    reason: Option<String>,
+    mut response_headers: Vec<(&'static str, String)>,
+) -> Result<Gate, Error> {
+    let Some(mut reason) = reason else {
+        return Ok(Gate::Pass { response_headers });
+    };
+    // User names may contain characters not allowed in headers.
+    reason.retain(|c| (c.is_ascii_graphic() && c != '%') || c == ' ');
+    // `/api/<service>/<method>`, optionally prefixed by an environment.
+    let path = req.uri().path();
+    let is_mutation = path.starts_with("/api/")
+        && MUTATING_METHODS.iter().any(|method| {
+            path.strip_suffix(method)
+                .is_some_and(|prefix| prefix.ends_with('/'))
+        });
+    if is_mutation {
+        return grpc_error("7", &reason, (READ_ONLY_HEADER, &reason)).map(Gate::respond);
+    }
+    response_headers.push((READ_ONLY_HEADER, reason));
+    Ok(Gate::Pass { response_headers })
+}
+
+/// A trailers-only gRPC-web response. `message` must be printable ASCII without `%`.
+fn grpc_error(
+    code: &str,
+    message: &str,
+    (header_name, header_value): (&str, &str),
+) -> Result<Response<Body>, Error> {
+    Response::builder()
+        .header("content-type", "application/grpc-web+proto")
+        .header("grpc-status", code)
+        .header("grpc-message", message)
+        .header(header_name, header_value)
+        .body(Body::empty())
+        .map_err(Error::from)
+}
+
+async fn unauthenticated(config: &Config, req: &Request<Body>) -> Result<Response<Body>, Error> {
+    if req.uri().path().starts_with("/api") {
+        return grpc_error(
+            "16",
+            "Session expired, sign in again",
+            (LOGIN_REQUIRED_HEADER, "required"),
+        );
+    }
+    match &config.mode {
+        // The challenge makes the browser forget its cached credentials and ask for new ones.
+        Mode::Basic(_) => Response::builder()
+            .status(StatusCode::UNAUTHORIZED)
+            .header(
+                "www-authenticate",
+                "Basic realm=\"Obelisk\", charset=\"UTF-8\"",
+            )
+            .header(
+                "set-cookie",
+                session::set_cookie(LOGGED_OUT_COOKIE, "", 0, config.secure_cookies),
+            )
+            .body(Body::from("Authentication required"))
+            .map_err(Error::from),
+        Mode::Oidc(oidc) => {
+            let return_to = req
+                .uri()
+                .path_and_query()
+                .map_or("/", |path_and_query| path_and_query.as_str());
+            oidc::login(config, oidc, return_to).await
+        }
+    }
+}
+
+fn text_response(status: StatusCode, text: &str) -> Result<Response<Body>, Error> {
+    Response::builder()
+        .status(status)
+        .header("content-type", "text/plain; charset=utf-8")
+        .body(Body::from(text.to_string()))
+        .map_err(Error::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        pin::pin,
+        task::{Context, Poll, Waker},
+    };
+
+    const PASSWORD_SHA256: &str =
+        "5e884898da28047151d0e56f8dc6292773603d0d6aabbdd62a11ef721d1542d8";
+
+    #[test]
+    fn plain_and_hashed_basic_users() {
+        let users =
+            parse_basic_users(&format!(" alice:password , bob:sha256:{PASSWORD_SHA256},")).unwrap();
+        assert_eq!(2, users.len());
+        assert_eq!("alice", users[0].name);
+        assert_eq!("bob", users[1].name);
+        assert_eq!(users[0].password_sha256, users[1].password_sha256);
+        assert_eq!(
+            decode_hex(PASSWORD_SHA256).unwrap(),
+            users[1].password_sha256
+        );
+    }
+
+    #[test]
+    fn password_may_contain_colons() {
+        let users = parse_basic_users("alice:pass:word").unwrap();
+        assert_eq!(
+            Sha256::digest("pass:word").to_vec(),
+            users[0].password_sha256
+        );
+    }
+
+    #[test]
+    fn invalid_basic_users() {
+        assert!(parse_basic_users("alice").is_err());
+        assert!(parse_basic_users("alice:sha256:abcd").is_err());
+        assert!(parse_basic_users(&format!("alice:sha256:{}zz", &PASSWORD_SHA256[2..])).is_err());
+    }
+
+    #[test]
+    fn hex_decoding() {
+        assert_eq!(Some(vec![0x00, 0xab, 0xFF]), decode_hex("00abFF"));
+        assert_eq!(Some(Vec::new()), decode_hex(""));
+        assert_eq!(None, decode_hex("abc"));
+        assert_eq!(None, decode_hex("0g"));
+        assert_eq!(None, decode_hex("+1"));
+        // Multi-byte characters must not panic when slicing.
+        assert_eq!(None, decode_hex("aé0"));
+    }
+
+    #[test]
+    fn constant_time_comparison() {
+        assert!(constant_time_eq(b"secret", b"secret"));
+        assert!(constant_time_eq(b"", b""));
+        assert!(!constant_time_eq(b"secret", b"secreT"));
+        assert!(!constant_time_eq(b"secret", b"secret!"));
+        assert!(!constant_time_eq(b"", b"s"));
+    }
+
+    #[test]
+    fn basic_credentials() {
+        let users = parse_basic_users("alice:password").unwrap();
+        let headers = |credentials: &str| {
+            let mut headers = HeaderMap::new();
+            let value = format!("Basic {}", STANDARD.encode(credentials));
+            headers.insert("authorization", HeaderValue::from_str(&value).unwrap());
+            headers
+        };
+        assert_eq!(
+            Some("alice"),
+            basic_user(&users, &headers("alice:password"))
+        );
+        assert_eq!(None, basic_user(&users, &headers("alice:Password")));
+        assert_eq!(None, basic_user(&users, &headers("bob:password")));
+        assert_eq!(None, basic_user(&users, &HeaderMap::new()));
+    }
+
+    const SECRET: &[u8] = b"0123456789abcdef0123456789abcdef";
+
+    fn basic_config() -> Config {
+        Config {
+            secret: SECRET.to_vec(),
+            session_ttl: DEFAULT_SESSION_TTL_SECS,
+            secure_cookies: true,
+            upstream_token: None,
+            read_only_users: Vec::new(),
+            mode: Mode::Basic(parse_basic_users("alice:password").unwrap()),
+        }
+    }
+
+    /// Requests authenticated with a session or without authentication complete without I/O.
+    fn authenticate_now(config: Option<&Config>, req: &mut Request<Body>) -> Gate {
+        let mut cx = Context::from_waker(Waker::noop());
+        match pin!(authenticate(config, false, req)).poll(&mut cx) {
+            Poll::Ready(gate) => gate.unwrap(),
+            Poll::Pending => panic!("no I/O expected"),
+        }
+    }
+
+    fn request(session_user: Option<&str>) -> Request<Body> {
+        let mut builder = Request::post("/api/obelisk.ExecutionRepository/ListExecutions")
+            .header(USER_HEADER, "admin");
+        if let Some(user) = session_user {
+            let session = Session {
+                sub: user.to_string(),
+                exp: session::now() + 60,
+                read_only: false,
+            };
+            builder = builder.header(
+                "cookie",
+                format!("{SESSION_COOKIE}={}", session::sign(SECRET, &session)),
+            );
+        }
+        builder.body(Body::empty()).unwrap()
+    }
+
+    #[test]
+    fn spoofed_user_is_removed_without_authentication() {
+        let mut req = request(None);
+        assert!(matches!(
+            authenticate_now(None, &mut req),
+            Gate::Pass { .. }
+        ));
+        assert_eq!(None, req.headers().get(USER_HEADER));
+    }
+
+    #[test]
+    fn signed_in_user_replaces_the_spoofed_one() {
+        let config = basic_config();
+        let mut req = request(Some("alice"));
+        assert!(matches!(
+            authenticate_now(Some(&config), &mut req),
+            Gate::Pass { .. }
+        ));
+        assert_eq!("alice", req.headers().get(USER_HEADER).unwrap());
+    }
+
+    #[test]
+    fn signed_in_user_is_announced_to_the_web_ui() {
+        let config = basic_config();
+        let mut req = request(Some("alice"));
+        let Gate::Pass { response_headers } = authenticate_now(Some(&config), &mut req) else {
+            panic!("expected the request to pass");
+        };
+        assert_eq!(
+            vec![(SESSION_USER_HEADER, "alice".to_string())],
+            response_headers
+        );
+    }
+
+    #[test]
+    fn spoofed_user_is_removed_when_the_name_is_not_a_header_value() {
+        let config = basic_config();
+        let mut req = request(Some("al\nice"));
+        assert!(matches!(
+            authenticate_now(Some(&config), &mut req),
+            Gate::Pass { .. }
+        ));
+        assert_eq!(None, req.headers().get(USER_HEADER));
+    }
+
+    #[test]
+    fn spoofed_user_is_removed_from_unauthenticated_requests() {
+        let config = basic_config();
+        let mut req = request(None);
+        assert!(matches!(
+            authenticate_now(Some(&config), &mut req),
+            Gate::Respond(_)
+        ));
+        assert_eq!(None, req.headers().get(USER_HEADER));
+    }
+
+    #[test]
+    fn read_only_users_apply_to_existing_sessions() {
+        let mut config = basic_config();
+        let read_only_header = |config: &Config| {
+            let mut req = request(Some("alice"));
+            match authenticate_now(Some(config), &mut req) {
+                Gate::Pass { response_headers } => response_headers
+                    .into_iter()
+                    .find(|(name, _)| *name == READ_ONLY_HEADER)
+                    .map(|(_, reason)| reason),
+                Gate::Respond(_) => panic!("expected the request to pass"),
+            }
+        };
+        assert_eq!(None, read_only_header(&config));
+        config.read_only_users = vec!["alice".to_string()];
+        assert_eq!(
+            Some("Signed in as alice with read-only access".to_string()),
+            read_only_header(&config)
+        );
+    }
+}
@@ -0,0 +1,430 @@
+//! `OpenID` Connect authorization code flow.
+//!
+//! The ID token is received directly from the token endpoint, so its signature is not checked,
+//! relying on the TLS connection to the issuer as allowed by `OpenID` Connect Core 3.1.3.7.
+
+use super::{Config, required_env, session, text_response};
+use base64::{
+    Engine as _,
+    engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD},
+};
+use serde::{Deserialize, Serialize};
+use wstd::http::{Body, Client, Error, Request, Response, StatusCode, Uri};
+
+const LOGIN_COOKIE: &str = "obelisk_webui_oidc";
+/// Time the user has to sign in at the identity provider.
+const LOGIN_TTL_SECS: u64 = 10 * 60;
+
+pub(super) struct OidcConfig {
+    issuer: String,
+    client_id: String,
+    client_secret: String,
+    redirect_url: String,
+    callback_path: String,
+    scopes: String,
+    user_claim: String,
+    /// `claim=value` granting read-only access, e.g. `groups=support`.
+    read_only_claim: Option<(String, String)>,
+}
+
+impl OidcConfig {
+    pub(super) fn from_env() -> Result<Self, String> {
+        let redirect_url = required_env("OIDC_REDIRECT_URL")?;
+        let read_only_claim = super::env("OIDC_READ_ONLY_CLAIM")
+            .map(|claim| {
+                claim
+                    .split_once('=')
+                    .map(|(name, value)| (name.to_string(), value.to_string()))
+                    .ok_or_else(|| {
+                        format!("OIDC_READ_ONLY_CLAIM must be `claim=value`, got `{claim}`")
+                    })
+            })
+            .transpose()?;
+        let callback_path = crate::base_path::unprefixed(
+            redirect_url
+                .parse::<Uri>()
+                .map_err(|err| format!("OIDC_REDIRECT_URL is not a valid URL - {err}"))?
+                .path(),
+        );
+        Ok(OidcConfig {
+            issuer: required_env("OIDC_ISSUER_URL")?
+                .trim_end_matches('/')
+                .to_string(),
+            client_id: required_env("OIDC_CLIENT_ID")?,
+            client_secret: required_env("OIDC_CLIENT_SECRET")?,
+            redirect_url,
+            callback_path,
+            scopes: super::env("OIDC_SCOPES").unwrap_or_else(|| "openid email profile".to_string()),
+            user_claim: super::env("OIDC_USER_CLAIM").unwrap_or_else(|| "email".to_string()),
+            read_only_claim,
+        })
+    }
+
+    pub(super) fn callback_path(&self) -> &str {
+        &self.callback_path
+    }
+}
+
+/// Subset of the `OpenID` provider metadata.
+#[derive(Deserialize)]
+struct Discovery {
+    authorization_endpoint: String,
+    token_endpoint: String,
+}
+
+/// Kept in a signed cookie between the redirect to the provider and the callback.
+#[derive(Serialize, Deserialize)]
+struct LoginState {
+    state: String,
+    nonce: String,
+    return_to: String,
+    exp: u64,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    id_token: String,
+}
+
+#[derive(Deserialize)]
+struct IdTokenClaims {
+    iss: String,
+    aud: serde_json::Value,
+    exp: u64,
+    nonce: Option<String>,
+    sub: String,
+    #[serde(flatten)]
+    other: serde_json::Map<String, serde_json::Value>,
+}
+
+async fn discover(oidc: &OidcConfig) -> Result<Discovery, Error> {
+    let req = Request::get(format!("{}/.well-known/openid-configuration", oidc.issuer))
+        .body(Body::empty())?;
+    let resp = Client::new().send(req).await?;
+    if !resp.status().is_success() {
+        return Err(Error::msg(format!(
+            "discovery failed with status {}",
+            resp.status()
+        )));
+    }
+    resp.into_body().json().await
+}
+
+fn encode_query(pairs: &[(&str, &str)]) -> String {
+    form_urlencoded::Serializer::new(String::new())
+        .extend_pairs(pairs)
+        .finish()
+}
+
+fn bad_gateway(err: &Error) -> Result<Response<Body>, Error> {
+    eprintln!("OpenID Connect sign-in failed: {err:?}");
+    text_response(
+        StatusCode::BAD_GATEWAY,
+        "Sign-in at the identity provider failed",
+    )
+}
+
+/// Redirect to the provider, coming back to `return_to` after the callback.
+pub(super) async fn login(
+    config: &Config,
+    oidc: &OidcConfig,
+    return_to: &str,
+) -> Result<Response<Body>, Error> {
+    let discovery = match discover(oidc).await {
+        Ok(discovery) => discovery,
+        Err(err) => return bad_gateway(&err),
+    };
+    let login_state = LoginState {
+        state: session::random_token(),
+        nonce: session::random_token(),
+        return_to: return_to.to_string(),
+        exp: session::now() + LOGIN_TTL_SECS,
+    };
+    let location = format!(
+        "{}?{}",
+        discovery.authorization_endpoint,
+        encode_query(&[
+            ("response_type", "code"),
+            ("client_id", &oidc.client_id),
+            ("redirect_uri", &oidc.redirect_url),
+            ("scope", &oidc.scopes),
+            ("state", &login_state.state),
+            ("nonce", &login_state.nonce),
+        ])
+    );
+    Response::builder()
+        .status(StatusCode::FOUND)
+        .header("location", location)
+        .header(
+            "set-cookie",
+            session::set_cookie(
+                LOGIN_COOKIE,
+                &session::sign(&config.secret, &login_state),
+                LOGIN_TTL_SECS,
+                config.secure_cookies,
+            ),
+        )
+        .body(Body::empty())
+        .map_err(Error::from)
+}
+
+/// Exchange the authorization code and start a session.
+pub(super) async fn callback(
+    config: &Config,
+    oidc: &OidcConfig,
+    req: &Request<Body>,
+) -> Result<Response<Body>, Error> {
+    let login_state = session::cookie(req.headers(), LOGIN_COOKIE)
+        .and_then(|signed| session::verify::<LoginState>(&config.secret, signed))
+        .filter(|login_state| login_state.exp > session::now());
+    let Some(login_state) = login_state else {
+        return text_response(
+            StatusCode::BAD_REQUEST,
+            "Sign-in expired, reload the page to try again",
+        );
+    };
+    let query = req.uri().query().unwrap_or_default();
+    let param = |name: &str| {
+        form_urlencoded::parse(query.as_bytes())
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.into_owned())
+    };
+    if let Some(error) = param("error") {
+        eprintln!("identity provider returned error `{error}`");
+        return text_response(StatusCode::FORBIDDEN, "Sign-in was rejected");
+    }
+    if param("state").as_deref() != Some(login_state.state.as_str()) {
+        return text_response(StatusCode::BAD_REQUEST, "Sign-in state mismatch");
+    }
+    let Some(code) = param("code") else {
+        return text_response(StatusCode::BAD_REQUEST, "Missing authorization code");
+    };
+
+    let (user, read_only) = match exchange_code(oidc, &code, &login_state.nonce).await {
+        Ok(signed_in) => signed_in,
+        Err(err) => return bad_gateway(&err),
+    };
+    Response::builder()
+        .status(StatusCode::SEE_OTHER)
+        .header(
+            "location",
+            crate::base_path::prefixed(safe_return_to(&login_state.return_to)),
+        )
+        .header(
+            "set-cookie",
+            config.session_cookie(&config.new_session(&user, read_only)),
+        )
+        .header(
+            "set-cookie",
+            session::set_cookie(LOGIN_COOKIE, "", 0, config.secure_cookies),
+        )
+        .body(Body::empty())
+        .map_err(Error::from)
+}
+
+/// Only paths of this origin, browsers read `/\\host` like `//host`.
+fn safe_return_to(return_to: &str) -> &str {
+    if return_to.starts_with('/') && !return_to.starts_with("//") && !return_to.starts_with("/\\") {
+        return_to
+    } else {
+        "/"
+    }
+}
+
+/// Redeem the code at the token endpoint, returning the name of the user and whether the
+/// read-only claim is present.
+async fn exchange_code(
+    oidc: &OidcConfig,
+    code: &str,
+    nonce: &str,
+) -> Result<(String, bool), Error> {
+    let discovery = discover(oidc).await?;
+    // `client_secret_basic` expects form encoded credentials.
+    let form_encode =
+        |value: &str| form_urlencoded::byte_serialize(value.as_bytes()).collect::<String>();
+    let credentials = STANDARD.encode(format!(
+        "{}:{}",
+        form_encode(&oidc.client_id),
+        form_encode(&oidc.client_secret)
+    ));
+    let req = Request::post(discovery.token_endpoint)
+        .header("content-type", "application/x-www-form-urlencoded")
+        .header("accept", "application/json")
+        .header("authorization", format!("Basic {credentials}"))
+        .body(Body::from(encode_query(&[
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", &oidc.redirect_url),
+        ])))?;
+    let resp = Client::new().send(req).await?;
+    if !resp.status().is_success() {
+        return Err(Error::msg(format!(
+            "token endpoint responded with status {}",
+            resp.status()
+        )));
+    }
+    let token: TokenResponse = resp.into_body().json().await?;
+    verify_id_token(oidc, &token.id_token, nonce, session::now())
+}
+
+/// Check the claims of the ID token, returning the name of the user and whether the
+/// read-only claim is present.
+fn verify_id_token(
+    oidc: &OidcConfig,
+    id_token: &str,
+    nonce: &str,
+    now: u64,
+) -> Result<(String, bool), Error> {
+    let payload = id_token
+        .split('.')
+        .nth(1)
+        .ok_or_else(|| Error::msg("ID token is not a JWT"))?;
+    let claims: IdTokenClaims = serde_json::from_slice(&URL_SAFE_NO_PAD.decode(payload)?)?;
+    let audience_matches = match &claims.aud {
+        serde_json::Value::String(aud) => *aud == oidc.client_id,
+        serde_json::Value::Array(auds) => auds.iter().any(|aud| *aud == *oidc.client_id),
+        _ => false,
+    };
+    if claims.iss.trim_end_matches('/') != oidc.issuer {
+        return Err(Error::msg(format!("unexpected issuer `{}`", claims.iss)));
+    }
+    if !audience_matches {
+        return Err(Error::msg("ID token was issued for another client"));
+    }
+    if claims.exp <= now {
+        return Err(Error::msg("ID token has expired"));
+    }
+    if claims.nonce.as_deref() != Some(nonce) {
+        return Err(Error::msg("ID token nonce mismatch"));
+    }
+    let read_only =
+        oidc.read_only_claim
+            .as_ref()
+            .is_some_and(|(name, value)| match claims.other.get(name) {
+                Some(serde_json::Value::String(claim)) => claim == value,
+                Some(serde_json::Value::Array(claims)) => claims.iter().any(|claim| claim == value),
+                _ => false,
+            });
+    let user = claims
+        .other
+        .get(&oidc.user_claim)
+        .and_then(serde_json::Value::as_str)
+        .map_or(claims.sub, ToString::to_string);
+    Ok((user, read_only))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const NOW: u64 = 1_700_000_000;
+
+    fn config() -> OidcConfig {
+        OidcConfig {
+            issuer: "https://idp.example.com".to_string(),
+            client_id: "webui".to_string(),
+            client_secret: "secret".to_string(),
+            redirect_url: "https://obelisk.example.com/auth/callback".to_string(),
+            callback_path: "/auth/callback".to_string(),
+            scopes: "openid email".to_string(),
+            user_claim: "email".to_string(),
+            read_only_claim: Some(("groups".to_string(), "support".to_string())),
+        }
+    }
+
+    fn claims() -> serde_json::Value {
+        json!({
+            "iss": "https://idp.example.com/",
+            "aud": "webui",
+            "exp": NOW + 60,
+            "nonce": "n0nce",
+            "sub": "1234",
+            "email": "alice@example.com",
+        })
+    }
+
+    fn id_token(claims: &serde_json::Value) -> String {
+        let payload = URL_SAFE_NO_PAD.encode(serde_json::to_vec(claims).unwrap());
+        format!("eyJhbGciOiJSUzI1NiJ9.{payload}.signature")
+    }
+
+    fn verify(claims: &serde_json::Value) -> Result<(String, bool), Error> {
+        verify_id_token(&config(), &id_token(claims), "n0nce", NOW)
+    }
+
+    fn with(key: &str, value: serde_json::Value) -> serde_json::Value {
+        let mut claims = claims();
+        claims[key] = value;
+        claims
+    }
+
+    #[test]
+    fn valid_id_token() {
+        assert_eq!(
+            ("alice@example.com".to_string(), false),
+            verify(&claims()).unwrap()
+        );
+        let audiences = with("aud", json!(["other", "webui"]));
+        assert!(verify(&audiences).is_ok());
+    }
+
+    #[test]
+    fn user_falls_back_to_sub() {
+        let mut claims = claims();
+        claims.as_object_mut().unwrap().remove("email");
+        assert_eq!("1234", verify(&claims).unwrap().0);
+    }
+
+    #[test]
+    fn read_only_claim() {
+        assert!(verify(&with("groups", json!("support"))).unwrap().1);
+        assert!(
+            verify(&with("groups", json!(["dev", "support"])))
+                .unwrap()
+                .1
+        );
+        assert!(!verify(&with("groups", json!(["dev"]))).unwrap().1);
+    }
+
+    #[test]
+    fn issuer_must_match() {
+        assert!(verify(&with("iss", json!("https://evil.example.com"))).is_err());
+    }
+
+    #[test]
+    fn audience_must_match() {
+        assert!(verify(&with("aud", json!("other"))).is_err());
+        assert!(verify(&with("aud", json!(["other"]))).is_err());
+        assert!(verify(&with("aud", json!(null))).is_err());
+    }
+
+    #[test]
+    fn expired_token_is_rejected() {
+        assert!(verify(&with("exp", json!(NOW))).is_err());
+        assert!(verify(&with("exp", json!(NOW - 1))).is_err());
+    }
+
+    #[test]
+    fn nonce_must_match() {
+        assert!(verify(&with("nonce", json!("other"))).is_err());
+        let mut claims = claims();
+        claims.as_object_mut().unwrap().remove("nonce");
+        assert!(verify(&claims).is_err());
+    }
+
+    #[test]
+    fn malformed_token_is_rejected() {
+        assert!(verify_id_token(&config(), "not a jwt", "n0nce", NOW).is_err());
+        assert!(verify_id_token(&config(), "header.!!!.signature", "n0nce", NOW).is_err());
+    }
+
+    #[test]
+    fn return_to_stays_on_this_origin() {
+        assert_eq!("/executions?page=2", safe_return_to("/executions?page=2"));
+        assert_eq!("/", safe_return_to("//evil.example.com/"));
+        assert_eq!("/", safe_return_to("/\\evil.example.com/"));
+        assert_eq!("/", safe_return_to("https://evil.example.com/"));
+        assert_eq!("/", safe_return_to(""));
+    }
+}
@@ -0,0 +1,122 @@
+//! Cookies signed with HMAC-SHA256, formatted as `base64url(json).base64url(tag)`.
+
+use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
+use hmac::{Hmac, Mac as _};
+use serde::{Serialize, de::DeserializeOwned};
+use sha2::Sha256;
+use std::time::{SystemTime, UNIX_EPOCH};
+use wstd::http::HeaderMap;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Seconds since the Unix epoch.
+pub(super) fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("wall clock is after the epoch")
+        .as_secs()
+}
+
+pub(super) fn random_token() -> String {
+    let mut bytes = [0; 16];
+    wstd::rand::get_random_bytes(&mut bytes);
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+fn mac(secret: &[u8], payload: &str) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    mac.update(payload.as_bytes());
+    mac
+}
+
+pub(super) fn sign<T: Serialize>(secret: &[u8], value: &T) -> String {
+    let payload = URL_SAFE_NO_PAD.encode(serde_json::to_vec(value).expect("serializable"));
+    let tag = URL_SAFE_NO_PAD.encode(mac(secret, &payload).finalize().into_bytes());
+    format!("{payload}.{tag}")
+}
+
+/// Decode a value produced by [`sign`], `None` if it was tampered with.
+pub(super) fn verify<T: DeserializeOwned>(secret: &[u8], signed: &str) -> Option<T> {
+    let (payload, tag) = signed.split_once('.')?;
+    let tag = URL_SAFE_NO_PAD.decode(tag).ok()?;
+    mac(secret, payload).verify_slice(&tag).ok()?;
+    serde_json::from_slice(&URL_SAFE_NO_PAD.decode(payload).ok()?).ok()
+}
+
+/// Value of a cookie sent by the browser.
+pub(super) fn cookie<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers
+        .get_all("cookie")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .find_map(|pair| {
+            let (key, value) = pair.trim().split_once('=')?;
+            (key == name).then_some(value)
+        })
+}
+
+/// `Set-Cookie` value of an HTTP-only cookie, `max_age` of zero removes it.
+pub(super) fn set_cookie(name: &str, value: &str, max_age: u64, secure: bool) -> String {
+    let secure = if secure { "; Secure" } else { "" };
+    format!("{name}={value}; Path=/; Max-Age={max_age}; HttpOnly; SameSite=Lax{secure}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use wstd::http::HeaderValue;
+
+    const SECRET: &[u8] = b"0123456789abcdef0123456789abcdef";
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct Value {
+        sub: String,
+        exp: u64,
+    }
+
+    fn value() -> Value {
+        Value {
+            sub: "alice".to_string(),
+            exp: 42,
+        }
+    }
+
+    #[test]
+    fn signed_value_round_trips() {
+        let signed = sign(SECRET, &value());
+        assert_eq!(Some(value()), verify(SECRET, &signed));
+    }
+
+    #[test]
+    fn tampering_is_rejected() {
+        let signed = sign(SECRET, &value());
+        let (payload, tag) = signed.split_once('.').unwrap();
+
+        let forged = URL_SAFE_NO_PAD.encode(br#"{"sub":"admin","exp":42}"#);
+        assert_eq!(None, verify::<Value>(SECRET, &format!("{forged}.{tag}")));
+        let mut tag = URL_SAFE_NO_PAD.decode(tag).unwrap();
+        tag[0] ^= 1;
+        let tag = URL_SAFE_NO_PAD.encode(tag);
+        assert_eq!(None, verify::<Value>(SECRET, &format!("{payload}.{tag}")));
+        assert_eq!(None, verify::<Value>(SECRET, payload));
+        assert_eq!(None, verify::<Value>(SECRET, &format!("{payload}.")));
+        assert_eq!(None, verify::<Value>(b"another secret", &signed));
+    }
+
+    #[test]
+    fn cookie_lookup() {
+        let mut headers = HeaderMap::new();
+        headers.append(
+            "cookie",
+            HeaderValue::from_static("other=1; xsession=forged;session=a.b"),
+        );
+        headers.append("cookie", HeaderValue::from_static("last=x=y"));
+        assert_eq!(Some("a.b"), cookie(&headers, "session"));
+        assert_eq!(Some("1"), cookie(&headers, "other"));
+        assert_eq!(Some("x=y"), cookie(&headers, "last"));
+        assert_eq!(None, cookie(&headers, "missing"));
+        assert_eq!(None, cookie(&HeaderMap::new(), "session"));
+    }
+}
@@ -0,0 +1,93 @@
+//! Serving the UI under a path prefix, e.g. `BASE_PATH=/obelisk` behind a shared ingress.
+//!
+//! Requests may arrive with or without the prefix, depending on whether the ingress strips it.
+//! The embedded `index.html` gets prefixed asset URLs and a [`META_NAME`] element telling the
+//! UI where it lives.
+
+use std::borrow::Cow;
+use wstd::http::{Body, Request, Uri};
+
+const META_NAME: &str = "obelisk-base-path";
+
+/// `BASE_PATH` without the trailing slash, empty when serving from the root.
+pub(crate) fn base_path() -> String {
+    let Ok(base_path) = std::env::var("BASE_PATH") else {
+        return String::new();
+    };
+    let base_path = base_path.trim_matches('/');
+    // Written into `index.html` unescaped.
+    if !base_path.bytes().all(|byte| {
+        byte.is_ascii_alphanumeric() || matches!(byte, b'/' | b'-' | b'_' | b'.' | b'~')
+    }) {
+        eprintln!("ignoring invalid BASE_PATH `{base_path}`");
+        return String::new();
+    }
+    if base_path.is_empty() {
+        String::new()
+    } else {
+        format!("/{base_path}")
+    }
+}
+
+/// Strip the prefix, so that routing never sees it.
+pub(crate) fn strip(req: &mut Request<Body>) {
+    let base_path = base_path();
+    if base_path.is_empty() {
+        return;
+    }
+    let Some(rest) = req
+        .uri()
+        .path_and_query()
+        .and_then(|path_and_query| path_and_query.as_str().strip_prefix(base_path.as_str()))
+    else {
+        return;
+    };
+    let rest = match rest.chars().next() {
+        None => "/".to_string(),
+        Some('/') => rest.to_string(),
+        Some('?') => format!("/{rest}"),
+        // Another path sharing the beginning, e.g. `/obelisk-docs`.
+        Some(_) => return,
+    };
+    let mut parts = req.uri().clone().into_parts();
+    parts.path_and_query = rest.parse().ok();
+    if let Ok(uri) = Uri::from_parts(parts) {
+        *req.uri_mut() = uri;
+    }
+}
+
+/// Public path of a path the proxy serves, e.g. for redirects.
+pub(crate) fn prefixed(path: &str) -> String {
+    format!("{}{path}", base_path())
+}
+
+/// The path as routed, e.g. of the `OIDC_REDIRECT_URL`.
+pub(crate) fn unprefixed(path: &str) -> String {
+    let base_path = base_path();
+    match path.strip_prefix(base_path.as_str()) {
+        Some(rest) if !base_path.is_empty() && rest.starts_with('/') => rest.to_string(),
+        _ => path.to_string(),
+    }
+}
+
+/// Point the absolute asset URLs written by trunk below the prefix.
+pub(crate) fn rewrite_index_html<'a>(html: &'a str, asset_paths: &[&str]) -> Cow<'a, str> {
+    let base_path = base_path();
+    if base_path.is_empty() {
+        return Cow::Borrowed(html);
+    }
+    let mut html = html.to_string();
+    for asset_path in asset_paths {
+        for quote in ['"', '\''] {
+            html = html.replace(
+                &format!("{quote}{asset_path}{quote}"),
+                &format!("{quote}{base_path}{asset_path}{quote}"),
+            );
+        }
+    }
+    Cow::Owned(html.replacen(
+        "<head>",
+        &format!("<head>\n    <meta name=\"{META_NAME}\" content=\"{base_path}\" />"),
+        1,
+    ))
+}
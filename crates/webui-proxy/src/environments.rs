@@ -0,0 +1,67 @@
+//! Named Obelisk servers, e.g. `ENVIRONMENTS=dev=http://127.0.0.1:5005,prod=https://obelisk.example.com:5005`.
+//!
+//! `/api/<name>/…` is forwarded to the server of the environment, `/api/…` to `TARGET_URL`.
+//! The web UI lists the names at [`ENVIRONMENTS_PATH`] to offer a switcher.
+
+use wstd::http::{Body, Error, Response};
+
+pub(crate) const ENVIRONMENTS_PATH: &str = "/api/environments";
+
+pub(crate) struct Environment {
+    pub(crate) name: String,
+    pub(crate) target_url: String,
+}
+
+/// Names cannot contain dots, so they never clash with gRPC service names like `obelisk.ExecutionRepository`.
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .bytes()
+            .all(|byte| byte.is_ascii_alphanumeric() || byte == b'-' || byte == b'_')
+}
+
+pub(crate) fn environments() -> Vec<Environment> {
+    let Ok(environments) = std::env::var("ENVIRONMENTS") else {
+        return Vec::new();
+    };
+    environments
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .filter_map(|entry| {
+            let environment = entry
+                .split_once('=')
+                .filter(|(name, target_url)| is_valid_name(name) && !target_url.is_empty())
+                .map(|(name, target_url)| Environment {
+                    name: name.to_string(),
+                    target_url: target_url.trim_end_matches('/').to_string(),
+                });
+            if environment.is_none() {
+                eprintln!("ignoring invalid ENVIRONMENTS entry `{entry}`, expected `name=url`");
+            }
+            environment
+        })
+        .collect()
+}
+
+/// The environment addressed by an `/api/<name>/…` path.
+pub(crate) fn find(path: &str) -> Option<Environment> {
+    let name = path.strip_prefix("/api/")?.split('/').next()?;
+    environments()
+        .into_iter()
+        .find(|environment| environment.name == name)
+}
+
+pub(crate) fn list_response() -> Result<Response<Body>, Error> {
+    let names: Vec<String> = environments()
+        .into_iter()
+        .map(|environment| environment.name)
+        .collect();
+    Response::builder()
+        .header("content-type", "application/json")
+        .header("cache-control", "no-cache")
+        .body(Body::from_json(
+            &serde_json::json!({ "environments": names }),
+        )?)
+        .map_err(Error::from)
+}
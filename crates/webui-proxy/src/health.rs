@@ -0,0 +1,72 @@
+//! Probes for load balancers, served without authentication.
+//!
+//! [`HEALTHZ_PATH`] only reports that the proxy is up, [`READYZ_PATH`] also calls
+//! `GetCurrentDeploymentId` of the Obelisk server at `TARGET_URL`.
+
+use std::time::Duration;
+use wstd::http::{Body, Client, Error, Request, Response, StatusCode};
+
+pub(crate) const HEALTHZ_PATH: &str = "/healthz";
+pub(crate) const READYZ_PATH: &str = "/readyz";
+const READINESS_METHOD: &str = "/obelisk.DeploymentRepository/GetCurrentDeploymentId";
+const READINESS_TIMEOUT: Duration = Duration::from_secs(2);
+
+pub(crate) fn healthz() -> Result<Response<Body>, Error> {
+    crate::json_response(StatusCode::OK, &serde_json::json!({ "status": "ok" }))
+}
+
+pub(crate) async fn readyz() -> Result<Response<Body>, Error> {
+    match check_upstream().await {
+        Ok(()) => crate::json_response(StatusCode::OK, &serde_json::json!({ "status": "ready" })),
+        Err(reason) => crate::json_response(
+            StatusCode::SERVICE_UNAVAILABLE,
+            &serde_json::json!({ "status": "unavailable", "reason": reason }),
+        ),
+    }
+}
+
+async fn check_upstream() -> Result<(), String> {
+    let target_url = crate::target_url()?;
+    let mut client = Client::new();
+    client.set_connect_timeout(READINESS_TIMEOUT);
+    client.set_first_byte_timeout(READINESS_TIMEOUT);
+    client.set_between_bytes_timeout(READINESS_TIMEOUT);
+    let mut req = Request::post(format!("{target_url}{READINESS_METHOD}"))
+        .header("content-type", "application/grpc-web+proto")
+        .header("x-grpc-web", "1");
+    if let Ok(token) = std::env::var("UPSTREAM_TOKEN") {
+        req = req.header("authorization", format!("Bearer {token}"));
+    }
+    // The empty request message in an uncompressed data frame.
+    let req = req
+        .body(Body::from(vec![0_u8; 5]))
+        .map_err(|err| err.to_string())?;
+    let mut resp = client
+        .send(req)
+        .await
+        .map_err(|err| format!("cannot reach TARGET_URL: {err}"))?;
+    if !resp.status().is_success() {
+        return Err(format!("upstream responded with HTTP {}", resp.status()));
+    }
+    // Trailers-only responses carry the status in the headers.
+    let header_status = resp
+        .headers()
+        .get("grpc-status")
+        .and_then(|status| status.to_str().ok())
+        .map(ToString::to_string);
+    let grpc_status = if header_status.is_some() {
+        header_status
+    } else {
+        let body = resp
+            .body_mut()
+            .contents()
+            .await
+            .map_err(|err| format!("cannot read the upstream response: {err}"))?;
+        crate::access_log::trailer_grpc_status(body)
+    };
+    match grpc_status.as_deref() {
+        Some("0") => Ok(()),
+        Some(status) => Err(format!("upstream responded with grpc-status {status}")),
+        None => Err("upstream response carries no grpc-status".to_string()),
+    }
+}
@@ -1,50 +1,150 @@
-use wstd::http::{Body, Client, Error, HeaderValue, Request, Response, Uri};
+mod access_log;
+mod auth;
+mod base_path;
+mod environments;
+mod health;
+mod security;
+
+use std::{
+    borrow::Cow,
+    hash::{DefaultHasher, Hasher as _},
+    sync::OnceLock,
+};
+use wstd::http::{Body, Client, Error, HeaderValue, Request, Response, StatusCode, Uri};
+
+/// A file of `webui/dist`, see `build.rs`.
+struct Asset {
+    path: &'static str,
+    /// Hash of the uncompressed content.
+    hash: &'static str,
+    identity: &'static [u8],
+    gzip: Option<&'static [u8]>,
+    brotli: Option<&'static [u8]>,
+}
+
+include!(concat!(env!("OUT_DIR"), "/assets.rs"));
 
 #[wstd::http_server]
-async fn main(server_req: Request<Body>) -> Result<Response<Body>, Error> {
-    match server_req.uri().path_and_query().unwrap().as_str() {
-        "/webui_bg.wasm" => {
-            let content = get_webui_bg_wasm();
-            let content_type = "application/wasm";
-            write_static_response(content, content_type)
-        }
-        "/webui.js" => {
-            let content = get_webui_js();
-            let content_type = "text/javascript";
-            write_static_response(content, content_type)
-        }
-        "/styles.css" => {
-            let content = get_styles_css();
-            let content_type = "text/css";
-            write_static_response(content, content_type)
-        }
-        "/syntect.css" => {
-            let content = get_syntect_css();
-            let content_type = "text/css";
-            write_static_response(content, content_type)
-        }
-        api_prefixed_path if api_prefixed_path.starts_with("/api") => {
-            // Remove /api prefix
-            let target_url =
-                std::env::var("TARGET_URL").expect("missing environment variable TARGET_URL");
-            let target_url: Uri = format!(
-                "{target_url}{}",
-                api_prefixed_path
-                    .strip_prefix("/api")
-                    .expect("checked above")
-            )
-            .parse()
-            .expect("final target url should be parseable");
-            proxy(server_req, target_url).await
+async fn main(mut server_req: Request<Body>) -> Result<Response<Body>, Error> {
+    base_path::strip(&mut server_req);
+    let mut resp = serve(server_req).await?;
+    security::append_headers(resp.headers_mut(), index_html());
+    Ok(resp)
+}
+
+/// `index.html` as served, empty in debug mode. Rewritten once per instance.
+fn index_html() -> &'static str {
+    static INDEX_HTML: OnceLock<Cow<'static, str>> = OnceLock::new();
+    INDEX_HTML.get_or_init(|| {
+        let Some(index) = ASSETS.iter().find(|asset| asset.path == "/index.html") else {
+            return Cow::Borrowed("");
+        };
+        let html = std::str::from_utf8(index.identity).expect("trunk writes UTF-8");
+        let asset_paths: Vec<&str> = ASSETS.iter().map(|asset| asset.path).collect();
+        base_path::rewrite_index_html(html, &asset_paths)
+    })
+}
+
+async fn serve(mut server_req: Request<Body>) -> Result<Response<Body>, Error> {
+    // Probes and scrapes skip the session and the access log.
+    match server_req.uri().path() {
+        health::HEALTHZ_PATH => return health::healthz(),
+        health::READYZ_PATH => return health::readyz().await,
+        access_log::METRICS_PATH if access_log::metrics_enabled() => {
+            return access_log::metrics_response(&server_req);
         }
-        _ => {
-            let content = get_index();
-            let content_type = "text/html";
-            write_static_response(content, content_type)
+        _ => {}
+    }
+    let mut log = access_log::AccessLog::new(&server_req);
+    let response_headers = match auth::gate(&mut server_req).await? {
+        auth::Gate::Pass { response_headers } => response_headers,
+        auth::Gate::Respond(resp) => return Ok(log.observe(*resp)),
+    };
+    log.set_user(server_req.headers());
+    let mut resp = route(server_req).await?;
+    for (name, value) in response_headers {
+        resp.headers_mut()
+            .append(name, HeaderValue::from_str(&value)?);
+    }
+    Ok(log.observe(resp))
+}
+
+async fn route(server_req: Request<Body>) -> Result<Response<Body>, Error> {
+    let path = server_req.uri().path();
+    if path == environments::ENVIRONMENTS_PATH {
+        return environments::list_response();
+    }
+    if path.starts_with("/api") {
+        let path_and_query = server_req.uri().path_and_query().unwrap().as_str();
+        // Remove /api or /api/<environment> prefix
+        let (target_url, path_and_query) = match environments::find(path) {
+            Some(environment) => {
+                let prefix = format!("/api/{}", environment.name);
+                (
+                    environment.target_url,
+                    path_and_query
+                        .strip_prefix(&prefix)
+                        .expect("checked by find"),
+                )
+            }
+            None => match target_url() {
+                Ok(target_url) => (
+                    target_url,
+                    path_and_query.strip_prefix("/api").expect("checked above"),
+                ),
+                Err(reason) => return server_error(&reason),
+            },
+        };
+        let Ok(target_url) = format!("{target_url}{path_and_query}").parse::<Uri>() else {
+            return server_error(&format!("cannot forward to `{target_url}`, invalid URL"));
+        };
+        return proxy(server_req, target_url).await;
+    }
+    // Unknown paths are client-side routes of the single page application.
+    let asset = ASSETS
+        .iter()
+        .find(|asset| asset.path == path)
+        .or_else(|| ASSETS.iter().find(|asset| asset.path == "/index.html"));
+    let Some(asset) = asset else {
+        unreachable!("embedding is skipped in debug mode")
+    };
+    write_static_response(&server_req, asset)
+}
+
+/// `TARGET_URL` of the Obelisk server, e.g. `http://127.0.0.1:5005`.
+fn target_url() -> Result<String, String> {
+    let target_url = std::env::var("TARGET_URL")
+        .map_err(|_| "missing environment variable TARGET_URL".to_string())?;
+    match target_url.parse::<Uri>() {
+        Ok(uri)
+            if matches!(uri.scheme_str(), Some("http" | "https")) && uri.authority().is_some() =>
+        {
+            Ok(target_url.trim_end_matches('/').to_string())
         }
+        _ => Err(format!(
+            "invalid TARGET_URL `{target_url}`, expected e.g. `http://127.0.0.1:5005`"
+        )),
     }
 }
 
+fn json_response(status: StatusCode, body: &serde_json::Value) -> Result<Response<Body>, Error> {
+    Response::builder()
+        .status(status)
+        .header("content-type", "application/json")
+        .header("cache-control", "no-store")
+        .body(Body::from_json(body)?)
+        .map_err(Error::from)
+}
+
+/// A misconfiguration, logged and reported as JSON.
+fn server_error(reason: &str) -> Result<Response<Body>, Error> {
+    eprintln!("{reason}");
+    json_response(
+        StatusCode::INTERNAL_SERVER_ERROR,
+        &serde_json::json!({ "error": reason }),
+    )
+}
+
 async fn proxy(server_req: Request<Body>, target_url: Uri) -> Result<Response<Body>, Error> {
     let client = Client::new();
     let mut client_req = Request::builder();
@@ -70,62 +170,231 @@ async fn proxy(server_req: Request<Body>, target_url: Uri) -> Result<Response<Bo
     Ok(server_resp.body(client_resp.into_body())?)
 }
 
-fn write_static_response(body: &[u8], content_type: &'static str) -> Result<Response<Body>, Error> {
-    let mut resp = Response::builder();
-    resp.headers_mut()
-        .unwrap()
-        .append("content-type", HeaderValue::from_static(content_type));
-    resp.body(body.into()).map_err(Error::from)
+fn content_type(path: &str) -> &'static str {
+    match path.rsplit_once('.').map(|(_, extension)| extension) {
+        Some("html") => "text/html",
+        Some("js") => "text/javascript",
+        Some("wasm") => "application/wasm",
+        Some("css") => "text/css",
+        _ => "application/octet-stream",
+    }
 }
 
-// release: Include real files
-#[cfg(not(debug_assertions))]
-fn get_index() -> &'static [u8] {
-    include_bytes!("../../webui/dist/index.html")
+/// Whether trunk put a content hash into the file name, e.g. `webui-0123456789abcdef_bg.wasm`.
+fn is_hashed(path: &str) -> bool {
+    path.split(['-', '_', '.'])
+        .any(|segment| segment.len() == 16 && segment.bytes().all(|byte| byte.is_ascii_hexdigit()))
 }
 
-#[cfg(not(debug_assertions))]
-fn get_webui_bg_wasm() -> &'static [u8] {
-    include_bytes!("../../webui/dist/webui_bg.wasm")
+/// Whether the `Accept-Encoding` header accepts the given coding with a non-zero quality.
+/// The coding's own entry wins over `*`, so `br;q=0, *` refuses brotli.
+fn accepts_encoding(accept_encoding: &str, coding: &str) -> bool {
+    let mut explicit = None;
+    let mut wildcard = None;
+    for item in accept_encoding.split(',') {
+        let mut parts = item.split(';').map(str::trim);
+        let name = parts.next().unwrap_or_default();
+        let quality = parts
+            .find_map(|param| param.strip_prefix("q="))
+            .and_then(|quality| quality.parse::<f32>().ok())
+            .unwrap_or(1.0);
+        if name.eq_ignore_ascii_case(coding) {
+            explicit = Some(quality);
+        } else if name == "*" {
+            wildcard = Some(quality);
+        }
+    }
+    explicit.or(wildcard).is_some_and(|quality| quality > 0.0)
 }
 
-#[cfg(not(debug_assertions))]
-fn get_webui_js() -> &'static [u8] {
-    include_bytes!("../../webui/dist/webui.js")
+/// The precompressed variant to send, `None` for the identity.
+fn negotiate_encoding(asset: &Asset, accept_encoding: &str) -> Option<&'static str> {
+    if asset.brotli.is_some() && accepts_encoding(accept_encoding, "br") {
+        Some("br")
+    } else if asset.gzip.is_some() && accepts_encoding(accept_encoding, "gzip") {
+        Some("gzip")
+    } else {
+        None
+    }
 }
 
-#[cfg(not(debug_assertions))]
-fn get_styles_css() -> &'static [u8] {
-    include_bytes!("../../webui/dist/styles.css")
+/// Each representation needs its own strong validator.
+fn etag(hash: &str, encoding: Option<&str>) -> String {
+    match encoding {
+        Some(encoding) => format!("\"{hash}-{encoding}\""),
+        None => format!("\"{hash}\""),
+    }
 }
 
-#[cfg(not(debug_assertions))]
-fn get_syntect_css() -> &'static [u8] {
-    include_bytes!("../../webui/dist/syntect.css")
+/// Whether the `If-None-Match` header lists the `ETag`, weak comparison.
+fn if_none_match(if_none_match: &str, etag: &str) -> bool {
+    if_none_match.split(',').map(str::trim).any(|candidate| {
+        candidate == "*" || candidate.strip_prefix("W/").unwrap_or(candidate) == etag
+    })
 }
 
-// debug: Include dummy file content
-#[cfg(debug_assertions)]
-fn get_index() -> &'static [u8] {
-    unreachable!("embedding is skipped in debug mode")
-}
+fn write_static_response(
+    server_req: &Request<Body>,
+    asset: &'static Asset,
+) -> Result<Response<Body>, Error> {
+    let accept_encoding = server_req
+        .headers()
+        .get("accept-encoding")
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    let (body, encoding, hash): (Body, _, Cow<str>) =
+        if asset.path == "/index.html" && !base_path::base_path().is_empty() {
+            // The rewritten document differs from the precompressed variants.
+            let html = index_html();
+            let mut hasher = DefaultHasher::new();
+            hasher.write(html.as_bytes());
+            let hash = format!("{:016x}", hasher.finish());
+            (Body::from(html), None, Cow::Owned(hash))
+        } else {
+            let encoding = negotiate_encoding(asset, accept_encoding);
+            let body = match encoding {
+                Some("br") => asset.brotli,
+                Some(_) => asset.gzip,
+                None => None,
+            }
+            .unwrap_or(asset.identity);
+            (body.into(), encoding, Cow::Borrowed(asset.hash))
+        };
+    let etag = etag(&hash, encoding);
+    let cache_control = if asset.path != "/index.html" && is_hashed(asset.path) {
+        "public, max-age=31536000, immutable"
+    } else {
+        // Revalidate with the ETag on every use.
+        "no-cache"
+    };
 
-#[cfg(debug_assertions)]
-fn get_webui_bg_wasm() -> &'static [u8] {
-    unreachable!("embedding is skipped in debug mode")
-}
+    let mut resp = Response::builder();
+    let headers = resp.headers_mut().unwrap();
+    headers.append("etag", HeaderValue::from_str(&etag)?);
+    headers.append("cache-control", HeaderValue::from_static(cache_control));
+    headers.append("vary", HeaderValue::from_static("accept-encoding"));
 
-#[cfg(debug_assertions)]
-fn get_webui_js() -> &'static [u8] {
-    unreachable!("embedding is skipped in debug mode")
-}
+    let not_modified = server_req
+        .headers()
+        .get("if-none-match")
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| if_none_match(value, &etag));
+    if not_modified {
+        return resp
+            .status(StatusCode::NOT_MODIFIED)
+            .body(Body::empty())
+            .map_err(Error::from);
+    }
 
-#[cfg(debug_assertions)]
-fn get_styles_css() -> &'static [u8] {
-    unreachable!("embedding is skipped in debug mode")
+    headers.append(
+        "content-type",
+        HeaderValue::from_static(content_type(asset.path)),
+    );
+    if let Some(encoding) = encoding {
+        headers.append("content-encoding", HeaderValue::from_static(encoding));
+    }
+    resp.body(body).map_err(Error::from)
 }
 
-#[cfg(debug_assertions)]
-fn get_syntect_css() -> &'static [u8] {
-    unreachable!("embedding is skipped in debug mode")
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static SCRIPT: Asset = Asset {
+        path: "/webui-0123456789abcdef.js",
+        hash: "feed",
+        identity: b"identity",
+        gzip: Some(b"gzip"),
+        brotli: Some(b"brotli"),
+    };
+
+    fn request(headers: &[(&str, &str)]) -> Request<Body> {
+        let mut req = Request::builder().uri(SCRIPT.path);
+        for (name, value) in headers {
+            req = req.header(*name, *value);
+        }
+        req.body(Body::empty()).unwrap()
+    }
+
+    fn header<'a>(resp: &'a Response<Body>, name: &str) -> Option<&'a str> {
+        resp.headers()
+            .get(name)
+            .map(|value| value.to_str().unwrap())
+    }
+
+    #[test]
+    fn explicit_coding_wins_over_wildcard() {
+        assert!(accepts_encoding("gzip, br", "br"));
+        assert!(accepts_encoding("*", "br"));
+        assert!(!accepts_encoding("br;q=0, *", "br"));
+        assert!(!accepts_encoding("*, br;q=0", "br"));
+        assert!(accepts_encoding("br;q=0, *", "gzip"));
+        assert!(!accepts_encoding("*;q=0", "gzip"));
+        assert!(accepts_encoding("GZIP;q=0.5", "gzip"));
+        assert!(!accepts_encoding("identity", "gzip"));
+        assert!(!accepts_encoding("", "gzip"));
+    }
+
+    #[test]
+    fn hashed_file_names() {
+        assert!(is_hashed("/webui-0123456789abcdef_bg.wasm"));
+        assert!(is_hashed("/styles-0123456789abcdef.css"));
+        assert!(!is_hashed("/index.html"));
+        assert!(!is_hashed("/favicon-0123.ico"));
+    }
+
+    #[test]
+    fn if_none_match_comparison() {
+        assert!(if_none_match("\"a\", \"feed-br\"", "\"feed-br\""));
+        assert!(if_none_match("W/\"feed-br\"", "\"feed-br\""));
+        assert!(if_none_match("*", "\"feed\""));
+        assert!(!if_none_match("\"feed-gzip\"", "\"feed-br\""));
+    }
+
+    #[test]
+    fn etag_depends_on_encoding() {
+        let resp =
+            write_static_response(&request(&[("accept-encoding", "gzip, br")]), &SCRIPT).unwrap();
+        assert_eq!(Some("br"), header(&resp, "content-encoding"));
+        assert_eq!(Some("\"feed-br\""), header(&resp, "etag"));
+
+        let resp =
+            write_static_response(&request(&[("accept-encoding", "gzip")]), &SCRIPT).unwrap();
+        assert_eq!(Some("gzip"), header(&resp, "content-encoding"));
+        assert_eq!(Some("\"feed-gzip\""), header(&resp, "etag"));
+
+        let resp = write_static_response(&request(&[]), &SCRIPT).unwrap();
+        assert_eq!(None, header(&resp, "content-encoding"));
+        assert_eq!(Some("\"feed\""), header(&resp, "etag"));
+        assert_eq!(
+            Some("public, max-age=31536000, immutable"),
+            header(&resp, "cache-control")
+        );
+    }
+
+    #[test]
+    fn not_modified_only_for_the_same_representation() {
+        let resp = write_static_response(
+            &request(&[
+                ("accept-encoding", "gzip"),
+                ("if-none-match", "\"feed-gzip\""),
+            ]),
+            &SCRIPT,
+        )
+        .unwrap();
+        assert_eq!(StatusCode::NOT_MODIFIED, resp.status());
+        assert_eq!(None, header(&resp, "content-type"));
+
+        // A cached brotli variant must not be revalidated for a gzip-only client.
+        let resp = write_static_response(
+            &request(&[
+                ("accept-encoding", "gzip"),
+                ("if-none-match", "\"feed-br\""),
+            ]),
+            &SCRIPT,
+        )
+        .unwrap();
+        assert_eq!(StatusCode::OK, resp.status());
+        assert_eq!(Some("gzip"), header(&resp, "content-encoding"));
+    }
 }
@@ -0,0 +1,148 @@
+//! Security headers of every response.
+//!
+//! Each header is configured through the variable of the same name, e.g. `X_FRAME_OPTIONS=SAMEORIGIN`,
+//! an empty value omits it. The default `Content-Security-Policy` admits the loader script that
+//! trunk inlines into `index.html` by its hash and lets wasm-bindgen compile the module.
+//! The origins of `TOKEN_REFRESH_ORIGINS` are added to its `connect-src`, so that the UI can
+//! refresh short-lived API tokens from another origin.
+
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use sha2::{Digest as _, Sha256};
+use std::sync::OnceLock;
+use wstd::http::{HeaderMap, HeaderValue};
+
+/// `'unsafe-inline'` styles are needed by `style` attributes of the UI.
+const DEFAULT_CSP: &str = "default-src 'self'; \
+    script-src 'self' 'wasm-unsafe-eval'{inline_scripts}; \
+    style-src 'self' 'unsafe-inline'; \
+    img-src 'self' data:; \
+    connect-src 'self'{refresh_origins}; \
+    object-src 'none'; \
+    base-uri 'self'; \
+    form-action 'self'; \
+    frame-ancestors 'none'";
+
+/// `'sha256-…'` sources of the inline `<script>` elements.
+fn inline_script_hashes(html: &str) -> String {
+    let mut hashes = String::new();
+    let mut rest = html;
+    while let Some(start) = rest.find("<script") {
+        rest = &rest[start..];
+        let Some(tag_end) = rest.find('>') else {
+            break;
+        };
+        let tag = &rest[..tag_end];
+        let Some(content_end) = rest.find("</script>") else {
+            break;
+        };
+        if !tag.contains(" src=") {
+            let content = &rest[tag_end + 1..content_end];
+            hashes.push_str(" 'sha256-");
+            hashes.push_str(&STANDARD.encode(Sha256::digest(content.as_bytes())));
+            hashes.push('\'');
+        }
+        rest = &rest[content_end..];
+    }
+    hashes
+}
+
+/// Sources of the comma separated `TOKEN_REFRESH_ORIGINS`, e.g. `https://sso.example.com`.
+fn refresh_origins(configured: &str) -> String {
+    let mut sources = String::new();
+    for origin in configured
+        .split(',')
+        .map(str::trim)
+        .filter(|origin| !origin.is_empty())
+    {
+        let origin = origin.trim_end_matches('/');
+        let host = origin
+            .strip_prefix("https://")
+            .or_else(|| origin.strip_prefix("http://"));
+        if host.is_some_and(|host| {
+            !host.is_empty()
+                && host
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | ':' | '[' | ']'))
+        }) {
+            sources.push(' ');
+            sources.push_str(origin);
+        } else {
+            eprintln!(
+                "ignoring `{origin}` of TOKEN_REFRESH_ORIGINS, expected an origin like `https://sso.example.com`"
+            );
+        }
+    }
+    sources
+}
+
+fn default_csp(index_html: &str) -> String {
+    DEFAULT_CSP
+        .replace("{inline_scripts}", &inline_script_hashes(index_html))
+        .replace(
+            "{refresh_origins}",
+            &refresh_origins(&std::env::var("TOKEN_REFRESH_ORIGINS").unwrap_or_default()),
+        )
+}
+
+/// Value of the variable, the default when unset, `None` when set to an empty value.
+fn configured(name: &str, default: impl FnOnce() -> String) -> Option<String> {
+    match std::env::var(name) {
+        Ok(value) if value.trim().is_empty() => None,
+        Ok(value) => Some(value),
+        Err(_) => Some(default()),
+    }
+}
+
+/// `index_html` is the document as served, its inline scripts are allowed by the default CSP.
+/// The document does not change, so the policy is computed once per instance.
+pub(crate) fn append_headers(headers: &mut HeaderMap, index_html: &str) {
+    static CSP: OnceLock<String> = OnceLock::new();
+    let security_headers = [
+        (
+            "content-security-policy",
+            configured("CONTENT_SECURITY_POLICY", || {
+                CSP.get_or_init(|| default_csp(index_html)).clone()
+            }),
+        ),
+        (
+            "x-frame-options",
+            configured("X_FRAME_OPTIONS", || "DENY".to_string()),
+        ),
+        (
+            "referrer-policy",
+            configured("REFERRER_POLICY", || "no-referrer".to_string()),
+        ),
+        (
+            "x-content-type-options",
+            configured("X_CONTENT_TYPE_OPTIONS", || "nosniff".to_string()),
+        ),
+    ];
+    for (name, value) in security_headers {
+        let Some(value) = value else {
+            continue;
+        };
+        match HeaderValue::from_str(&value) {
+            Ok(value) => {
+                headers.insert(name, value);
+            }
+            Err(_) => eprintln!("ignoring invalid value of the `{name}` header"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refresh_origins_are_validated() {
+        assert_eq!(
+            " https://sso.example.com http://127.0.0.1:9000",
+            refresh_origins("https://sso.example.com/, http://127.0.0.1:9000")
+        );
+        assert_eq!("", refresh_origins(""));
+        assert_eq!("", refresh_origins("sso.example.com"));
+        assert_eq!("", refresh_origins("https://sso.example.com/token"));
+        assert_eq!("", refresh_origins("https://sso.example.com; script-src *"));
+    }
+}
@@ -1,13 +1,15 @@
 use crate::{
-    app::query::BacktraceVersionsPath,
-    auth::AuthProvider,
+    app::query::{BacktraceVersionsPath, DeploymentIdList},
+    auth::{AuthProvider, SessionStatus, use_read_only},
     components::{
+        annotations_page::AnnotationsPage,
         component_list_page::ComponentListPage,
         debugger::debugger_view::DebuggerView,
         deployment_detail_page::DeploymentDetailPage,
         deployment_diff_page::DeploymentDiffPage,
         deployment_list_page::DeploymentListPage,
         execution_detail_page::ExecutionLogPage,
+        execution_family_page::ExecutionFamilyPage,
         execution_list_page::ExecutionListPage,
         execution_logs_page::LogsPage,
         execution_new_page::ExecutionNewPage,
@@ -15,8 +17,11 @@ use crate::{
         execution_submit_page::ExecutionSubmitPage,
         not_found::NotFound,
         notification::{Notification, NotificationContext, NotificationProvider},
+        stub_inbox_page::StubInboxPage,
         trace::trace_view::TraceView,
+        wit_diff_page::WitDiffPage,
     },
+    environment::{self, EnvironmentSwitcher},
     grpc::{
         ffqn::FunctionFqn,
         grpc_client::{self, ComponentId, DeploymentId, ExecutionId},
@@ -126,6 +131,41 @@ pub mod query {
             BacktraceVersionsPath(vec![0])
         }
     }
+
+    /// Deployments compared on the diff page, the first one being the base.
+    #[derive(Clone, PartialEq)]
+    pub struct DeploymentIdList(pub Vec<DeploymentId>);
+    const DEPLOYMENT_ID_LIST_SEPARATOR: char = ',';
+    impl Display for DeploymentIdList {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            for (idx, deployment_id) in self.0.iter().enumerate() {
+                if idx == 0 {
+                    write!(f, "{deployment_id}")?;
+                } else {
+                    write!(f, "{DEPLOYMENT_ID_LIST_SEPARATOR}{deployment_id}")?;
+                }
+            }
+            Ok(())
+        }
+    }
+
+    impl FromStr for DeploymentIdList {
+        type Err = ();
+
+        fn from_str(input: &str) -> Result<Self, Self::Err> {
+            let deployment_ids = input
+                .split(DEPLOYMENT_ID_LIST_SEPARATOR)
+                .filter(|split| !split.is_empty())
+                .map(|split| DeploymentId {
+                    id: split.to_string(),
+                })
+                .collect::<Vec<_>>();
+            if deployment_ids.len() < 2 {
+                return Err(());
+            }
+            Ok(DeploymentIdList(deployment_ids))
+        }
+    }
 }
 
 #[derive(Clone, Routable, PartialEq)]
@@ -138,6 +178,11 @@ pub enum Route {
     Component {
         component_id: grpc_client::ComponentId,
     },
+    #[at("/components/diff/:from/:to")]
+    ComponentWitDiff {
+        from: grpc_client::ComponentId,
+        to: grpc_client::ComponentId,
+    },
     #[at("/deployments")]
     DeploymentList,
     #[at("/deployments/diff/:from/:to")]
@@ -145,6 +190,8 @@ pub enum Route {
         from: grpc_client::DeploymentId,
         to: grpc_client::DeploymentId,
     },
+    #[at("/deployments/compare/:deployments")]
+    DeploymentCompare { deployments: DeploymentIdList },
     #[at("/deployment/:deployment_id")]
     DeploymentDetail {
         deployment_id: grpc_client::DeploymentId,
@@ -160,6 +207,8 @@ pub enum Route {
     },
     #[at("/execution/list")]
     ExecutionList,
+    #[at("/execution/stubs")]
+    StubInbox,
     #[at("/execution/:execution_id/log")]
     ExecutionLog {
         execution_id: grpc_client::ExecutionId,
@@ -181,6 +230,12 @@ pub enum Route {
     Logs {
         execution_id: grpc_client::ExecutionId,
     },
+    #[at("/execution/:execution_id/family")]
+    ExecutionFamily {
+        execution_id: grpc_client::ExecutionId,
+    },
+    #[at("/annotations")]
+    Annotations,
     #[not_found]
     #[at("/404")]
     NotFound,
@@ -194,16 +249,23 @@ impl Route {
             Route::Component { component_id } => {
                 html! { <ComponentListPage maybe_component_id={Some(component_id)}/> }
             }
+            Route::ComponentWitDiff { from, to } => html! { <WitDiffPage {from} {to} /> },
             Route::DeploymentList => html! { <DeploymentListPage /> },
             Route::DeploymentDetail { deployment_id } => {
                 html! { <DeploymentDetailPage {deployment_id} /> }
             }
-            Route::DeploymentDiff { from, to } => html! { <DeploymentDiffPage {from} {to} /> },
+            Route::DeploymentDiff { from, to } => {
+                html! { <DeploymentDiffPage deployments={vec![from, to]} /> }
+            }
+            Route::DeploymentCompare { deployments } => {
+                html! { <DeploymentDiffPage deployments={deployments.0} /> }
+            }
             Route::ExecutionNew => html! { <ExecutionNewPage /> },
             Route::ExecutionSubmit { ffqn } => html! { <ExecutionSubmitPage {ffqn} /> },
             Route::ExecutionStubResult { ffqn, execution_id } => {
                 html! { <ExecutionStubResultPage {ffqn}  {execution_id} /> }
             }
+            Route::StubInbox => html! { <StubInboxPage /> },
             Route::ExecutionLog { execution_id } => {
                 html! { <ExecutionLogPage {execution_id} /> }
             }
@@ -222,6 +284,10 @@ impl Route {
             Route::Logs { execution_id } => {
                 html! { <LogsPage {execution_id} />}
             }
+            Route::ExecutionFamily { execution_id } => {
+                html! { <ExecutionFamilyPage {execution_id} /> }
+            }
+            Route::Annotations => html! { <AnnotationsPage /> },
             Route::NotFound => html! { <NotFound /> },
         }
     }
@@ -231,6 +297,8 @@ impl Route {
 pub struct AppProps {
     pub initial_components: LoadedComponents,
     pub initial_deployment_id: Option<grpc_client::DeploymentId>,
+    /// Names of the Obelisk servers offered by `webui-proxy`.
+    pub environments: Vec<String>,
 }
 
 #[component(App)]
@@ -238,6 +306,7 @@ pub fn app(
     AppProps {
         initial_components,
         initial_deployment_id,
+        environments,
     }: &AppProps,
 ) -> Html {
     html! {
@@ -246,6 +315,7 @@ pub fn app(
                 <AppInner
                     initial_components={initial_components.clone()}
                     initial_deployment_id={initial_deployment_id.clone()}
+                    environments={environments.clone()}
                 />
             </NotificationProvider>
         </AuthProvider>
@@ -258,10 +328,12 @@ fn app_inner(
     AppProps {
         initial_components,
         initial_deployment_id,
+        environments,
     }: &AppProps,
 ) -> Html {
     let notifications = use_context::<NotificationContext>()
         .expect("NotificationContext should be provided by App");
+    let read_only = use_read_only();
 
     let app_state =
         use_state(|| AppState::from_loaded(initial_components, initial_deployment_id.clone()));
@@ -347,8 +419,9 @@ fn app_inner(
 
     html! {
         <ContextProvider<AppState> context={app_state.deref().clone()}>
+            <EnvironmentSwitcher environments={environments.clone()} />
             <div class="container">
-                <BrowserRouter>
+                <BrowserRouter basename={environment::router_basename()}>
                     <nav>
                         <Link<Route> to={Route::DeploymentList }>
                             {"Deployments"}
@@ -361,6 +434,18 @@ fn app_inner(
                         <Link<Route> to={Route::ExecutionNew }>
                             {"Submit"}
                         </Link<Route>>
+                        {" "}
+                        <Link<Route> to={Route::StubInbox }>
+                            {"Stub inbox"}
+                        </Link<Route>>
+                        {" "}
+                        <Link<Route> to={Route::Annotations }>
+                            {"Annotations"}
+                        </Link<Route>>
+                        if let Some(reason) = read_only {
+                            <span class="read-only-badge" title={reason}>{"Read-only"}</span>
+                        }
+                        <SessionStatus />
 
                     </nav>
                     <Switch<Route> render={Route::render} />
@@ -1,3 +1,11 @@
+use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
+use chrono::{DateTime, TimeDelta, Utc};
+use futures::channel::oneshot;
+use gloo::events::EventListener;
+use http_body_util::{BodyExt as _, Full};
+use log::warn;
+use serde::Deserialize;
+use sha2::{Digest as _, Sha256};
 use std::{
     cell::RefCell,
     future::Future,
@@ -9,24 +17,139 @@ use tonic::{
     codegen::{Service, http},
 };
 use tonic_web_wasm_client::{Client, Error, ResponseBody};
-use web_sys::{HtmlInputElement, SubmitEvent};
+use wasm_bindgen::JsCast as _;
+use web_sys::{HtmlInputElement, RequestCredentials, StorageEvent, SubmitEvent};
 use yew::prelude::*;
 
-use crate::BASE_URL;
+use crate::environment;
 
 const TOKEN_STORAGE_KEY: &str = "obelisk-api-token";
+const REFRESH_URL_STORAGE_KEY: &str = "obelisk-token-refresh-url";
+/// Tokens expiring sooner are refreshed before sending a request.
+const REFRESH_BEFORE_EXPIRY_SECS: i64 = 30;
+/// Set by `webui-proxy` when it authenticates users itself.
+const PROXY_LOGIN_HEADER: &str = "x-obelisk-login";
+/// Set by `webui-proxy` to the user of its session.
+const PROXY_SESSION_USER_HEADER: &str = "x-obelisk-session-user";
+/// Ends the session of `webui-proxy`.
+const PROXY_LOGOUT_PATH: &str = "/auth/logout";
+/// Set by `webui-proxy` to the reason why mutations are not allowed.
+const PROXY_READ_ONLY_HEADER: &str = "x-obelisk-read-only";
+/// RPCs changing the state of the server, see `obelisk.proto`.
+const MUTATING_METHODS: &[&str] = &[
+    "Submit",
+    "Stub",
+    "CancelExecution",
+    "CancelDelay",
+    "PersistExecutionBacktraces",
+    "AdvanceExecution",
+    "UpgradeExecutionComponent",
+    "PauseExecution",
+    "UnpauseExecution",
+    "PauseDelay",
+    "UnpauseDelay",
+    "SwitchDeployment",
+    "SubmitDeployment",
+    "GcOrphanFiles",
+];
 
 thread_local! {
     static ON_AUTH_REQUIRED: RefCell<Option<Callback<()>>> = const { RefCell::new(None) };
     static AUTH_REQUIRED_PENDING: RefCell<bool> = const { RefCell::new(false) };
+    /// Requests waiting for the user to enter a token.
+    static REAUTH_WAITERS: RefCell<Vec<oneshot::Sender<bool>>> = const { RefCell::new(Vec::new()) };
+    /// `Some` while a refresh is in flight, with the requests waiting for it.
+    static REFRESH_WAITERS: RefCell<Option<Vec<oneshot::Sender<bool>>>> = const { RefCell::new(None) };
+    static ON_SESSION_CHANGED: RefCell<Option<Callback<()>>> = const { RefCell::new(None) };
+    /// User signed in to `webui-proxy`, if it authenticates users.
+    static PROXY_USER: RefCell<Option<AttrValue>> = const { RefCell::new(None) };
+    /// Why the last refresh failed, shown in the dialog asking for a token.
+    static REFRESH_ERROR: RefCell<Option<String>> = const { RefCell::new(None) };
+    /// Why mutations are not allowed for the current token, cleared when it changes.
+    static READ_ONLY_REASON: RefCell<Option<AttrValue>> = const { RefCell::new(None) };
+    static ON_READ_ONLY: RefCell<Option<Callback<Option<AttrValue>>>> = const { RefCell::new(None) };
+}
+
+/// Each environment has its own token and refresh URL.
+fn storage_key(key: &str) -> String {
+    match environment::current() {
+        Some(name) => format!("{key}:{name}"),
+        None => key.to_string(),
+    }
+}
+
+fn storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+fn stored(key: &str) -> Option<String> {
+    storage()?.get_item(&storage_key(key)).ok()?
+}
+
+fn store(key: &str, value: Option<&str>) {
+    let Some(storage) = storage() else {
+        return;
+    };
+    let _ = match value {
+        Some(value) => storage.set_item(&storage_key(key), value),
+        None => storage.remove_item(&storage_key(key)),
+    };
 }
 
 fn token() -> Option<String> {
-    web_sys::window()?
-        .local_storage()
-        .ok()??
-        .get_item(TOKEN_STORAGE_KEY)
+    stored(TOKEN_STORAGE_KEY)
+}
+
+fn set_token(token: Option<&str>) {
+    store(TOKEN_STORAGE_KEY, token);
+    // A denial of the previous token says nothing about the new one.
+    set_read_only(None);
+    session_changed();
+}
+
+fn session_changed() {
+    ON_SESSION_CHANGED.with(|callback| {
+        if let Some(callback) = callback.borrow().as_ref() {
+            callback.emit(());
+        }
+    });
+}
+
+fn set_proxy_user(user: Option<&str>) {
+    let changed = PROXY_USER.with(|current| {
+        let mut current = current.borrow_mut();
+        if current.as_deref() == user {
+            false
+        } else {
+            *current = user.map(|user| AttrValue::from(user.to_string()));
+            true
+        }
+    });
+    if changed {
+        session_changed();
+    }
+}
+
+/// First 8 hex digits of the SHA-256 digest, e.g. of `printf '%s' "$TOKEN" | sha256sum`.
+fn fingerprint(token: &str) -> String {
+    Sha256::digest(token.as_bytes())
+        .iter()
+        .take(4)
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// The `exp` claim when the token is a JWT. The signature is not verified.
+fn expires_at(token: &str) -> Option<DateTime<Utc>> {
+    let [_header, payload, _signature] = token.split('.').collect::<Vec<_>>()[..] else {
+        return None;
+    };
+    let payload = URL_SAFE_NO_PAD.decode(payload.trim_end_matches('=')).ok()?;
+    let exp = serde_json::from_slice::<serde_json::Value>(&payload)
         .ok()?
+        .get("exp")?
+        .as_i64()?;
+    DateTime::from_timestamp(exp, 0)
 }
 
 fn auth_required() {
@@ -38,11 +161,173 @@ fn auth_required() {
     });
 }
 
+/// Wait for the user to enter a token, `false` when they dismissed the dialog.
+async fn ask_for_token() -> bool {
+    if ON_AUTH_REQUIRED.with(|callback| callback.borrow().is_none()) {
+        // Not mounted yet, the dialog is shown once it is.
+        auth_required();
+        return false;
+    }
+    let (sender, receiver) = oneshot::channel();
+    REAUTH_WAITERS.with(|waiters| waiters.borrow_mut().push(sender));
+    auth_required();
+    receiver.await.unwrap_or(false)
+}
+
+/// Resume the requests waiting in [`ask_for_token`].
+fn reauth_finished(authenticated: bool) {
+    AUTH_REQUIRED_PENDING.with(|pending| *pending.borrow_mut() = false);
+    for sender in REAUTH_WAITERS.with(RefCell::take) {
+        let _ = sender.send(authenticated);
+    }
+}
+
+#[derive(Deserialize)]
+struct RefreshResponse {
+    #[serde(alias = "access_token")]
+    token: String,
+}
+
+async fn fetch_token(url: &str) -> Result<String, String> {
+    let response = gloo::net::http::Request::post(url)
+        .credentials(RequestCredentials::Include)
+        .header("accept", "application/json")
+        .send()
+        .await
+        .map_err(|err| err.to_string())?;
+    if !response.ok() {
+        return Err(format!("status {}", response.status()));
+    }
+    let response = response
+        .json::<RefreshResponse>()
+        .await
+        .map_err(|err| err.to_string())?;
+    let token = response.token.trim();
+    if token.is_empty() {
+        return Err("empty token".to_string());
+    }
+    Ok(token.to_string())
+}
+
+/// Obtain a new token from the refresh URL, concurrent requests share one refresh.
+async fn refresh_token() -> bool {
+    let Some(url) = stored(REFRESH_URL_STORAGE_KEY) else {
+        return false;
+    };
+    let in_flight = REFRESH_WAITERS.with(|waiters| {
+        let mut waiters = waiters.borrow_mut();
+        if let Some(waiters) = waiters.as_mut() {
+            let (sender, receiver) = oneshot::channel();
+            waiters.push(sender);
+            Some(receiver)
+        } else {
+            *waiters = Some(Vec::new());
+            None
+        }
+    });
+    if let Some(receiver) = in_flight {
+        return receiver.await.unwrap_or(false);
+    }
+    let refreshed = match fetch_token(&url).await {
+        Ok(token) => {
+            REFRESH_ERROR.with(|error| *error.borrow_mut() = None);
+            set_token(Some(&token));
+            true
+        }
+        Err(err) => {
+            warn!("Cannot refresh the API token using {url}: {err}");
+            let mut error = format!("Refreshing the token using {url} failed: {err}.");
+            if is_cross_origin(&url) {
+                error.push_str(
+                    " The Content-Security-Policy of the web UI must allow its origin in \
+                    `connect-src`, see `TOKEN_REFRESH_ORIGINS` of webui-proxy, and the endpoint \
+                    must allow CORS requests with credentials.",
+                );
+            }
+            REFRESH_ERROR.with(|current| *current.borrow_mut() = Some(error));
+            false
+        }
+    };
+    for sender in REFRESH_WAITERS.with(RefCell::take).into_iter().flatten() {
+        let _ = sender.send(refreshed);
+    }
+    refreshed
+}
+
+fn is_cross_origin(url: &str) -> bool {
+    let Some(origin) = web_sys::window().and_then(|window| window.location().origin().ok()) else {
+        return false;
+    };
+    url.contains("://") && url != origin && !url.starts_with(&format!("{origin}/"))
+}
+
+/// Refresh a short-lived token before the server rejects it.
+async fn refresh_if_expiring() {
+    let expiring = token()
+        .and_then(|token| expires_at(&token))
+        .is_some_and(|expires_at| {
+            expires_at - Utc::now() < TimeDelta::seconds(REFRESH_BEFORE_EXPIRY_SECS)
+        });
+    if expiring {
+        refresh_token().await;
+    }
+}
+
+fn set_read_only(reason: Option<&str>) {
+    let changed = READ_ONLY_REASON.with(|current| {
+        let mut current = current.borrow_mut();
+        if current.as_deref() == reason {
+            false
+        } else {
+            *current = reason.map(|reason| AttrValue::from(reason.to_string()));
+            true
+        }
+    });
+    if changed {
+        ON_READ_ONLY.with(|callback| {
+            if let Some(callback) = callback.borrow().as_ref() {
+                callback.emit(reason.map(|reason| AttrValue::from(reason.to_string())));
+            }
+        });
+    }
+}
+
+/// Why mutating actions are unavailable, `None` when they are allowed.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ReadOnly(pub Option<AttrValue>);
+
+/// The reason to show on disabled mutating controls, `None` when they are enabled.
+#[hook]
+pub fn use_read_only() -> Option<AttrValue> {
+    use_context::<ReadOnly>().and_then(|read_only| read_only.0)
+}
+
+/// The API token kept in this browser.
+#[derive(Clone, PartialEq)]
+pub struct Session {
+    /// User signed in to `webui-proxy`, who signs out at its logout URL.
+    pub proxy_user: Option<AttrValue>,
+    pub fingerprint: Option<AttrValue>,
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Forget the token and the refresh URL.
+    pub sign_out: Callback<()>,
+}
+
+fn session(sign_out: Callback<()>) -> Session {
+    let token = token();
+    Session {
+        proxy_user: PROXY_USER.with(|user| user.borrow().clone()),
+        fingerprint: token.as_deref().map(|token| fingerprint(token).into()),
+        expires_at: token.as_deref().and_then(expires_at),
+        sign_out,
+    }
+}
+
 #[derive(Clone)]
 pub struct AuthenticatedClient(Client);
 
 pub fn client() -> AuthenticatedClient {
-    AuthenticatedClient(Client::new(BASE_URL.to_string()))
+    AuthenticatedClient(Client::new(environment::base_url()))
 }
 
 impl Service<http::Request<Body>> for AuthenticatedClient {
@@ -54,27 +339,81 @@ impl Service<http::Request<Body>> for AuthenticatedClient {
         self.0.poll_ready(cx)
     }
 
-    fn call(&mut self, mut request: http::Request<Body>) -> Self::Future {
-        if let Some(token) = token()
-            && let Ok(mut value) = format!("Bearer {token}").parse::<http::HeaderValue>()
-        {
-            value.set_sensitive(true);
-            request
-                .headers_mut()
-                .insert(http::header::AUTHORIZATION, value);
-        }
-
-        let future = self.0.call(request);
+    fn call(&mut self, request: http::Request<Body>) -> Self::Future {
+        let mut client = self.0.clone();
         Box::pin(async move {
-            let response = future.await?;
-            if response
-                .headers()
-                .get("grpc-status")
-                .is_some_and(|status| status == "16")
-            {
-                auth_required();
+            let method = request
+                .uri()
+                .path()
+                .rsplit('/')
+                .next()
+                .unwrap_or_default()
+                .to_string();
+            let (parts, body) = request.into_parts();
+            // Buffered, so that the request can be sent again after authenticating.
+            let body = body.collect().await?.to_bytes();
+            refresh_if_expiring().await;
+            let mut refresh_tried = false;
+            loop {
+                let mut request = http::Request::builder()
+                    .method(parts.method.clone())
+                    .uri(parts.uri.clone())
+                    .version(parts.version)
+                    .body(Body::new(Full::new(body.clone())))?;
+                *request.headers_mut() = parts.headers.clone();
+                if let Some(token) = token()
+                    && let Ok(mut value) = format!("Bearer {token}").parse::<http::HeaderValue>()
+                {
+                    value.set_sensitive(true);
+                    request
+                        .headers_mut()
+                        .insert(http::header::AUTHORIZATION, value);
+                }
+
+                let response = client.call(request).await?;
+                let grpc_status = response
+                    .headers()
+                    .get("grpc-status")
+                    .and_then(|status| status.to_str().ok());
+                if let Some(user) = response
+                    .headers()
+                    .get(PROXY_SESSION_USER_HEADER)
+                    .and_then(|user| user.to_str().ok())
+                {
+                    set_proxy_user(Some(user));
+                }
+                if let Some(reason) = response
+                    .headers()
+                    .get(PROXY_READ_ONLY_HEADER)
+                    .and_then(|reason| reason.to_str().ok())
+                {
+                    set_read_only(Some(reason));
+                } else if grpc_status == Some("7") && MUTATING_METHODS.contains(&method.as_str()) {
+                    set_read_only(Some(&format!(
+                        "The server denied {method}, access is read-only"
+                    )));
+                }
+                if response.headers().contains_key(PROXY_LOGIN_HEADER) {
+                    // The proxy session expired, reloading goes through the proxy's sign-in.
+                    if let Some(window) = web_sys::window() {
+                        let _ = window.location().reload();
+                    }
+                    return Ok(response);
+                }
+                if grpc_status != Some("16") {
+                    return Ok(response);
+                }
+                // Try the refresh URL once, then ask the user until they give up.
+                let authenticated = if refresh_tried {
+                    ask_for_token().await
+                } else {
+                    refresh_tried = true;
+                    refresh_token().await || ask_for_token().await
+                };
+                if !authenticated {
+                    return Ok(response);
+                }
             }
-            Ok(response)
         })
     }
 }
@@ -88,6 +427,18 @@ pub struct AuthProviderProps {
 pub fn auth_provider(props: &AuthProviderProps) -> Html {
     let show_dialog = use_state(|| false);
     let token_input = use_node_ref();
+    let refresh_url_input = use_node_ref();
+    // Requests made before mounting may have already found out.
+    let read_only_reason = use_state(|| READ_ONLY_REASON.with(|reason| reason.borrow().clone()));
+    let sign_out = use_callback((), |(), ()| {
+        // Otherwise the next request would sign in again.
+        store(REFRESH_URL_STORAGE_KEY, None);
+        set_token(None);
+    });
+    let session_state = use_state({
+        let sign_out = sign_out.clone();
+        move || session(sign_out)
+    });
 
     {
         let show_dialog = show_dialog.clone();
@@ -106,9 +457,60 @@ pub fn auth_provider(props: &AuthProviderProps) -> Html {
             || ON_AUTH_REQUIRED.with(|callback| *callback.borrow_mut() = None)
         });
     }
+    {
+        let read_only_reason = read_only_reason.clone();
+        use_effect_with((), move |()| {
+            read_only_reason.set(READ_ONLY_REASON.with(|reason| reason.borrow().clone()));
+            ON_READ_ONLY.with(|callback| {
+                *callback.borrow_mut() = Some(Callback::from(move |reason| {
+                    read_only_reason.set(reason);
+                }));
+            });
+            || ON_READ_ONLY.with(|callback| *callback.borrow_mut() = None)
+        });
+    }
+    {
+        let session_state = session_state.clone();
+        let show_dialog = show_dialog.clone();
+        use_effect_with(sign_out, move |sign_out| {
+            ON_SESSION_CHANGED.with(|callback| {
+                *callback.borrow_mut() = Some(Callback::from({
+                    let session_state = session_state.clone();
+                    let sign_out = sign_out.clone();
+                    move |()| session_state.set(session(sign_out.clone()))
+                }));
+            });
+            // Requests made before mounting may have found the proxy user.
+            session_state.set(session(sign_out.clone()));
+            // Another tab of the same environment signed in or out.
+            let sign_out = sign_out.clone();
+            let listener = web_sys::window().map(|window| {
+                EventListener::new(&window, "storage", move |event| {
+                    let Some(event) = event.dyn_ref::<StorageEvent>() else {
+                        return;
+                    };
+                    if event.key().as_deref() != Some(storage_key(TOKEN_STORAGE_KEY).as_str()) {
+                        return;
+                    }
+                    session_state.set(session(sign_out.clone()));
+                    set_read_only(None);
+                    if event.new_value().is_some() && *show_dialog {
+                        show_dialog.set(false);
+                        reauth_finished(true);
+                    }
+                })
+            });
+            move || {
+                ON_SESSION_CHANGED.with(|callback| *callback.borrow_mut() = None);
+                drop(listener);
+            }
+        });
+    }
 
     let onsubmit = {
         let token_input = token_input.clone();
+        let refresh_url_input = refresh_url_input.clone();
+        let show_dialog = show_dialog.clone();
         Callback::from(move |event: SubmitEvent| {
             event.prevent_default();
             let Some(input) = token_input.cast::<HtmlInputElement>() else {
@@ -119,21 +521,35 @@ pub fn auth_provider(props: &AuthProviderProps) -> Html {
             if token.is_empty() {
                 return;
             }
-            if let Some(storage) =
-                web_sys::window().and_then(|window| window.local_storage().ok().flatten())
-            {
-                let _ = storage.set_item(TOKEN_STORAGE_KEY, token);
-            }
-            AUTH_REQUIRED_PENDING.with(|pending| *pending.borrow_mut() = false);
-            if let Some(window) = web_sys::window() {
-                let _ = window.location().reload();
+            if let Some(input) = refresh_url_input.cast::<HtmlInputElement>() {
+                let refresh_url = input.value();
+                let refresh_url = refresh_url.trim();
+                store(
+                    REFRESH_URL_STORAGE_KEY,
+                    (!refresh_url.is_empty()).then_some(refresh_url),
+                );
             }
+            REFRESH_ERROR.with(|error| *error.borrow_mut() = None);
+            set_token(Some(token));
+            input.set_value("");
+            show_dialog.set(false);
+            // Failed requests are sent again, nothing on the page is lost.
+            reauth_finished(true);
+        })
+    };
+    let oncancel = {
+        let show_dialog = show_dialog.clone();
+        Callback::from(move |_: MouseEvent| {
+            show_dialog.set(false);
+            reauth_finished(false);
         })
     };
 
     html! {
-        <>
-            {props.children.clone()}
+        <ContextProvider<ReadOnly> context={ReadOnly((*read_only_reason).clone())}>
+            <ContextProvider<Session> context={(*session_state).clone()}>
+                {props.children.clone()}
+            </ContextProvider<Session>>
             if *show_dialog {
                 <div class="modal-overlay auth-modal-overlay" role="presentation">
                     <section
@@ -148,6 +564,9 @@ pub fn auth_provider(props: &AuthProviderProps) -> Html {
                             </div>
                             <div class="auth-modal-body">
                                 <p>{"Paste an Obelisk API token to continue. It will be kept in this browser."}</p>
+                                if let Some(error) = REFRESH_ERROR.with(|error| error.borrow().clone()) {
+                                    <p class="auth-refresh-error" role="alert">{error}</p>
+                                }
                                 <label for="auth-token">{"API token"}</label>
                                 <input
                                     ref={token_input}
@@ -158,14 +577,100 @@ pub fn auth_provider(props: &AuthProviderProps) -> Html {
                                     autofocus=true
                                     required=true
                                 />
+                                <details class="auth-refresh" open={stored(REFRESH_URL_STORAGE_KEY).is_some()}>
+                                    <summary>{"Short-lived tokens"}</summary>
+                                    <label for="auth-refresh-url">{"Refresh URL"}</label>
+                                    <input
+                                        ref={refresh_url_input}
+                                        id="auth-refresh-url"
+                                        name="auth-refresh-url"
+                                        type="url"
+                                        placeholder="https://sso.example.com/obelisk/token"
+                                        value={stored(REFRESH_URL_STORAGE_KEY).unwrap_or_default()}
+                                    />
+                                    <p>{"Optional, called with the browser's cookies to obtain a new token when the current one expires."}</p>
+                                </details>
                             </div>
                             <div class="modal-footer">
+                                <button type="button" class="action-button" onclick={oncancel}>{"Cancel"}</button>
                                 <button type="submit" class="action-button confirm">{"Continue"}</button>
                             </div>
                         </form>
                     </section>
                 </div>
             }
-        </>
+        </ContextProvider<ReadOnly>>
+    }
+}
+
+/// The user signed in to the proxy or the fingerprint of the token in use, with the sign-out
+/// action. Nothing without either.
+#[component(SessionStatus)]
+pub fn session_status() -> Html {
+    let Some(Session {
+        proxy_user,
+        fingerprint,
+        expires_at,
+        sign_out,
+    }) = use_context::<Session>()
+    else {
+        return Html::default();
+    };
+    // The proxy replaces the token, its session is ended by the proxy.
+    if let Some(user) = proxy_user {
+        let logout_url = format!("{}{PROXY_LOGOUT_PATH}", environment::base_path());
+        return html! {
+            <span class="session-status">
+                <span class="session-user">{"Signed in as "}{user}</span>
+                <a href={logout_url}>{"Sign out"}</a>
+            </span>
+        };
+    }
+    let Some(fingerprint) = fingerprint else {
+        return Html::default();
+    };
+    let title = match expires_at {
+        Some(expires_at) => {
+            format!("SHA-256 fingerprint of the API token, expires at {expires_at}")
+        }
+        None => "SHA-256 fingerprint of the API token".to_string(),
+    };
+    html! {
+        <span class="session-status">
+            <span class="session-fingerprint" {title}>{"Token "}{fingerprint}</span>
+            <button type="button" onclick={move |_| sign_out.emit(())}>{"Sign out"}</button>
+        </span>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fingerprint_is_sha256_prefix() {
+        // printf '%s' 'secret' | sha256sum
+        assert_eq!("2bb80d53", fingerprint("secret"));
+    }
+
+    #[test]
+    fn expiry_of_jwt() {
+        let payload = URL_SAFE_NO_PAD.encode(r#"{"sub":"dev","exp":1760000000}"#);
+        assert_eq!(
+            DateTime::from_timestamp(1_760_000_000, 0),
+            expires_at(&format!("eyJhbGciOiJIUzI1NiJ9.{payload}.c2ln"))
+        );
+        assert_eq!(None, expires_at("opaque-token"));
+    }
+
+    #[test]
+    fn read_only_reason_can_be_cleared() {
+        set_read_only(Some("The server denied Submit, access is read-only"));
+        assert!(READ_ONLY_REASON.with(|reason| reason.borrow().is_some()));
+        set_read_only(None);
+        assert_eq!(
+            None,
+            READ_ONLY_REASON.with(|reason| reason.borrow().clone())
+        );
     }
 }
@@ -0,0 +1,442 @@
+//! Notes attached to executions, versions and source lines, kept in the browser's local
+//! storage. A bundle of them can be exported and imported to hand over an annotated
+//! walkthrough of an incident.
+//!
+//! All environments of the origin share the storage, so each note records the base path and
+//! environment it was taken in. Views list the notes of their own environment only.
+
+use crate::{
+    app::Route,
+    components::{
+        notification::{Notification, NotificationContext},
+        trace::highlight::set_highlight_hash,
+    },
+    environment,
+    grpc::{grpc_client::ExecutionId, version::VersionType},
+};
+use chrono::{DateTime, Utc};
+use gloo::storage::{LocalStorage, Storage};
+use serde::{Deserialize, Serialize};
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+use yew_router::prelude::{Routable, use_navigator};
+
+const ANNOTATIONS_STORAGE_KEY: &str = "obelisk-annotations";
+const BUNDLE_FORMAT: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceLocation {
+    pub file: String,
+    pub line: u32,
+}
+
+/// The web UI a note was taken in, see [`crate::environment`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct AnnotationEnvironment {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub base_path: String,
+    /// `None` when the proxy offers no environments.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+impl AnnotationEnvironment {
+    pub fn current() -> Self {
+        AnnotationEnvironment {
+            base_path: environment::base_path(),
+            name: environment::current(),
+        }
+    }
+
+    pub fn label(&self) -> String {
+        match (&self.name, self.base_path.as_str()) {
+            (Some(name), "") => name.clone(),
+            (Some(name), base_path) => format!("{name} at {base_path}"),
+            (None, "") => "no environment".to_string(),
+            (None, base_path) => base_path.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Annotation {
+    pub id: String,
+    pub execution_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<VersionType>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<SourceLocation>,
+    pub note: String,
+    /// Path of the view the note was taken in, relative to the router basename of `environment`.
+    pub path: String,
+    #[serde(default)]
+    pub environment: AnnotationEnvironment,
+    pub created_at: DateTime<Utc>,
+}
+impl Annotation {
+    pub fn target(&self) -> String {
+        let mut target = self.execution_id.clone();
+        if let Some(version) = self.version {
+            target.push_str(&format!(" @ version {version}"));
+        }
+        if let Some(SourceLocation { file, line }) = &self.location {
+            target.push_str(&format!(" @ {file}:{line}"));
+        }
+        target
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnnotationBundle {
+    pub format: u32,
+    /// Origin of the exporting web UI, so that absolute links can be rebuilt.
+    #[serde(default)]
+    pub origin: String,
+    /// Environment of the exporting web UI, given to annotations of older bundles without one.
+    #[serde(default)]
+    pub environment: AnnotationEnvironment,
+    pub annotations: Vec<Annotation>,
+}
+
+pub fn load_annotations() -> Vec<Annotation> {
+    LocalStorage::get(ANNOTATIONS_STORAGE_KEY).unwrap_or_default()
+}
+
+pub fn save_annotations(annotations: &[Annotation]) {
+    if let Err(err) = LocalStorage::set(ANNOTATIONS_STORAGE_KEY, annotations) {
+        log::error!("Cannot store annotations: {err:?}");
+    }
+}
+
+pub fn export_bundle(annotations: Vec<Annotation>) -> String {
+    let origin = web_sys::window()
+        .and_then(|window| window.location().origin().ok())
+        .unwrap_or_default();
+    serde_json::to_string_pretty(&AnnotationBundle {
+        format: BUNDLE_FORMAT,
+        origin,
+        environment: AnnotationEnvironment::current(),
+        annotations,
+    })
+    .expect("annotations are serializable")
+}
+
+/// Merge the annotations of an exported bundle, returning how many were new.
+pub fn import_bundle(
+    annotations: &mut Vec<Annotation>,
+    bundle: &str,
+) -> Result<usize, serde_json::Error> {
+    let bundle: AnnotationBundle = serde_json::from_str(bundle)?;
+    let mut added = 0;
+    for mut annotation in bundle.annotations {
+        if annotation.environment == AnnotationEnvironment::default() {
+            annotation.environment = bundle.environment.clone();
+        }
+        if !annotations
+            .iter()
+            .any(|existing| existing.id == annotation.id)
+        {
+            annotations.push(annotation);
+            added += 1;
+        }
+    }
+    annotations.sort_by_key(|annotation| annotation.created_at);
+    Ok(added)
+}
+
+/// Navigate to the view an annotation was taken in, restoring the trace highlight. Notes of
+/// another environment reload the UI in it, the error explains when that is not possible.
+pub fn open_annotation(
+    navigator: &yew_router::navigator::Navigator,
+    annotation: &Annotation,
+) -> Result<(), String> {
+    let current = AnnotationEnvironment::current();
+    let AnnotationEnvironment { base_path, name } = &annotation.environment;
+    if annotation.environment != current {
+        if *base_path == current.base_path {
+            match name {
+                Some(name) if !environment::is_configured(name) => {
+                    return Err(format!(
+                        "The note was taken in the environment {name}, which this web UI does not offer"
+                    ));
+                }
+                None => {
+                    return Err(format!(
+                        "The note was taken without environments, open {} in the environment it belongs to",
+                        annotation.path
+                    ));
+                }
+                Some(_) => {}
+            }
+        }
+        let url = environment::app_url(base_path, name.as_deref(), &annotation.path);
+        if let Some(window) = web_sys::window() {
+            let _ = window.location().set_href(&url);
+        }
+        return Ok(());
+    }
+    let path = annotation.path.as_str();
+    let (path, fragment) = path.split_once('#').unwrap_or((path, ""));
+    if let Some(route) = Route::recognize(path) {
+        navigator.push(&route);
+        if let Some(version) = fragment
+            .strip_prefix("trace-highlight-")
+            .and_then(|version| version.parse().ok())
+        {
+            set_highlight_hash(Some(version));
+        }
+    }
+    Ok(())
+}
+
+#[derive(Properties, PartialEq)]
+pub struct AnnotationBoxProps {
+    pub execution_id: ExecutionId,
+    /// Version shown by the view. When unset, the user can type one.
+    #[prop_or_default]
+    pub version: Option<VersionType>,
+    /// Source lines the note can be attached to.
+    #[prop_or_default]
+    pub locations: Vec<SourceLocation>,
+    /// View the note links back to.
+    pub route: Route,
+}
+
+/// Notes of one execution plus a form to add a new one.
+#[component(AnnotationBox)]
+pub fn annotation_box(
+    AnnotationBoxProps {
+        execution_id,
+        version,
+        locations,
+        route,
+    }: &AnnotationBoxProps,
+) -> Html {
+    let navigator = use_navigator().expect("navigator should be available");
+    let notifications =
+        use_context::<NotificationContext>().expect("NotificationContext should be provided");
+    let annotations = use_state(load_annotations);
+    let note = use_state(String::new);
+    // `None` attaches the note to the version only.
+    let location_idx = use_state(|| None::<usize>);
+    let typed_version = use_state(|| None::<VersionType>);
+
+    let environment = AnnotationEnvironment::current();
+    let execution_annotations: Vec<_> = annotations
+        .iter()
+        .filter(|annotation| {
+            annotation.execution_id == execution_id.id && annotation.environment == environment
+        })
+        .cloned()
+        .collect();
+
+    let on_note_input = {
+        let note = note.clone();
+        Callback::from(move |e: InputEvent| {
+            note.set(e.target_unchecked_into::<HtmlInputElement>().value());
+        })
+    };
+    let on_location_change = {
+        let location_idx = location_idx.clone();
+        Callback::from(move |e: Event| {
+            location_idx.set(
+                e.target_unchecked_into::<HtmlSelectElement>()
+                    .value()
+                    .parse()
+                    .ok(),
+            );
+        })
+    };
+    let on_version_change = {
+        let typed_version = typed_version.clone();
+        Callback::from(move |e: Event| {
+            typed_version.set(
+                e.target_unchecked_into::<HtmlInputElement>()
+                    .value()
+                    .trim()
+                    .parse()
+                    .ok(),
+            );
+        })
+    };
+    let on_add = {
+        let annotations = annotations.clone();
+        let note = note.clone();
+        let execution_id = execution_id.clone();
+        let location = location_idx.and_then(|idx| locations.get(idx).cloned());
+        let version = version.or(*typed_version);
+        let path = match (version, route) {
+            (Some(version), Route::ExecutionTrace { .. }) => {
+                format!("{}#trace-highlight-{version}", route.to_path())
+            }
+            _ => route.to_path(),
+        };
+        Callback::from(move |e: SubmitEvent| {
+            e.prevent_default();
+            let text = note.trim();
+            if text.is_empty() {
+                return;
+            }
+            let created_at = Utc::now();
+            let mut new_annotations = load_annotations();
+            new_annotations.push(Annotation {
+                id: format!(
+                    "{}-{}",
+                    created_at.timestamp_millis(),
+                    crate::util::trace_id()
+                ),
+                execution_id: execution_id.id.clone(),
+                version,
+                location: location.clone(),
+                note: text.to_string(),
+                path: path.clone(),
+                environment: AnnotationEnvironment::current(),
+                created_at,
+            });
+            save_annotations(&new_annotations);
+            annotations.set(new_annotations);
+            note.set(String::new());
+        })
+    };
+    let on_delete = |id: String| {
+        let annotations = annotations.clone();
+        Callback::from(move |_: MouseEvent| {
+            let mut new_annotations = load_annotations();
+            new_annotations.retain(|annotation| annotation.id != id);
+            save_annotations(&new_annotations);
+            annotations.set(new_annotations);
+        })
+    };
+    let on_open = |annotation: Annotation| {
+        let navigator = navigator.clone();
+        let notifications = notifications.clone();
+        Callback::from(move |_: MouseEvent| {
+            if let Err(err) = open_annotation(&navigator, &annotation) {
+                notifications.push(Notification::error(err));
+            }
+        })
+    };
+
+    html! {
+        <details class="annotation-box" open={!execution_annotations.is_empty()}>
+            <summary>
+                {"Notes"}
+                if !execution_annotations.is_empty() {
+                    <span class="filter-count">{ execution_annotations.len() }</span>
+                }
+            </summary>
+            <ul class="annotation-list">
+                { for execution_annotations.into_iter().map(|annotation| {
+                    let is_current = annotation.version.is_some() && annotation.version == *version;
+                    html! {
+                        <li class={classes!("annotation", is_current.then_some("current"))}>
+                            <button class="annotation-target" onclick={on_open(annotation.clone())}>
+                                { annotation.target() }
+                            </button>
+                            <span class="annotation-note">{ &annotation.note }</span>
+                            <button class="annotation-delete" title="Delete note" onclick={on_delete(annotation.id.clone())}>
+                                {"✕"}
+                            </button>
+                        </li>
+                    }
+                }) }
+            </ul>
+            <form class="annotation-form" onsubmit={on_add}>
+                if version.is_none() {
+                    <input
+                        type="number"
+                        min="0"
+                        placeholder="version (optional)"
+                        value={typed_version.map(|v| v.to_string()).unwrap_or_default()}
+                        onchange={on_version_change}
+                    />
+                }
+                if !locations.is_empty() {
+                    <select onchange={on_location_change}>
+                        <option value="" selected={location_idx.is_none()}>{"This version"}</option>
+                        { for locations.iter().enumerate().map(|(idx, location)| html! {
+                            <option value={idx.to_string()} selected={*location_idx == Some(idx)}>
+                                { format!("{}:{}", location.file, location.line) }
+                            </option>
+                        }) }
+                    </select>
+                }
+                <input
+                    type="text"
+                    placeholder="Add a note"
+                    value={(*note).clone()}
+                    oninput={on_note_input}
+                />
+                <button type="submit">{"Add"}</button>
+            </form>
+        </details>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn annotation(id: &str, created_at: i64) -> Annotation {
+        Annotation {
+            id: id.to_string(),
+            execution_id: "E_1".to_string(),
+            version: Some(3),
+            location: None,
+            note: format!("note {id}"),
+            path: "/execution/E_1/debug/3".to_string(),
+            environment: AnnotationEnvironment::default(),
+            created_at: DateTime::from_timestamp(created_at, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn import_skips_known_annotations() {
+        let mut annotations = vec![annotation("b", 2)];
+        let bundle = serde_json::to_string(&AnnotationBundle {
+            format: BUNDLE_FORMAT,
+            origin: String::new(),
+            environment: AnnotationEnvironment::default(),
+            annotations: vec![annotation("a", 1), annotation("b", 2)],
+        })
+        .unwrap();
+        assert_eq!(1, import_bundle(&mut annotations, &bundle).unwrap());
+        assert_eq!(
+            vec!["a", "b"],
+            annotations
+                .iter()
+                .map(|annotation| annotation.id.as_str())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn import_assigns_the_bundle_environment_to_older_annotations() {
+        let prod = AnnotationEnvironment {
+            base_path: "/obelisk".to_string(),
+            name: Some("prod".to_string()),
+        };
+        let dev = AnnotationEnvironment {
+            name: Some("dev".to_string()),
+            ..prod.clone()
+        };
+        let mut tagged = annotation("b", 2);
+        tagged.environment = dev.clone();
+        let bundle = serde_json::to_string(&AnnotationBundle {
+            format: BUNDLE_FORMAT,
+            origin: String::new(),
+            environment: prod.clone(),
+            annotations: vec![annotation("a", 1), tagged],
+        })
+        .unwrap();
+        let mut annotations = Vec::new();
+        import_bundle(&mut annotations, &bundle).unwrap();
+        assert_eq!(
+            vec![prod, dev],
+            annotations
+                .into_iter()
+                .map(|annotation| annotation.environment)
+                .collect::<Vec<_>>()
+        );
+    }
+}
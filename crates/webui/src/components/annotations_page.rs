@@ -0,0 +1,161 @@
+use crate::{
+    components::{
+        annotations::{
+            Annotation, AnnotationEnvironment, export_bundle, import_bundle, load_annotations,
+            open_annotation, save_annotations,
+        },
+        notification::{Notification, NotificationContext},
+    },
+    util::download::download,
+};
+use gloo::file::{
+    File,
+    callbacks::{FileReader, read_as_text},
+};
+use indexmap::IndexMap;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+use yew_router::prelude::use_navigator;
+
+#[component(AnnotationsPage)]
+pub fn annotations_page() -> Html {
+    let notifications =
+        use_context::<NotificationContext>().expect("NotificationContext should be provided");
+    let navigator = use_navigator().expect("navigator should be available");
+    let annotations = use_state(load_annotations);
+    // Keeps the pending file read alive.
+    let reader = use_mut_ref(|| None::<FileReader>);
+
+    let on_export = {
+        let annotations = annotations.clone();
+        Callback::from(move |_: MouseEvent| {
+            download(
+                "obelisk-annotations.json",
+                "application/json",
+                &export_bundle((*annotations).clone()),
+            );
+        })
+    };
+    let on_import = {
+        let annotations = annotations.clone();
+        let notifications = notifications.clone();
+        Callback::from(move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            let Some(file) = input.files().and_then(|files| files.get(0)) else {
+                return;
+            };
+            input.set_value("");
+            let annotations = annotations.clone();
+            let notifications = notifications.clone();
+            *reader.borrow_mut() = Some(read_as_text(&File::from(file), move |content| {
+                let result = content.map_err(|err| err.to_string()).and_then(|content| {
+                    let mut merged = load_annotations();
+                    let added =
+                        import_bundle(&mut merged, &content).map_err(|err| err.to_string())?;
+                    Ok((merged, added))
+                });
+                match result {
+                    Ok((merged, added)) => {
+                        save_annotations(&merged);
+                        annotations.set(merged);
+                        notifications.push(Notification::success(format!(
+                            "Imported {added} new annotations"
+                        )));
+                    }
+                    Err(err) => {
+                        notifications.push(Notification::error(format!(
+                            "Cannot import annotations: {err}"
+                        )));
+                    }
+                }
+            }));
+        })
+    };
+    let on_delete = |id: String| {
+        let annotations = annotations.clone();
+        Callback::from(move |_: MouseEvent| {
+            let mut new_annotations = load_annotations();
+            new_annotations.retain(|annotation| annotation.id != id);
+            save_annotations(&new_annotations);
+            annotations.set(new_annotations);
+        })
+    };
+    let on_clear = {
+        let annotations = annotations.clone();
+        Callback::from(move |_: MouseEvent| {
+            save_annotations(&[]);
+            annotations.set(Vec::new());
+        })
+    };
+    let on_open = |annotation: Annotation| {
+        let navigator = navigator.clone();
+        let notifications = notifications.clone();
+        Callback::from(move |_: MouseEvent| {
+            if let Err(err) = open_annotation(&navigator, &annotation) {
+                notifications.push(Notification::error(err));
+            }
+        })
+    };
+
+    // Notes of the current environment first, each other environment in its own sections.
+    let current = AnnotationEnvironment::current();
+    let mut by_execution: IndexMap<(Option<&AnnotationEnvironment>, &str), Vec<&Annotation>> =
+        IndexMap::new();
+    for annotation in annotations.iter() {
+        let environment = (annotation.environment != current).then_some(&annotation.environment);
+        by_execution
+            .entry((environment, annotation.execution_id.as_str()))
+            .or_default()
+            .push(annotation);
+    }
+    by_execution
+        .sort_by_cached_key(|(environment, _), _| environment.map(AnnotationEnvironment::label));
+
+    html! {
+        <div class="annotations-page">
+            <h3>{"Annotations"}</h3>
+            <p class="secondary-text">
+                {"Notes taken in the debugger and trace views. They are kept in this browser; export them to share a walkthrough. Notes of another environment open in it."}
+            </p>
+            <div class="annotations-actions">
+                <button onclick={on_export} disabled={annotations.is_empty()}>{"Export JSON"}</button>
+                <label class="annotations-import">
+                    {"Import JSON "}
+                    <input type="file" accept="application/json,.json" onchange={on_import} />
+                </label>
+                if !annotations.is_empty() {
+                    <button onclick={on_clear}>{"Delete all"}</button>
+                }
+            </div>
+            if by_execution.is_empty() {
+                <p>{"No annotations yet."}</p>
+            }
+            { for by_execution.into_iter().map(|((environment, execution_id), annotations)| html! {
+                <section class="annotations-execution">
+                    <h4>
+                        { execution_id }
+                        if let Some(environment) = environment {
+                            <span class="secondary-text">{ format!(" in {}", environment.label()) }</span>
+                        }
+                    </h4>
+                    <ul class="annotation-list">
+                        { for annotations.into_iter().map(|annotation| html! {
+                            <li class="annotation">
+                                <button class="annotation-target" onclick={on_open((*annotation).clone())}>
+                                    { annotation.target() }
+                                </button>
+                                <span class="annotation-note">{ &annotation.note }</span>
+                                <span class="secondary-text">
+                                    { annotation.created_at.format("%Y-%m-%d %H:%M").to_string() }
+                                </span>
+                                <button class="annotation-delete" title="Delete note" onclick={on_delete(annotation.id.clone())}>
+                                    {"✕"}
+                                </button>
+                            </li>
+                        }) }
+                    </ul>
+                </section>
+            }) }
+        </div>
+    }
+}
@@ -11,6 +11,7 @@ use crate::{
         ffqn_with_links::FfqnWithLinks,
         function_signature::FunctionSignature,
         notification::{Notification, NotificationContext},
+        wit_diff_page::WitDiffQuery,
     },
     grpc::{
         ffqn::FunctionFqn,
@@ -470,6 +471,16 @@ pub fn component_list_page(
                 .name;
             let component_id = component.component_id.clone();
             let component_sources = component_file_sources(&component.files);
+            // The same component in the active deployment, when it was built from a different digest.
+            let wit_diff_route = component_id.clone().and_then(|from| {
+                components_by_id
+                    .keys()
+                    .find(|active| active.name == from.name && active.digest != from.digest)
+                    .map(|to| Route::ComponentWitDiff {
+                        from,
+                        to: to.clone(),
+                    })
+            });
             // Link back to the deployment this component belongs to (from the query,
             // else the active deployment). "Deployments" already lives in the header nav.
             let component_deployment_id = deployment_id
@@ -571,8 +582,24 @@ pub fn component_list_page(
                     {imported_interfaces}
                 </>},
                 ComponentDetailTab::Wit => {
+                    let wit_diff_link = wit_diff_route.map(|route| html! {
+                        <p class="component-section-help">
+                            <Link<Route, WitDiffQuery>
+                                to={route}
+                                query={WitDiffQuery {
+                                    from_deployment_id: deployment_id.clone(),
+                                    to_deployment_id: None,
+                                }}
+                            >
+                                {"Compare WIT with the active deployment"}
+                            </Link<Route, WitDiffQuery>>
+                        </p>
+                    });
                     if let Some(wit) = wit_state.deref() {
-                        html! { <CodeBlock source={wit.clone()} /> }
+                        html! {<>
+                            { wit_diff_link }
+                            <CodeBlock source={wit.clone()} />
+                        </>}
                     } else if *wit_loaded {
                         html! {
                             <p class="component-empty-state">
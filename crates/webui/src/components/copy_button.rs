@@ -5,11 +5,13 @@ use yew::prelude::*;
 pub struct CopyButtonProps {
     /// Text copied to the clipboard on click.
     pub text: String,
+    #[prop_or(AttrValue::Static("Copy"))]
+    pub label: AttrValue,
 }
 
 /// Button that copies `text` to the clipboard and briefly confirms.
 #[component(CopyButton)]
-pub fn copy_button(CopyButtonProps { text }: &CopyButtonProps) -> Html {
+pub fn copy_button(CopyButtonProps { text, label }: &CopyButtonProps) -> Html {
     let copied = use_state(|| false);
 
     let onclick = {
@@ -34,7 +36,7 @@ pub fn copy_button(CopyButtonProps { text }: &CopyButtonProps) -> Html {
 
     html! {
         <button class="action-button copy-button" {onclick}>
-            { if *copied { "Copied!" } else { "Copy" } }
+            if *copied { {"Copied!"} } else { {label} }
         </button>
     }
 }
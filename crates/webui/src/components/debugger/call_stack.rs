@@ -0,0 +1,239 @@
+//! Call-stack panel of the debugger: every frame of every backtrace in the ancestry chain,
+//! with inlined frames marked and library frames set apart from the component's own code.
+
+use crate::grpc::grpc_client::{ExecutionId, WasmBacktrace};
+use wasm_bindgen::JsCast;
+use yew::prelude::*;
+
+/// Path fragments of source files that belong to the toolchain or to dependencies.
+const LIBRARY_PATH_MARKERS: [&str; 5] = [
+    "/rustc/",
+    "/.cargo/",
+    "/cargo/registry/",
+    "/cargo/git/",
+    "/rustlib/",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StackRow {
+    /// DOM id of the symbol's source block in the backtrace view.
+    pub anchor: String,
+    /// Physical frame index counted from the outermost frame.
+    pub frame_idx: usize,
+    pub func_name: String,
+    pub file: Option<String>,
+    pub line: Option<u32>,
+    pub col: Option<u32>,
+    /// Inlined into the next symbol of the same physical frame.
+    pub is_inlined: bool,
+    pub is_library: bool,
+}
+impl StackRow {
+    pub fn location(&self) -> String {
+        match (&self.file, self.line, self.col) {
+            (Some(file), Some(line), Some(col)) => format!("{file}:{line}:{col}"),
+            (Some(file), Some(line), None) => format!("{file}:{line}"),
+            (Some(file), _, _) => file.clone(),
+            (None, _, _) => "unknown location".to_string(),
+        }
+    }
+}
+
+pub fn is_library_file(file: Option<&str>) -> bool {
+    file.is_none_or(|file| {
+        LIBRARY_PATH_MARKERS
+            .iter()
+            .any(|marker| file.contains(marker))
+    })
+}
+
+/// DOM id of a whole frame in the backtrace view.
+pub fn frame_anchor(execution_id: &ExecutionId, frame_idx: usize) -> String {
+    format!("frame-{execution_id}-{frame_idx}")
+}
+
+/// DOM id of the source block of a symbol in the backtrace view.
+pub fn symbol_anchor(execution_id: &ExecutionId, frame_idx: usize, symbol_idx: usize) -> String {
+    format!("{}-{symbol_idx}", frame_anchor(execution_id, frame_idx))
+}
+
+/// Rows from the innermost call to the outermost one.
+pub fn stack_rows(execution_id: &ExecutionId, backtrace: &WasmBacktrace) -> Vec<StackRow> {
+    let frame_count = backtrace.frames.len();
+    backtrace
+        .frames
+        .iter()
+        .enumerate()
+        .flat_map(|(i, frame)| {
+            let frame_idx = frame_count - 1 - i;
+            let symbol_count = frame.symbols.len();
+            let rows: Vec<_> = if symbol_count == 0 {
+                vec![StackRow {
+                    anchor: frame_anchor(execution_id, frame_idx),
+                    frame_idx,
+                    func_name: frame.func_name.clone(),
+                    file: None,
+                    line: None,
+                    col: None,
+                    is_inlined: false,
+                    is_library: true,
+                }]
+            } else {
+                // Wasmtime lists the inlined functions first, the physical function last.
+                frame
+                    .symbols
+                    .iter()
+                    .enumerate()
+                    .map(|(symbol_idx, symbol)| StackRow {
+                        anchor: symbol_anchor(execution_id, frame_idx, symbol_idx),
+                        frame_idx,
+                        func_name: symbol
+                            .func_name
+                            .clone()
+                            .unwrap_or_else(|| frame.func_name.clone()),
+                        file: symbol.file.clone(),
+                        line: symbol.line,
+                        col: symbol.col,
+                        is_inlined: symbol_idx + 1 < symbol_count,
+                        is_library: is_library_file(symbol.file.as_deref()),
+                    })
+                    .collect()
+            };
+            rows
+        })
+        .collect()
+}
+
+/// Scroll to the symbol's source block, or to its frame when the source is not shown.
+fn scroll_to(anchor: &str) {
+    let Some(document) = web_sys::window().and_then(|window| window.document()) else {
+        return;
+    };
+    let Some(element) = document.get_element_by_id(anchor).or_else(|| {
+        let (frame, _symbol_idx) = anchor.rsplit_once('-')?;
+        document.get_element_by_id(frame)
+    }) else {
+        return;
+    };
+    element.scroll_into_view();
+    if let Some(element) = element.dyn_ref::<web_sys::HtmlElement>() {
+        let style = element.style();
+        let _ = style.set_property("transition", "none");
+        let _ = style.set_property("outline", "2px solid #5fd7ff");
+        let _ = element.offset_height();
+        let _ = style.set_property("transition", "outline-color 1.5s ease-out");
+        let _ = style.set_property("outline-color", "transparent");
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct CallStackPanelProps {
+    /// One stack per execution, from the leaf to the top-level execution.
+    pub stacks: Vec<(ExecutionId, Vec<StackRow>)>,
+}
+
+#[component(CallStackPanel)]
+pub fn call_stack_panel(CallStackPanelProps { stacks }: &CallStackPanelProps) -> Html {
+    if stacks.iter().all(|(_, rows)| rows.is_empty()) {
+        return Html::default();
+    }
+    let render_stack = |(execution_id, rows): &(ExecutionId, Vec<StackRow>)| {
+        let mut previous_is_library = None;
+        let rows = rows.iter().map(|row| {
+            let separator = previous_is_library.is_some_and(|previous| previous != row.is_library);
+            previous_is_library = Some(row.is_library);
+            let onclick = {
+                let anchor = row.anchor.clone();
+                Callback::from(move |_: MouseEvent| scroll_to(&anchor))
+            };
+            html! {
+                <li
+                    class={classes!(
+                        "call-stack-frame",
+                        row.is_library.then_some("is-library"),
+                        separator.then_some("group-start"),
+                    )}
+                    title={row.location()}
+                    {onclick}
+                >
+                    <span class="call-stack-index">{ row.frame_idx }</span>
+                    <span class="call-stack-function">{ &row.func_name }</span>
+                    if row.is_inlined {
+                        <span class="call-stack-inlined">{"inlined"}</span>
+                    }
+                    <span class="call-stack-location">{ row.location() }</span>
+                </li>
+            }
+        });
+        html! {
+            <div class="call-stack">
+                <div class="call-stack-execution">{ execution_id.to_string() }</div>
+                <ol>
+                    { for rows }
+                </ol>
+            </div>
+        }
+    };
+    html! {
+        <details class="call-stack-panel" open=true>
+            <summary>{"Call stack"}</summary>
+            { for stacks.iter().map(render_stack) }
+        </details>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grpc::grpc_client::{FrameInfo, FrameSymbol};
+
+    fn symbol(func_name: &str, file: &str, line: u32) -> FrameSymbol {
+        FrameSymbol {
+            func_name: Some(func_name.to_string()),
+            file: Some(file.to_string()),
+            line: Some(line),
+            col: None,
+        }
+    }
+
+    #[test]
+    fn inlined_and_library_frames_are_marked() {
+        let execution_id = ExecutionId {
+            id: "E_1".to_string(),
+        };
+        let backtrace = WasmBacktrace {
+            frames: vec![
+                FrameInfo {
+                    module: "workflow".to_string(),
+                    func_name: "helper".to_string(),
+                    symbols: vec![
+                        symbol(
+                            "core::option::Option::unwrap",
+                            "/rustc/abc/library/core/src/option.rs",
+                            10,
+                        ),
+                        symbol("helper", "src/lib.rs", 20),
+                    ],
+                },
+                FrameInfo {
+                    module: "workflow".to_string(),
+                    func_name: "run".to_string(),
+                    symbols: vec![symbol("run", "src/lib.rs", 5)],
+                },
+            ],
+            ..Default::default()
+        };
+        let rows: Vec<_> = stack_rows(&execution_id, &backtrace)
+            .into_iter()
+            .map(|row| (row.anchor, row.is_inlined, row.is_library))
+            .collect();
+        assert_eq!(
+            vec![
+                ("frame-E_1-1-0".to_string(), true, true),
+                ("frame-E_1-1-1".to_string(), false, false),
+                ("frame-E_1-0-0".to_string(), false, false),
+            ],
+            rows
+        );
+    }
+}
@@ -1,9 +1,17 @@
 use crate::{
-    app::{Route, query::BacktraceVersionsPath},
+    app::{AppState, Route, query::BacktraceVersionsPath},
     components::{
+        annotations::{AnnotationBox, SourceLocation},
         code::syntect_code_block::{
             DEFAULT_CONTEXT_LINES, SyntectCodeBlock, highlight_code_line_by_line,
         },
+        debugger::call_stack::{CallStackPanel, frame_anchor, stack_rows, symbol_anchor},
+        debugger::inspector::{InspectorPanel, inspect_events},
+        debugger::persist_backtraces::{PersistBacktracesAction, missing_backtrace_versions},
+        debugger::run_to::{
+            RunToBar, RunToCondition, SearchDirection, candidate_versions, find_event_version,
+            location_matches,
+        },
         debugger::version_slider::VersionSlider,
         execution_detail::utils::{compute_join_next_to_response, event_to_detail},
         execution_header::{ExecutionHeader, ExecutionLink},
@@ -284,6 +292,8 @@ pub fn debugger_view(
     let debugger_state = use_reducer_eq(DebuggerState::default);
     let notifications =
         use_context::<NotificationContext>().expect("NotificationContext should be provided");
+    let app_state =
+        use_context::<AppState>().expect("AppState context is set when starting the App");
 
     // 1. Toggle for hiding frame locations
     let hide_frames = use_state(|| true);
@@ -783,7 +793,7 @@ pub fn debugger_view(
                                 });
                             }
 
-                            for symbol in &frame.symbols {
+                            for (symbol_idx, symbol) in frame.symbols.iter().enumerate() {
                                 if !*hide_frames {
                                         let location = match (&symbol.file, symbol.line, symbol.col) {
                                         (Some(file), Some(line), Some(col)) => format!("{file}:{line}:{col}"),
@@ -825,18 +835,27 @@ pub fn debugger_view(
                                             })
                                         };
                                         frame_html.push(html! {
-                                            <SyntectCodeBlock
+                                            <div
                                                 key={map_key}
-                                                source={source.clone()}
-                                                focus_line={Some(line as usize)}
-                                                lines_above={cb_lines_above}
-                                                lines_below={cb_lines_below}
-                                                on_expand={on_expand}
-                                            />
+                                                class="frame-source"
+                                                id={symbol_anchor(curr_exec_id, frame_idx, symbol_idx)}
+                                            >
+                                                <SyntectCodeBlock
+                                                    source={source.clone()}
+                                                    focus_line={Some(line as usize)}
+                                                    lines_above={cb_lines_above}
+                                                    lines_below={cb_lines_below}
+                                                    on_expand={on_expand}
+                                                />
+                                            </div>
                                         });
                                     }
                             }
-                            html! { <div class="frame-container">{frame_html}</div> }
+                            html! {
+                                <div class="frame-container" id={frame_anchor(curr_exec_id, frame_idx)}>
+                                    {frame_html}
+                                </div>
+                            }
                         }).collect::<Html>()
                     }
                 }
@@ -883,6 +902,199 @@ pub fn debugger_view(
         }
     };
 
+    let call_stacks: Vec<_> = ancestry
+        .iter()
+        .filter_map(|(curr_exec_id, curr_path)| {
+            let backtrace = backtraces_state
+                .0
+                .get(&(curr_exec_id.clone(), curr_path.last()))?
+                .as_ref()
+                .ok()?
+                .wasm_backtrace
+                .as_ref()?;
+            Some((curr_exec_id.clone(), stack_rows(curr_exec_id, backtrace)))
+        })
+        .collect();
+
+    // Current position of the leaf execution as `[min, max)`.
+    let leaf_window = match leaf_backtrace_response {
+        Some(Ok(GetBacktraceResponse {
+            wasm_backtrace: Some(wasm_backtrace),
+            ..
+        })) => (
+            wasm_backtrace.version_min_including,
+            wasm_backtrace.version_max_excluding,
+        ),
+        _ => (leaf_version, leaf_version + 1),
+    };
+    let inspected = inspect_events(
+        leaf_events,
+        leaf_window,
+        &join_next_version_to_response,
+        &app_state,
+    );
+
+    let run_to_searching = use_state(|| false);
+    // Bumped by every search, cancel, execution change and unmount. A search only reports and
+    // navigates while it is the latest one.
+    let run_to_generation = use_mut_ref(|| 0_u64);
+    {
+        let run_to_generation = run_to_generation.clone();
+        let run_to_searching = run_to_searching.clone();
+        use_effect_with(execution_id.clone(), move |_| {
+            move || {
+                *run_to_generation.borrow_mut() += 1;
+                run_to_searching.set(false);
+            }
+        });
+    }
+    let on_run_to = {
+        let navigator = navigator.clone();
+        let execution_id = execution_id.clone();
+        let versions = versions.clone();
+        let debugger_state = debugger_state.clone();
+        let run_to_searching = run_to_searching.clone();
+        let run_to_generation = run_to_generation.clone();
+        let notifications = notifications.clone();
+        let window = leaf_window;
+        let backtrace_versions = leaf_backtrace_versions.clone();
+        Callback::from(
+            move |(condition, direction): (RunToCondition, SearchDirection)| {
+                let navigate = {
+                    let navigator = navigator.clone();
+                    let execution_id = execution_id.clone();
+                    let versions = versions.clone();
+                    move |version: VersionType| {
+                        navigator.push(&Route::ExecutionDebuggerWithVersions {
+                            execution_id: execution_id.clone(),
+                            versions: versions.change(version),
+                        });
+                    }
+                };
+                let RunToCondition::Location { file, line } = condition.clone() else {
+                    let events = debugger_state
+                        .events
+                        .get(&execution_id)
+                        .map(Vec::as_slice)
+                        .unwrap_or_default();
+                    let dummy_response_map = HashMap::new();
+                    let responses = debugger_state
+                        .responses
+                        .get(&execution_id)
+                        .unwrap_or(&dummy_response_map);
+                    let join_next_version_to_response =
+                        compute_join_next_to_response(events, responses);
+                    match find_event_version(
+                        &condition,
+                        events,
+                        &join_next_version_to_response,
+                        &backtrace_versions,
+                        window,
+                        direction,
+                    ) {
+                        Some(version) => navigate(version),
+                        None => notifications.push(Notification::info("No matching event found")),
+                    }
+                    return;
+                };
+                let candidates = candidate_versions(&backtrace_versions, window, direction);
+                let execution_id = execution_id.clone();
+                let run_to_searching = run_to_searching.clone();
+                let notifications = notifications.clone();
+                let search = {
+                    let mut generation = run_to_generation.borrow_mut();
+                    *generation += 1;
+                    *generation
+                };
+                let is_current = {
+                    let run_to_generation = run_to_generation.clone();
+                    move || *run_to_generation.borrow() == search
+                };
+                run_to_searching.set(true);
+                wasm_bindgen_futures::spawn_local(async move {
+                    let mut execution_client =
+                        grpc_client::execution_repository_client::ExecutionRepositoryClient::new(
+                            crate::auth::client(),
+                        );
+                    let mut found = None;
+                    for version in candidates {
+                        let response = execution_client
+                            .get_backtrace(tonic::Request::new(grpc_client::GetBacktraceRequest {
+                                execution_id: Some(execution_id.clone()),
+                                filter: Some(get_backtrace_request::Filter::Specific(
+                                    get_backtrace_request::Specific { version },
+                                )),
+                            }))
+                            .await;
+                        // Cancelled or superseded, the state belongs to another search.
+                        if !is_current() {
+                            return;
+                        }
+                        match response {
+                            Ok(response) => {
+                                if response
+                                    .into_inner()
+                                    .wasm_backtrace
+                                    .is_some_and(|backtrace| {
+                                        location_matches(&backtrace, &file, line)
+                                    })
+                                {
+                                    found = Some(version);
+                                    break;
+                                }
+                            }
+                            Err(err) if err.code() == tonic::Code::NotFound => {}
+                            Err(err) => {
+                                error!(
+                                    "Failed to get backtrace of {execution_id} at {version}: {err:?}"
+                                );
+                                notifications.push(Notification::error(format!(
+                                    "Failed to load backtrace: {}",
+                                    err.message()
+                                )));
+                                break;
+                            }
+                        }
+                    }
+                    run_to_searching.set(false);
+                    match found {
+                        Some(version) => navigate(version),
+                        None => {
+                            notifications.push(Notification::info("No matching location found"))
+                        }
+                    }
+                });
+            },
+        )
+    };
+    let on_run_to_cancel = {
+        let run_to_searching = run_to_searching.clone();
+        Callback::from(move |()| {
+            *run_to_generation.borrow_mut() += 1;
+            run_to_searching.set(false);
+        })
+    };
+
+    // Source lines of the leaf execution's own code that a note can be attached to.
+    let mut annotation_locations: Vec<SourceLocation> = Vec::new();
+    for row in call_stacks
+        .first()
+        .filter(|(curr_exec_id, _)| curr_exec_id == execution_id)
+        .into_iter()
+        .flat_map(|(_, rows)| rows.iter())
+        .filter(|row| !row.is_library)
+    {
+        if let (Some(file), Some(line)) = (&row.file, row.line) {
+            let location = SourceLocation {
+                file: file.clone(),
+                line,
+            };
+            if !annotation_locations.contains(&location) {
+                annotation_locations.push(location);
+            }
+        }
+    }
+
     let on_advanced = {
         let navigator = navigator.clone();
         let execution_id = execution_id.clone();
@@ -894,76 +1106,27 @@ pub fn debugger_view(
         })
     };
 
-    let populating_backtraces = use_state(|| false);
-    let on_populate_backtraces = {
-        let execution_id = execution_id.clone();
+    let on_backtraces_persisted = {
         let debugger_state = debugger_state.clone();
         let backtraces_state = backtraces_state.clone();
         let backtraces_reload = backtraces_reload.clone();
-        let populating_backtraces = populating_backtraces.clone();
-        let notifications = notifications.clone();
-        Callback::from(move |_| {
-            let execution_id = execution_id.clone();
-            let debugger_state = debugger_state.clone();
-            let backtraces_state = backtraces_state.clone();
-            let backtraces_reload = backtraces_reload.clone();
-            let populating_backtraces = populating_backtraces.clone();
-            let notifications = notifications.clone();
-
-            populating_backtraces.set(true);
-            wasm_bindgen_futures::spawn_local(async move {
-                let mut client =
-                    grpc_client::execution_repository_client::ExecutionRepositoryClient::new(
-                        crate::auth::client(),
-                    );
-                match client
-                    .persist_execution_backtraces(grpc_client::PersistExecutionBacktracesRequest {
-                        execution_id: Some(execution_id.clone()),
-                    })
-                    .await
-                {
-                    Ok(response) => {
-                        let count = response.into_inner().persisted_backtrace_count;
-                        if count == 0 {
-                            notifications
-                                .push(Notification::info("No new backtraces were persisted"));
-                        } else {
-                            let suffix = if count == 1 { "" } else { "s" };
-                            notifications.push(Notification::success(format!(
-                                "Persisted {count} backtrace{suffix}"
-                            )));
-                        }
-                        debugger_state.dispatch(DebuggerStateAction::Reload);
-                        backtraces_state.dispatch(BacktracesStateAction::Clear);
-                        backtraces_reload.set(backtraces_reload.wrapping_add(1));
-                    }
-                    Err(err) => {
-                        error!("Failed to persist backtraces for {execution_id}: {err:?}");
-                        notifications.push(Notification::error(format!(
-                            "Failed to populate backtraces: {}",
-                            err.message()
-                        )));
-                    }
-                }
-                populating_backtraces.set(false);
-            });
+        Callback::from(move |_count: u32| {
+            debugger_state.dispatch(DebuggerStateAction::Reload);
+            backtraces_state.dispatch(BacktracesStateAction::Clear);
+            backtraces_reload.set(backtraces_reload.wrapping_add(1));
         })
     };
     let populate_backtraces_action = html! {
-        <div class="action-container">
-            <button
-                class="action-button"
-                onclick={on_populate_backtraces}
-                disabled={*populating_backtraces}
-                title="Replay this execution and persist any missing call-site backtraces"
-            >
-                if *populating_backtraces {
-                    {"Populating..."}
-                } else {
-                    {"Populate backtraces"}
-                }
-            </button>
-        </div>
+        <PersistBacktracesAction
+            execution_id={execution_id.clone()}
+            history_versions={leaf_events
+                .iter()
+                .filter(|event| matches!(event.event, Some(execution_event::Event::HistoryVariant(_))))
+                .map(|event| event.version)
+                .collect::<Vec<_>>()}
+            missing_versions={missing_backtrace_versions(leaf_events)}
+            on_persisted={on_backtraces_persisted}
+        />
     };
 
     html! {<>
@@ -979,6 +1142,20 @@ pub fn debugger_view(
             selected_version={leaf_version}
             on_version_change={on_version_change}
         />
+        <RunToBar
+            searching={*run_to_searching}
+            on_run={on_run_to}
+            on_cancel={on_run_to_cancel}
+        />
+        <AnnotationBox
+            execution_id={execution_id.clone()}
+            version={Some(leaf_version)}
+            locations={annotation_locations}
+            route={Route::ExecutionDebuggerWithVersions {
+                execution_id: execution_id.clone(),
+                versions: versions.clone(),
+            }}
+        />
 
         <div class="trace-layout-container">
             <div class="trace-view">
@@ -991,9 +1168,12 @@ pub fn debugger_view(
                     />
                     <label for="hide-frames">{"Hide locations (source only)"}</label>
                 </div>
+                <CallStackPanel stacks={call_stacks} />
                 {backtrace_view}
             </div>
             <div class="trace-detail">
+                // Values of another execution must not be compared with the ones seen so far.
+                <InspectorPanel key={execution_id.to_string()} {inspected} />
                 {execution_log}
             </div>
         </div>
@@ -0,0 +1,415 @@
+//! Values flowing in and out of the execution at the selected backtrace: params of the
+//! requests made there and the responses delivered to it. Replay is deterministic,
+//! so these are the closest equivalent of local variables.
+
+use crate::{
+    app::AppState,
+    grpc::{
+        ffqn::FunctionFqn,
+        grpc_client::{
+            ExecutionEvent, JoinSetResponseEvent, SupportedFunctionResult,
+            execution_event::{
+                self,
+                history_event::{self, join_set_request},
+            },
+            join_set_response_event, supported_function_result,
+        },
+        version::VersionType,
+    },
+};
+use chrono::{DateTime, Utc};
+use hashbrown::HashMap;
+use yew::prelude::*;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Variable {
+    /// Identifies the same variable across versions, e.g. `ffqn(param)`.
+    pub key: String,
+    pub name: String,
+    pub wit_type: Option<String>,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct InspectedEvent {
+    pub version: VersionType,
+    pub title: String,
+    pub variables: Vec<Variable>,
+}
+
+fn request_params(ffqn: &FunctionFqn, params: &[u8], app_state: &AppState) -> Vec<Variable> {
+    let values: Vec<serde_json::Value> = match serde_json::from_slice(params) {
+        Ok(values) => values,
+        Err(err) => {
+            return vec![Variable {
+                key: format!("{ffqn}(params)"),
+                name: "params".to_string(),
+                wit_type: None,
+                value: format!("cannot parse `{}`: {err}", String::from_utf8_lossy(params)),
+            }];
+        }
+    };
+    let param_types = app_state
+        .ffqns_to_details
+        .get(ffqn)
+        .map(|(function_detail, _)| &function_detail.params)
+        .filter(|param_types| param_types.len() == values.len());
+    values
+        .into_iter()
+        .enumerate()
+        .map(|(idx, value)| {
+            let param = param_types.map(|param_types| &param_types[idx]);
+            let name = param.map_or_else(|| format!("#{idx}"), |param| param.name.clone());
+            Variable {
+                key: format!("{ffqn}({name})"),
+                wit_type: param
+                    .and_then(|param| param.r#type.as_ref())
+                    .map(|wit_type| wit_type.wit_type.clone()),
+                name,
+                value: value.to_string(),
+            }
+        })
+        .collect()
+}
+
+fn response_variable(
+    ffqn: Option<&FunctionFqn>,
+    result: &SupportedFunctionResult,
+    app_state: &AppState,
+) -> Option<Variable> {
+    let (name, value) = match result.value.as_ref()? {
+        supported_function_result::Value::Ok(ok) => (
+            "ok",
+            ok.return_value
+                .as_ref()
+                .map(|any| String::from_utf8_lossy(&any.value).into_owned())
+                .unwrap_or_default(),
+        ),
+        supported_function_result::Value::Error(error) => (
+            "err",
+            error
+                .return_value
+                .as_ref()
+                .map(|any| String::from_utf8_lossy(&any.value).into_owned())
+                .unwrap_or_default(),
+        ),
+        supported_function_result::Value::ExecutionFailure(failure) => (
+            "execution failure",
+            failure
+                .reason
+                .clone()
+                .unwrap_or_else(|| failure.kind().as_str_name().to_string()),
+        ),
+    };
+    let wit_type = ffqn
+        .and_then(|ffqn| app_state.ffqns_to_details.get(ffqn))
+        .and_then(|(function_detail, _)| function_detail.return_type.as_ref())
+        .map(|wit_type| wit_type.wit_type.clone())
+        .or_else(|| result.wit_type_inline.clone());
+    Some(Variable {
+        key: format!(
+            "{}(return)",
+            ffqn.map(ToString::to_string).unwrap_or_default()
+        ),
+        name: name.to_string(),
+        wit_type,
+        value,
+    })
+}
+
+/// Requests and responses of the events in the backtrace window `[min, max)`.
+pub fn inspect_events(
+    events: &[ExecutionEvent],
+    (min, max): (VersionType, VersionType),
+    join_next_version_to_response: &HashMap<VersionType, &JoinSetResponseEvent>,
+    app_state: &AppState,
+) -> Vec<InspectedEvent> {
+    events
+        .iter()
+        .filter(|event| event.version >= min && event.version < max)
+        .filter_map(|event| {
+            let Some(execution_event::Event::HistoryVariant(history)) = event.event.as_ref() else {
+                return None;
+            };
+            let (title, variables) = match history.event.as_ref()? {
+                history_event::Event::JoinSetRequest(request) => {
+                    match request.join_set_request.as_ref()? {
+                        join_set_request::JoinSetRequest::ChildExecutionRequest(child) => {
+                            let ffqn = FunctionFqn::from(child.function_name.clone()?);
+                            let params = child
+                                .params
+                                .as_ref()
+                                .map(|params| request_params(&ffqn, &params.value, app_state))
+                                .unwrap_or_default();
+                            (format!("Submit {ffqn}"), params)
+                        }
+                        join_set_request::JoinSetRequest::DelayRequest(delay) => (
+                            "Delay request".to_string(),
+                            delay
+                                .expires_at
+                                .map(|expires_at| Variable {
+                                    key: "delay(expires_at)".to_string(),
+                                    name: "expires_at".to_string(),
+                                    wit_type: None,
+                                    value: DateTime::<Utc>::from(expires_at).to_string(),
+                                })
+                                .into_iter()
+                                .collect(),
+                        ),
+                    }
+                }
+                history_event::Event::Persist(persist) => (
+                    "Persist".to_string(),
+                    persist
+                        .data
+                        .as_ref()
+                        .map(|data| Variable {
+                            key: "persist(data)".to_string(),
+                            name: "data".to_string(),
+                            wit_type: None,
+                            value: String::from_utf8_lossy(&data.value).into_owned(),
+                        })
+                        .into_iter()
+                        .collect(),
+                ),
+                history_event::Event::Stub(stub) => (
+                    format!(
+                        "Stub {}",
+                        stub.execution_id
+                            .as_ref()
+                            .map(ToString::to_string)
+                            .unwrap_or_default()
+                    ),
+                    Vec::new(),
+                ),
+                history_event::Event::Schedule(schedule) => (
+                    format!(
+                        "Schedule {}",
+                        schedule
+                            .execution_id
+                            .as_ref()
+                            .map(ToString::to_string)
+                            .unwrap_or_default()
+                    ),
+                    Vec::new(),
+                ),
+                history_event::Event::JoinNext(join_next) => {
+                    let ffqn = join_next.function.clone().map(FunctionFqn::from);
+                    let variables = match join_next_version_to_response
+                        .get(&event.version)
+                        .and_then(|response| response.response.as_ref())
+                    {
+                        Some(join_set_response_event::Response::ChildExecutionFinished(
+                            finished,
+                        )) => finished
+                            .value
+                            .as_ref()
+                            .and_then(|result| response_variable(ffqn.as_ref(), result, app_state))
+                            .into_iter()
+                            .collect(),
+                        Some(join_set_response_event::Response::DelayFinished(delay)) => {
+                            vec![Variable {
+                                key: "delay(finished)".to_string(),
+                                name: "finished".to_string(),
+                                wit_type: None,
+                                value: if delay.success {
+                                    "expired"
+                                } else {
+                                    "cancelled"
+                                }
+                                .to_string(),
+                            }]
+                        }
+                        None => Vec::new(),
+                    };
+                    let title = match &ffqn {
+                        Some(ffqn) => format!("Await {ffqn}"),
+                        None => "Await next".to_string(),
+                    };
+                    (title, variables)
+                }
+                _ => return None,
+            };
+            Some(InspectedEvent {
+                version: event.version,
+                title,
+                variables,
+            })
+        })
+        .collect()
+}
+
+#[derive(Properties, PartialEq)]
+pub struct InspectorPanelProps {
+    pub inspected: Vec<InspectedEvent>,
+}
+
+#[component(InspectorPanel)]
+pub fn inspector_panel(InspectorPanelProps { inspected }: &InspectorPanelProps) -> Html {
+    // Values seen while stepping, per variable key, in the order they were visited.
+    let seen = use_mut_ref(HashMap::<String, Vec<(VersionType, String)>>::new);
+    use_effect_with(inspected.clone(), {
+        let seen = seen.clone();
+        move |inspected| {
+            let mut seen = seen.borrow_mut();
+            for event in inspected {
+                for variable in &event.variables {
+                    let values = seen.entry(variable.key.clone()).or_default();
+                    if !values.iter().any(|(version, _)| *version == event.version) {
+                        values.push((event.version, variable.value.clone()));
+                    }
+                }
+            }
+        }
+    });
+
+    if inspected.is_empty() {
+        return html! {
+            <details class="inspector-panel" open=true>
+                <summary>{"Values"}</summary>
+                <p class="secondary-text">{"No requests or responses at this version."}</p>
+            </details>
+        };
+    }
+    let seen = seen.borrow();
+    let render_variable = |version: VersionType, variable: &Variable| {
+        // The latest differing value seen at another version.
+        let previous = seen.get(&variable.key).and_then(|values| {
+            values
+                .iter()
+                .rev()
+                .find(|(seen_version, value)| *seen_version != version && *value != variable.value)
+        });
+        html! {
+            <tr class={classes!(previous.is_some().then_some("changed"))}>
+                <td class="inspector-name" title={variable.wit_type.clone()}>
+                    { &variable.name }
+                    if let Some(wit_type) = &variable.wit_type {
+                        <span class="inspector-type">{": "}{ wit_type }</span>
+                    }
+                </td>
+                <td class="inspector-value">
+                    <code>{ &variable.value }</code>
+                    if let Some((previous_version, previous_value)) = previous {
+                        <div class="inspector-previous">
+                            { format!("was {previous_value} at version {previous_version}") }
+                        </div>
+                    }
+                </td>
+            </tr>
+        }
+    };
+    html! {
+        <details class="inspector-panel" open=true>
+            <summary>{"Values"}</summary>
+            { for inspected.iter().map(|event| html! {
+                <div class="inspector-event">
+                    <div class="inspector-title">
+                        { format!("{}. {}", event.version, event.title) }
+                    </div>
+                    if !event.variables.is_empty() {
+                        <table>
+                            { for event.variables.iter().map(|variable| render_variable(event.version, variable)) }
+                        </table>
+                    }
+                </div>
+            }) }
+        </details>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grpc::grpc_client::{
+        FunctionDetail, FunctionName, FunctionParameter, WitType,
+        execution_event::{HistoryEvent, history_event::JoinSetRequest},
+    };
+
+    fn ffqn() -> FunctionFqn {
+        FunctionFqn::from(FunctionName {
+            interface_name: "ns:pkg/ifc".to_string(),
+            function_name: "fn".to_string(),
+        })
+    }
+
+    fn app_state() -> AppState {
+        AppState {
+            components_by_id: Default::default(),
+            components_by_exported_ifc: Default::default(),
+            ffqns_to_details: HashMap::new(),
+            current_deployment_id: None,
+        }
+    }
+
+    fn child_request(ffqn: &FunctionFqn, params: &[u8]) -> ExecutionEvent {
+        ExecutionEvent {
+            version: 3,
+            event: Some(execution_event::Event::HistoryVariant(HistoryEvent {
+                event: Some(history_event::Event::JoinSetRequest(JoinSetRequest {
+                    join_set_request: Some(
+                        join_set_request::JoinSetRequest::ChildExecutionRequest(
+                            join_set_request::ChildExecutionRequest {
+                                function_name: Some(ffqn.clone().into()),
+                                params: Some(prost_wkt_types::Any {
+                                    type_url: String::new(),
+                                    value: params.to_vec(),
+                                }),
+                                ..Default::default()
+                            },
+                        ),
+                    ),
+                    ..Default::default()
+                })),
+            })),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn child_request_params_are_named_by_the_function_signature() {
+        let ffqn = ffqn();
+        let mut app_state = app_state();
+        app_state.ffqns_to_details.insert(
+            ffqn.clone(),
+            (
+                FunctionDetail {
+                    params: vec![FunctionParameter {
+                        name: "count".to_string(),
+                        r#type: Some(WitType {
+                            wit_type: "u32".to_string(),
+                            ..Default::default()
+                        }),
+                    }],
+                    ..Default::default()
+                },
+                Default::default(),
+            ),
+        );
+        let event = child_request(&ffqn, b"[42]");
+        let inspected = inspect_events(&[event], (3, 4), &HashMap::new(), &app_state);
+        assert_eq!(
+            vec![Variable {
+                key: "ns:pkg/ifc.fn(count)".to_string(),
+                name: "count".to_string(),
+                wit_type: Some("u32".to_string()),
+                value: "42".to_string(),
+            }],
+            inspected[0].variables
+        );
+    }
+
+    #[test]
+    fn malformed_params_are_shown_as_a_parse_error() {
+        let event = child_request(&ffqn(), b"[42");
+        let inspected = inspect_events(&[event], (3, 4), &HashMap::new(), &app_state());
+        let variables = &inspected[0].variables;
+        assert_eq!(1, variables.len());
+        assert_eq!("params", variables[0].name);
+        assert!(
+            variables[0].value.starts_with("cannot parse `[42`"),
+            "{}",
+            variables[0].value
+        );
+    }
+}
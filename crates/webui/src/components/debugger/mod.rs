@@ -1,2 +1,6 @@
+pub mod call_stack;
 pub mod debugger_view;
+pub mod inspector;
+pub mod persist_backtraces;
+pub mod run_to;
 pub mod version_slider;
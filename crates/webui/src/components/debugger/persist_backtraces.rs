@@ -0,0 +1,273 @@
+//! Persisting call-site backtraces ahead of a debugging session, so that stepping through
+//! an old execution does not stop at every version without a stored backtrace.
+
+use crate::{
+    auth::use_read_only,
+    components::notification::{Notification, NotificationContext},
+    grpc::{
+        grpc_client::{
+            self, ExecutionEvent, ExecutionId, execution_event,
+            execution_repository_client::ExecutionRepositoryClient,
+        },
+        version::VersionType,
+    },
+};
+use log::error;
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+
+/// Replay the execution and persist all missing backtraces, returning how many were added.
+pub async fn persist_backtraces(execution_id: ExecutionId) -> Result<u32, tonic::Status> {
+    let mut client = ExecutionRepositoryClient::new(crate::auth::client());
+    client
+        .persist_execution_backtraces(grpc_client::PersistExecutionBacktracesRequest {
+            execution_id: Some(execution_id),
+        })
+        .await
+        .map(|response| response.into_inner().persisted_backtrace_count)
+}
+
+/// Versions of history events that no backtrace covers.
+pub fn missing_backtrace_versions(events: &[ExecutionEvent]) -> Vec<VersionType> {
+    events
+        .iter()
+        .filter(|event| {
+            matches!(event.event, Some(execution_event::Event::HistoryVariant(_)))
+                && event.backtrace_id.is_none()
+        })
+        .map(|event| event.version)
+        .collect()
+}
+
+/// Versions `from..=to` that must be covered by backtraces, an open bound is unlimited.
+/// The server always replays the whole execution, the range only decides whether it needs to.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct VersionRange {
+    from: Option<VersionType>,
+    to: Option<VersionType>,
+}
+
+impl VersionRange {
+    fn contains(self, version: VersionType) -> bool {
+        self.from.is_none_or(|from| version >= from) && self.to.is_none_or(|to| version <= to)
+    }
+
+    fn is_empty(self) -> bool {
+        matches!((self.from, self.to), (Some(from), Some(to)) if from > to)
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct PersistBacktracesActionProps {
+    pub execution_id: ExecutionId,
+    /// Versions of all history events.
+    pub history_versions: Vec<VersionType>,
+    /// Versions of history events without a backtrace.
+    pub missing_versions: Vec<VersionType>,
+    /// Called with the number of persisted backtraces, the caller reloads its data.
+    pub on_persisted: Callback<u32>,
+}
+
+#[component(PersistBacktracesAction)]
+pub fn persist_backtraces_action(
+    PersistBacktracesActionProps {
+        execution_id,
+        history_versions,
+        missing_versions,
+        on_persisted,
+    }: &PersistBacktracesActionProps,
+) -> Html {
+    let notifications =
+        use_context::<NotificationContext>().expect("NotificationContext should be provided");
+    let range_mode = use_state(|| false);
+    let range = use_state(VersionRange::default);
+    let running = use_state(|| false);
+    let read_only = use_read_only();
+
+    let selected = if *range_mode {
+        *range
+    } else {
+        VersionRange::default()
+    };
+    let total = history_versions
+        .iter()
+        .filter(|version| selected.contains(**version))
+        .count();
+    let missing = missing_versions
+        .iter()
+        .filter(|version| selected.contains(**version))
+        .count();
+
+    let on_mode_change = {
+        let range_mode = range_mode.clone();
+        Callback::from(move |e: Event| {
+            range_mode.set(e.target_unchecked_into::<HtmlSelectElement>().value() == "range");
+        })
+    };
+    let version_input = |set_bound: fn(&mut VersionRange, Option<VersionType>)| {
+        let range = range.clone();
+        Callback::from(move |e: Event| {
+            let mut updated = *range;
+            set_bound(
+                &mut updated,
+                e.target_unchecked_into::<HtmlInputElement>()
+                    .value()
+                    .trim()
+                    .parse()
+                    .ok(),
+            );
+            range.set(updated);
+        })
+    };
+
+    let on_persist = {
+        let execution_id = execution_id.clone();
+        let running = running.clone();
+        let notifications = notifications.clone();
+        let on_persisted = on_persisted.clone();
+        Callback::from(move |_: MouseEvent| {
+            if missing == 0 {
+                // The server can only replay the whole execution, skip it when there is nothing to add.
+                notifications.push(Notification::info(
+                    "All history events in the selection already have a backtrace",
+                ));
+                return;
+            }
+            let execution_id = execution_id.clone();
+            let running = running.clone();
+            let notifications = notifications.clone();
+            let on_persisted = on_persisted.clone();
+            running.set(true);
+            wasm_bindgen_futures::spawn_local(async move {
+                match persist_backtraces(execution_id.clone()).await {
+                    Ok(count) => {
+                        if count == 0 {
+                            notifications
+                                .push(Notification::info("No new backtraces were persisted"));
+                        } else {
+                            let suffix = if count == 1 { "" } else { "s" };
+                            notifications.push(Notification::success(format!(
+                                "Persisted {count} backtrace{suffix}"
+                            )));
+                        }
+                        on_persisted.emit(count);
+                    }
+                    Err(err) => {
+                        error!("Failed to persist backtraces for {execution_id}: {err:?}");
+                        notifications.push(Notification::error(format!(
+                            "Failed to populate backtraces: {}",
+                            err.message()
+                        )));
+                    }
+                }
+                running.set(false);
+            });
+        })
+    };
+
+    html! {
+        <div class="action-container persist-backtraces">
+            <select
+                onchange={on_mode_change}
+                disabled={*running}
+                title="The server replays the whole execution, a range only selects the versions that must have a backtrace"
+            >
+                <option value="all" selected={!*range_mode}>{"Whole history"}</option>
+                <option value="range" selected={*range_mode}>{"Version range"}</option>
+            </select>
+            if *range_mode {
+                <input
+                    type="number"
+                    min="0"
+                    placeholder="from"
+                    value={range.from.map(|v| v.to_string()).unwrap_or_default()}
+                    onchange={version_input(|range, from| range.from = from)}
+                />
+                <input
+                    type="number"
+                    min="0"
+                    placeholder="to"
+                    value={range.to.map(|v| v.to_string()).unwrap_or_default()}
+                    onchange={version_input(|range, to| range.to = to)}
+                />
+            }
+            <button
+                class="action-button"
+                onclick={on_persist}
+                disabled={*running || read_only.is_some() || selected.is_empty()}
+                title={read_only.clone().unwrap_or(AttrValue::Static("Replay this execution and persist any missing call-site backtraces"))}
+            >
+                if *running {
+                    {"Replaying execution..."}
+                } else {
+                    {"Populate backtraces"}
+                }
+            </button>
+            <span class="secondary-text">
+                if selected.is_empty() {
+                    {"The range ends before it starts"}
+                } else if *range_mode {
+                    { format!("{} of {total} history events in the range have a backtrace", total - missing) }
+                } else {
+                    { format!("{} of {total} history events have a backtrace", total - missing) }
+                }
+            </span>
+        </div>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grpc::grpc_client::execution_event::HistoryEvent;
+
+    fn history_event(version: VersionType, backtrace_id: Option<VersionType>) -> ExecutionEvent {
+        ExecutionEvent {
+            version,
+            backtrace_id,
+            event: Some(execution_event::Event::HistoryVariant(HistoryEvent {
+                event: None,
+            })),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn only_uncovered_history_events_are_missing() {
+        let events = vec![
+            ExecutionEvent {
+                version: 0,
+                event: Some(execution_event::Event::Created(Default::default())),
+                ..Default::default()
+            },
+            history_event(1, Some(1)),
+            history_event(2, None),
+            history_event(3, None),
+            history_event(4, Some(4)),
+        ];
+        assert_eq!(vec![2, 3], missing_backtrace_versions(&events));
+    }
+
+    #[test]
+    fn version_range_bounds_are_inclusive() {
+        let range = VersionRange {
+            from: Some(2),
+            to: Some(3),
+        };
+        assert_eq!(
+            vec![2, 3],
+            (0..5)
+                .filter(|version| range.contains(*version))
+                .collect::<Vec<_>>()
+        );
+        assert!(VersionRange::default().contains(7));
+        assert!(!range.is_empty());
+        assert!(
+            VersionRange {
+                from: Some(3),
+                to: Some(2)
+            }
+            .is_empty()
+        );
+    }
+}
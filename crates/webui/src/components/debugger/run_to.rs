@@ -0,0 +1,371 @@
+//! Conditional "run to" navigation of the debugger, the workflow counterpart of breakpoints:
+//! jump to the next or previous backtrace version where a condition holds.
+
+use crate::components::debugger::call_stack::is_library_file;
+use crate::components::execution_detail::event_filter::{EventKind, event_kind};
+use crate::grpc::{
+    ffqn::FunctionFqn,
+    finished_result_kind::FinishedResultKind,
+    grpc_client::{
+        ExecutionEvent, FunctionName, JoinSetResponseEvent, WasmBacktrace,
+        execution_event::{
+            self,
+            history_event::{Event as HistoryEventEnum, JoinSetRequest, join_set_request},
+        },
+        join_set_response_event,
+    },
+    version::VersionType,
+};
+use hashbrown::HashMap;
+use std::collections::BTreeSet;
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RunToCondition {
+    /// The innermost frame of the component's own code is in `file`, optionally at `line`.
+    Location { file: String, line: Option<u32> },
+    /// An event of the given kind, optionally calling a function whose ffqn contains `ffqn`.
+    Event { kind: EventKind, ffqn: String },
+    /// A join-next that received an error or execution failure from a child.
+    ChildError,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchDirection {
+    Prev,
+    Next,
+}
+
+fn event_function(event: &ExecutionEvent) -> Option<&FunctionName> {
+    match event.event.as_ref()? {
+        execution_event::Event::Created(created) => created.function_name.as_ref(),
+        execution_event::Event::HistoryVariant(history) => match history.event.as_ref()? {
+            HistoryEventEnum::JoinSetRequest(JoinSetRequest {
+                join_set_request:
+                    Some(join_set_request::JoinSetRequest::ChildExecutionRequest(request)),
+                ..
+            }) => request.function_name.as_ref(),
+            HistoryEventEnum::JoinNext(join_next) => join_next.function.as_ref(),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn event_matches(
+    condition: &RunToCondition,
+    event: &ExecutionEvent,
+    join_next_version_to_response: &HashMap<VersionType, &JoinSetResponseEvent>,
+) -> bool {
+    match condition {
+        RunToCondition::Location { .. } => false,
+        RunToCondition::Event { kind, ffqn } => {
+            event_kind(event) == Some(*kind)
+                && (ffqn.trim().is_empty()
+                    || event_function(event).is_some_and(|function_name| {
+                        FunctionFqn::from(function_name.clone())
+                            .to_string()
+                            .contains(ffqn.trim())
+                    }))
+        }
+        RunToCondition::ChildError => {
+            matches!(
+                join_next_version_to_response
+                    .get(&event.version)
+                    .and_then(|response| response.response.as_ref()),
+                Some(join_set_response_event::Response::ChildExecutionFinished(finished))
+                    if finished
+                        .value
+                        .as_ref()
+                        .and_then(|result| result.value.as_ref())
+                        .is_some_and(|value| FinishedResultKind::from(value) != FinishedResultKind::Ok)
+            )
+        }
+    }
+}
+
+/// Backtrace versions past the current window `[min, max)` in the given direction, nearest first.
+pub fn candidate_versions(
+    backtrace_versions: &BTreeSet<VersionType>,
+    (min, max): (VersionType, VersionType),
+    direction: SearchDirection,
+) -> Vec<VersionType> {
+    match direction {
+        SearchDirection::Next => backtrace_versions.range(max..).copied().collect(),
+        SearchDirection::Prev => backtrace_versions.range(..min).rev().copied().collect(),
+    }
+}
+
+/// Backtrace version to navigate to for the nearest event matching an event condition.
+/// Events without their own backtrace resolve to the closest backtrace in the search direction.
+pub fn find_event_version(
+    condition: &RunToCondition,
+    events: &[ExecutionEvent],
+    join_next_version_to_response: &HashMap<VersionType, &JoinSetResponseEvent>,
+    backtrace_versions: &BTreeSet<VersionType>,
+    (min, max): (VersionType, VersionType),
+    direction: SearchDirection,
+) -> Option<VersionType> {
+    let resolve = |event: &ExecutionEvent| {
+        event.backtrace_id.or_else(|| match direction {
+            SearchDirection::Next => backtrace_versions.range(event.version..).next().copied(),
+            SearchDirection::Prev => backtrace_versions
+                .range(..=event.version)
+                .next_back()
+                .copied(),
+        })
+    };
+    let is_outside_window = |version: &VersionType| *version < min || *version >= max;
+    let matching =
+        |event: &&ExecutionEvent| event_matches(condition, event, join_next_version_to_response);
+    match direction {
+        SearchDirection::Next => events
+            .iter()
+            .filter(|event| event.version >= max)
+            .filter(matching)
+            .filter_map(resolve)
+            .find(is_outside_window),
+        SearchDirection::Prev => events
+            .iter()
+            .rev()
+            .filter(|event| event.version < min)
+            .filter(matching)
+            .filter_map(resolve)
+            .find(is_outside_window),
+    }
+}
+
+/// Whether the innermost frame of the component's own code is at the given location.
+pub fn location_matches(backtrace: &WasmBacktrace, file: &str, line: Option<u32>) -> bool {
+    backtrace
+        .frames
+        .iter()
+        .flat_map(|frame| frame.symbols.iter())
+        .find(|symbol| !is_library_file(symbol.file.as_deref()))
+        .is_some_and(|symbol| {
+            symbol
+                .file
+                .as_deref()
+                .is_some_and(|symbol_file| symbol_file.contains(file.trim()))
+                && line.is_none_or(|line| symbol.line == Some(line))
+        })
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum ConditionType {
+    Location,
+    Event,
+    ChildError,
+}
+
+#[derive(Properties, PartialEq)]
+pub struct RunToBarProps {
+    /// A search is in progress, the buttons are disabled.
+    pub searching: bool,
+    pub on_run: Callback<(RunToCondition, SearchDirection)>,
+    /// Stops the search before the next backtrace is loaded.
+    pub on_cancel: Callback<()>,
+}
+
+#[component(RunToBar)]
+pub fn run_to_bar(
+    RunToBarProps {
+        searching,
+        on_run,
+        on_cancel,
+    }: &RunToBarProps,
+) -> Html {
+    let condition_type = use_state(|| ConditionType::Event);
+    let file = use_state(String::new);
+    let line = use_state(|| None::<u32>);
+    let kind = use_state(|| EventKind::JoinSetRequest);
+    let ffqn = use_state(String::new);
+
+    let condition = match *condition_type {
+        ConditionType::Location => RunToCondition::Location {
+            file: (*file).clone(),
+            line: *line,
+        },
+        ConditionType::Event => RunToCondition::Event {
+            kind: *kind,
+            ffqn: (*ffqn).clone(),
+        },
+        ConditionType::ChildError => RunToCondition::ChildError,
+    };
+    let is_incomplete =
+        matches!(&condition, RunToCondition::Location { file, .. } if file.trim().is_empty());
+    let run = |direction| {
+        let on_run = on_run.clone();
+        let condition = condition.clone();
+        Callback::from(move |_: MouseEvent| on_run.emit((condition.clone(), direction)))
+    };
+
+    let on_type_change = {
+        let condition_type = condition_type.clone();
+        Callback::from(move |e: Event| {
+            condition_type.set(
+                match e
+                    .target_unchecked_into::<HtmlSelectElement>()
+                    .value()
+                    .as_str()
+                {
+                    "location" => ConditionType::Location,
+                    "child-error" => ConditionType::ChildError,
+                    _ => ConditionType::Event,
+                },
+            );
+        })
+    };
+    let on_file_input = {
+        let file = file.clone();
+        Callback::from(move |e: InputEvent| {
+            file.set(e.target_unchecked_into::<HtmlInputElement>().value());
+        })
+    };
+    let on_line_change = {
+        let line = line.clone();
+        Callback::from(move |e: Event| {
+            line.set(
+                e.target_unchecked_into::<HtmlInputElement>()
+                    .value()
+                    .trim()
+                    .parse()
+                    .ok(),
+            );
+        })
+    };
+    let on_kind_change = {
+        let kind = kind.clone();
+        Callback::from(move |e: Event| {
+            let value = e.target_unchecked_into::<HtmlSelectElement>().value();
+            if let Some(new_kind) = EventKind::ALL
+                .into_iter()
+                .find(|kind| kind.to_string() == value)
+            {
+                kind.set(new_kind);
+            }
+        })
+    };
+    let on_ffqn_input = {
+        let ffqn = ffqn.clone();
+        Callback::from(move |e: InputEvent| {
+            ffqn.set(e.target_unchecked_into::<HtmlInputElement>().value());
+        })
+    };
+
+    html! {
+        <div class="run-to-bar">
+            <select onchange={on_type_change}>
+                <option value="event" selected={*condition_type == ConditionType::Event}>
+                    {"Event"}
+                </option>
+                <option value="location" selected={*condition_type == ConditionType::Location}>
+                    {"Top frame at"}
+                </option>
+                <option value="child-error" selected={*condition_type == ConditionType::ChildError}>
+                    {"Child returned an error"}
+                </option>
+            </select>
+            {match *condition_type {
+                ConditionType::Location => html! {<>
+                    <input
+                        type="text"
+                        placeholder="file, e.g. src/lib.rs"
+                        value={(*file).clone()}
+                        oninput={on_file_input}
+                    />
+                    <input
+                        type="number"
+                        min="1"
+                        placeholder="line"
+                        value={line.map(|line| line.to_string()).unwrap_or_default()}
+                        onchange={on_line_change}
+                    />
+                </>},
+                ConditionType::Event => html! {<>
+                    <select onchange={on_kind_change}>
+                        { for EventKind::ALL.into_iter().map(|option| html! {
+                            <option value={option.to_string()} selected={*kind == option}>
+                                { option.to_string() }
+                            </option>
+                        }) }
+                    </select>
+                    <input
+                        type="text"
+                        placeholder="ffqn contains (optional)"
+                        value={(*ffqn).clone()}
+                        oninput={on_ffqn_input}
+                    />
+                </>},
+                ConditionType::ChildError => html! {},
+            }}
+            <button
+                onclick={run(SearchDirection::Prev)}
+                disabled={*searching || is_incomplete}
+            >
+                {"◀ Run back to"}
+            </button>
+            <button
+                onclick={run(SearchDirection::Next)}
+                disabled={*searching || is_incomplete}
+            >
+                {"Run to ▶"}
+            </button>
+            if *searching {
+                <span class="secondary-text">{"Searching..."}</span>
+                <button onclick={on_cancel.reform(|_: MouseEvent| ())}>{"Cancel"}</button>
+            }
+        </div>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grpc::grpc_client::execution_event::HistoryEvent;
+
+    fn request(version: VersionType, backtrace_id: Option<VersionType>) -> ExecutionEvent {
+        ExecutionEvent {
+            version,
+            backtrace_id,
+            event: Some(execution_event::Event::HistoryVariant(HistoryEvent {
+                event: Some(HistoryEventEnum::JoinSetRequest(Default::default())),
+            })),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn event_condition_skips_the_current_window() {
+        let events = vec![
+            request(2, Some(2)),
+            request(3, None),
+            request(5, Some(5)),
+            request(8, Some(8)),
+        ];
+        let backtrace_versions = BTreeSet::from([2, 5, 6, 8]);
+        let condition = RunToCondition::Event {
+            kind: EventKind::JoinSetRequest,
+            ffqn: String::new(),
+        };
+        let find = |window, direction| {
+            find_event_version(
+                &condition,
+                &events,
+                &HashMap::new(),
+                &backtrace_versions,
+                window,
+                direction,
+            )
+        };
+        assert_eq!(Some(8), find((5, 6), SearchDirection::Next));
+        // Version 3 has no backtrace of its own and resolves to the closest earlier one.
+        assert_eq!(Some(2), find((5, 6), SearchDirection::Prev));
+        assert_eq!(None, find((8, 9), SearchDirection::Next));
+        assert_eq!(
+            vec![5, 2],
+            candidate_versions(&backtrace_versions, (6, 8), SearchDirection::Prev)
+        );
+    }
+}
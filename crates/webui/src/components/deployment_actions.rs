@@ -1,4 +1,5 @@
 use crate::{
+    auth::use_read_only,
     components::notification::{Notification, NotificationContext},
     grpc::grpc_client::{
         self, DeploymentId, DeploymentStatus, RuntimeConfigCheck,
@@ -39,6 +40,7 @@ pub fn deployment_actions(
         use_context::<NotificationContext>().expect("NotificationContext should be provided");
     let in_flight = use_state(|| false);
     let armed = use_state(|| None::<ArmedAction>);
+    let read_only = use_read_only();
     // Dropping the previous timeout cancels it when another button is armed.
     let disarm_timer = use_mut_ref(|| None::<Timeout>);
 
@@ -128,7 +130,8 @@ pub fn deployment_actions(
         }
     };
 
-    let enqueue_disabled = *in_flight || *status == DeploymentStatus::Enqueued;
+    let enqueue_disabled =
+        *in_flight || *status == DeploymentStatus::Enqueued || read_only.is_some();
     let apply_armed = *armed == Some(ArmedAction::Apply);
     let enqueue_armed = *armed == Some(ArmedAction::Enqueue);
     html! {
@@ -145,7 +148,8 @@ pub fn deployment_actions(
                         arm(ArmedAction::Apply)
                     }
                 }
-                disabled={*in_flight}
+                disabled={*in_flight || read_only.is_some()}
+                title={read_only.clone()}
             >
                 { if apply_armed { "Confirm apply" } else { "Apply" } }
             </button>
@@ -171,7 +175,7 @@ pub fn deployment_actions(
                     class="action-button"
                     onclick={arm(ArmedAction::Enqueue)}
                     disabled={enqueue_disabled}
-                    title="Enqueues the deployment for the next server restart"
+                    title={read_only.clone().unwrap_or(AttrValue::Static("Enqueues the deployment for the next server restart"))}
                 >
                     {"Enqueue for next restart"}
                 </button>
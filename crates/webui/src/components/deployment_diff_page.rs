@@ -5,33 +5,51 @@ use crate::{
             ComponentView, MANIFEST_SECTIONS, SectionView, SourceContent, SourceView,
             build_sections_from_manifest, render_config_value,
         },
+        function_signature::FunctionSignature,
         notification::{Notification, NotificationContext},
+        wit_diff_page::WitDiffQuery,
     },
-    grpc::grpc_client::{
-        self, DeploymentId, deployment_repository_client::DeploymentRepositoryClient,
-        execution_repository_client::ExecutionRepositoryClient,
-        function_repository_client::FunctionRepositoryClient,
+    grpc::{
+        ffqn::FunctionFqn,
+        grpc_client::{
+            self, DeploymentId, FunctionDetail,
+            deployment_repository_client::DeploymentRepositoryClient,
+            execution_repository_client::ExecutionRepositoryClient,
+            function_repository_client::FunctionRepositoryClient,
+        },
     },
 };
+use hashbrown::HashMap;
 use log::error;
 use serde_json::Value;
 use similar::{ChangeTag, TextDiff};
 use std::collections::BTreeMap;
 use std::ops::Deref;
+use std::{cell::RefCell, rc::Rc};
 use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlSelectElement;
 use yew::prelude::*;
 use yew_router::prelude::*;
 
 #[derive(Properties, PartialEq)]
 pub struct DeploymentDiffPageProps {
-    pub from: DeploymentId,
-    pub to: DeploymentId,
+    /// Deployments to compare, initially the first one to the second one. Must contain at least two.
+    pub deployments: Vec<DeploymentId>,
 }
 
 #[derive(Clone)]
 struct DeploymentInfo {
+    deployment_id: DeploymentId,
     sections: Vec<SectionView>,
-    components_by_name: BTreeMap<String, grpc_client::ComponentId>,
+    components_by_name: BTreeMap<String, grpc_client::Component>,
+}
+
+impl DeploymentInfo {
+    fn component_id(&self, name: &str) -> Option<grpc_client::ComponentId> {
+        self.components_by_name
+            .get(name)
+            .and_then(|component| component.component_id.clone())
+    }
 }
 
 #[derive(Clone)]
@@ -39,6 +57,103 @@ struct DeploymentDiffData {
     from: DeploymentInfo,
     to: DeploymentInfo,
     source_diffs: BTreeMap<(String, String), Vec<SourceDiff>>,
+    /// Exported function changes of components present in both deployments, by component name.
+    export_diffs: BTreeMap<String, Vec<ExportDiff>>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct ExportDiff {
+    ffqn: FunctionFqn,
+    change: ExportChange,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum ExportChange {
+    Added(FunctionDetail),
+    Removed(FunctionDetail),
+    SignatureChanged {
+        from: FunctionDetail,
+        to: FunctionDetail,
+    },
+}
+
+impl ExportChange {
+    /// Adding a function cannot break running executions; removing one or changing its
+    /// signature breaks callers and executions of that function after an upgrade.
+    fn is_upgrade_safe(&self) -> bool {
+        matches!(self, ExportChange::Added(_))
+    }
+}
+
+/// Compare signatures using the inline WIT types, so that a changed record field or
+/// variant case is detected even when the named type stays the same.
+fn signature_eq(from: &FunctionDetail, to: &FunctionDetail) -> bool {
+    let inline_type = |ty: Option<&grpc_client::WitType>| ty.map(|ty| ty.wit_type_inline.clone());
+    from.params.len() == to.params.len()
+        && from.params.iter().zip(&to.params).all(|(from, to)| {
+            from.name == to.name
+                && inline_type(from.r#type.as_ref()) == inline_type(to.r#type.as_ref())
+        })
+        && inline_type(from.return_type.as_ref()) == inline_type(to.return_type.as_ref())
+}
+
+fn diff_exports(from: &[FunctionDetail], to: &[FunctionDetail]) -> Vec<ExportDiff> {
+    let by_ffqn =
+        |fn_details: &[FunctionDetail]| -> BTreeMap<String, (FunctionFqn, FunctionDetail)> {
+            fn_details
+                .iter()
+                .map(|fn_detail| {
+                    let ffqn =
+                        FunctionFqn::from_fn_detail(fn_detail).expect("ffqn should be parseable");
+                    (ffqn.to_string(), (ffqn, fn_detail.clone()))
+                })
+                .collect()
+        };
+    let from = by_ffqn(from);
+    let to = by_ffqn(to);
+    let mut names: Vec<&String> = from.keys().chain(to.keys()).collect();
+    names.sort();
+    names.dedup();
+
+    names
+        .into_iter()
+        .filter_map(|name| match (from.get(name), to.get(name)) {
+            (Some((_, from)), Some((ffqn, to))) if !signature_eq(from, to) => Some(ExportDiff {
+                ffqn: ffqn.clone(),
+                change: ExportChange::SignatureChanged {
+                    from: from.clone(),
+                    to: to.clone(),
+                },
+            }),
+            (Some(_), Some(_)) => None,
+            (Some((ffqn, from)), None) => Some(ExportDiff {
+                ffqn: ffqn.clone(),
+                change: ExportChange::Removed(from.clone()),
+            }),
+            (None, Some((ffqn, to))) => Some(ExportDiff {
+                ffqn: ffqn.clone(),
+                change: ExportChange::Added(to.clone()),
+            }),
+            (None, None) => unreachable!("name comes from one of the maps"),
+        })
+        .collect()
+}
+
+fn build_export_diffs(
+    from: &DeploymentInfo,
+    to: &DeploymentInfo,
+) -> BTreeMap<String, Vec<ExportDiff>> {
+    from.components_by_name
+        .iter()
+        .filter_map(|(name, from_component)| {
+            let to_component = to.components_by_name.get(name)?;
+            if from_component.component_id == to_component.component_id {
+                return None;
+            }
+            let diffs = diff_exports(&from_component.exports, &to_component.exports);
+            (!diffs.is_empty()).then(|| (name.clone(), diffs))
+        })
+        .collect()
 }
 
 #[derive(Clone)]
@@ -205,11 +320,182 @@ fn render_source_diff(diff: &SourceDiff) -> Html {
     }
 }
 
+fn render_export_diff(diff: &ExportDiff) -> Html {
+    let signature = |fn_detail: &FunctionDetail| {
+        html! {
+            <FunctionSignature
+                params={fn_detail.params.clone()}
+                return_type={fn_detail.return_type.clone()}
+            />
+        }
+    };
+    let ffqn = diff.ffqn.to_string();
+    match &diff.change {
+        ExportChange::Added(to) => html! {
+            <li>
+                <span class="badge added">{"added"}</span>{" "}
+                <span class="export-ffqn">{ ffqn }</span>{": "}
+                <span class="diff-ins">{ signature(to) }</span>
+            </li>
+        },
+        ExportChange::Removed(from) => html! {
+            <li>
+                <span class="badge removed">{"removed"}</span>{" "}
+                <span class="export-ffqn">{ ffqn }</span>{": "}
+                <span class="diff-del">{ signature(from) }</span>
+            </li>
+        },
+        ExportChange::SignatureChanged { from, to } => {
+            // Named types may keep their names while their definitions change.
+            let same_named_types = from.return_type.as_ref().map(|ty| &ty.wit_type)
+                == to.return_type.as_ref().map(|ty| &ty.wit_type)
+                && from.params.len() == to.params.len()
+                && from.params.iter().zip(&to.params).all(|(from, to)| {
+                    from.name == to.name
+                        && from.r#type.as_ref().map(|ty| &ty.wit_type)
+                            == to.r#type.as_ref().map(|ty| &ty.wit_type)
+                });
+            html! {
+                <li>
+                    <span class="badge breaking">{"signature changed"}</span>{" "}
+                    <span class="export-ffqn">{ ffqn }</span>
+                    <div class="export-signature-change">
+                        <span class="diff-del">{ signature(from) }</span>
+                        {" → "}
+                        <span class="diff-ins">{ signature(to) }</span>
+                        if same_named_types {
+                            <p class="secondary-text">
+                                {"The named types keep their names, but their definitions changed."}
+                            </p>
+                        }
+                    </div>
+                </li>
+            }
+        }
+    }
+}
+
+fn render_export_diffs(export_diffs: &[ExportDiff]) -> Html {
+    let breaking = export_diffs
+        .iter()
+        .filter(|diff| !diff.change.is_upgrade_safe())
+        .count();
+    html! {
+        <>
+            <h5>
+                {"Exported functions"}
+                if breaking > 0 {
+                    {" "}<span class="badge breaking">{ format!("{breaking} breaking") }</span>
+                } else {
+                    {" "}<span class="badge compatible">{"upgrade-safe"}</span>
+                }
+            </h5>
+            <ul class="export-diff-list">
+                { for export_diffs.iter().map(render_export_diff) }
+            </ul>
+        </>
+    }
+}
+
+/// Summary of components whose digest changed between the deployments, telling which ones
+/// keep all exported signatures and are therefore safe to upgrade running executions to.
+fn render_upgrade_hints(diff_data: &DeploymentDiffData) -> Option<Html> {
+    let rows = diff_data
+        .from
+        .components_by_name
+        .iter()
+        .filter_map(|(name, from_component)| {
+            let to_component = diff_data.to.components_by_name.get(name)?;
+            if from_component.component_id == to_component.component_id {
+                return None;
+            }
+            let export_diffs = diff_data
+                .export_diffs
+                .get(name)
+                .map(Vec::as_slice)
+                .unwrap_or(&[]);
+            let breaking = export_diffs
+                .iter()
+                .filter(|diff| !diff.change.is_upgrade_safe())
+                .count();
+            let added = export_diffs.len() - breaking;
+            let (badge, hint) = if breaking > 0 {
+                (
+                    html! { <span class="badge breaking">{"breaking"}</span> },
+                    format!(
+                        "{breaking} exported function{} removed or changed",
+                        if breaking == 1 { "" } else { "s" }
+                    ),
+                )
+            } else if added > 0 {
+                (
+                    html! { <span class="badge compatible">{"upgrade-safe"}</span> },
+                    format!(
+                        "{added} function{} added, existing signatures unchanged",
+                        if added == 1 { "" } else { "s" }
+                    ),
+                )
+            } else {
+                (
+                    html! { <span class="badge compatible">{"upgrade-safe"}</span> },
+                    "Exported signatures unchanged".to_string(),
+                )
+            };
+            let wit_diff_link = from_component
+                .component_id
+                .clone()
+                .zip(to_component.component_id.clone())
+                .map(|(from, to)| {
+                    html! {
+                        <Link<Route, WitDiffQuery>
+                            to={Route::ComponentWitDiff { from, to }}
+                            query={WitDiffQuery {
+                                from_deployment_id: Some(diff_data.from.deployment_id.id.clone()),
+                                to_deployment_id: Some(diff_data.to.deployment_id.id.clone()),
+                            }}
+                        >
+                            {"WIT diff"}
+                        </Link<Route, WitDiffQuery>>
+                    }
+                });
+            Some(html! {
+                <tr>
+                    <th>
+                        if let Some(component_id) = to_component.component_id.clone() {
+                            <Link<Route> to={Route::Component { component_id }}>{ name }</Link<Route>>
+                        } else {
+                            { name }
+                        }
+                    </th>
+                    <td>{ badge }</td>
+                    <td>{ hint }</td>
+                    <td>{ wit_diff_link }</td>
+                </tr>
+            })
+        })
+        .collect::<Vec<_>>();
+    if rows.is_empty() {
+        return None;
+    }
+    Some(html! {
+        <section class="deployment-section interface-compatibility">
+            <h4>{"Interface compatibility"}</h4>
+            <p class="secondary-text">
+                {"Components with a new digest. Running executions can be upgraded to components whose exported signatures are unchanged."}
+            </p>
+            <table class="config-table">
+                { rows }
+            </table>
+        </section>
+    })
+}
+
 fn render_changed_component(
     name: &str,
     from: &Value,
     to: &Value,
     source_diffs: &[SourceDiff],
+    export_diffs: &[ExportDiff],
 ) -> Html {
     let mut from_leaves = BTreeMap::new();
     let mut to_leaves = BTreeMap::new();
@@ -263,6 +549,9 @@ fn render_changed_component(
                     { rows }
                 </table>
             }
+            if !export_diffs.is_empty() {
+                { render_export_diffs(export_diffs) }
+            }
             if !source_diffs.is_empty() {
                 <h5>{"Source diffs"}</h5>
                 { for source_diffs.iter().map(render_source_diff) }
@@ -277,6 +566,7 @@ fn render_section_diff(
     from_components: &BTreeMap<String, ComponentView>,
     to_components: &BTreeMap<String, ComponentView>,
     source_diffs: &BTreeMap<(String, String), Vec<SourceDiff>>,
+    export_diffs: &BTreeMap<String, Vec<ExportDiff>>,
 ) -> Option<Html> {
     let mut names: Vec<&String> = from_components.keys().chain(to_components.keys()).collect();
     names.sort();
@@ -292,9 +582,12 @@ fn render_section_diff(
             .get(&(section_key.to_string(), name.clone()))
             .map(Vec::as_slice)
             .unwrap_or(&[]);
+        let export_diffs_for_component = export_diffs.get(name).map(Vec::as_slice).unwrap_or(&[]);
         match (from_components.get(name), to_components.get(name)) {
             (Some(from), Some(to))
-                if from.config == to.config && source_diffs_for_component.is_empty() =>
+                if from.config == to.config
+                    && source_diffs_for_component.is_empty()
+                    && export_diffs_for_component.is_empty() =>
             {
                 unchanged += 1;
             }
@@ -303,6 +596,7 @@ fn render_section_diff(
                 &from.config,
                 &to.config,
                 source_diffs_for_component,
+                export_diffs_for_component,
             )),
             (Some(from), None) => entries.push(html! {
                 <details class="component-config diff-removed">
@@ -366,20 +660,23 @@ async fn fetch_deployment_info(deployment_id: DeploymentId) -> Result<Deployment
             function_name: None,
             component_digest: None,
             extensions: false,
-            deployment_id: Some(deployment_id),
+            deployment_id: Some(deployment_id.clone()),
         })
         .await
         .map(|resp| {
             resp.into_inner()
                 .components
                 .into_iter()
-                .filter_map(|component| component.component_id)
-                .map(|component_id| (component_id.name.clone(), component_id))
+                .filter_map(|component| {
+                    let name = component.component_id.as_ref()?.name.clone();
+                    Some((name, component))
+                })
                 .collect()
         })
         .unwrap_or_default();
 
     Ok(DeploymentInfo {
+        deployment_id,
         sections,
         components_by_name,
     })
@@ -507,8 +804,8 @@ async fn build_source_diffs(
             let diffs = diff_sources(
                 &from_component.sources,
                 &to_component.sources,
-                from.components_by_name.get(name).cloned(),
-                to.components_by_name.get(name).cloned(),
+                from.component_id(name),
+                to.component_id(name),
             )
             .await;
             if !diffs.is_empty() {
@@ -520,36 +817,152 @@ async fn build_source_diffs(
     source_diffs
 }
 
+type DeploymentInfoCache = Rc<RefCell<HashMap<DeploymentId, DeploymentInfo>>>;
+
+/// Switching between pairs of the compared deployments reuses already fetched manifests.
+async fn fetch_deployment_info_cached(
+    cache: &DeploymentInfoCache,
+    deployment_id: DeploymentId,
+) -> Result<DeploymentInfo, String> {
+    let cached = cache.borrow().get(&deployment_id).cloned();
+    if let Some(cached) = cached {
+        return Ok(cached);
+    }
+    let info = fetch_deployment_info(deployment_id.clone()).await?;
+    cache.borrow_mut().insert(deployment_id, info.clone());
+    Ok(info)
+}
+
 async fn fetch_diff_data(
+    cache: DeploymentInfoCache,
     from: DeploymentId,
     to: DeploymentId,
 ) -> Result<DeploymentDiffData, String> {
-    let from = fetch_deployment_info(from).await?;
-    let to = fetch_deployment_info(to).await?;
+    let from = fetch_deployment_info_cached(&cache, from).await?;
+    let to = fetch_deployment_info_cached(&cache, to).await?;
     let source_diffs = build_source_diffs(&from, &to).await;
+    let export_diffs = build_export_diffs(&from, &to);
     Ok(DeploymentDiffData {
         from,
         to,
         source_diffs,
+        export_diffs,
     })
 }
 
+/// The deployments at the selected indexes, comparing a deployment to itself is rejected.
+fn compared_pair(
+    deployments: &[DeploymentId],
+    (from_idx, to_idx): (usize, usize),
+) -> Result<(DeploymentId, DeploymentId), &'static str> {
+    let (Some(from), Some(to)) = (deployments.get(from_idx), deployments.get(to_idx)) else {
+        return Err("Select at least two deployments to compare.");
+    };
+    if from == to {
+        return Err("Select two different deployments to compare.");
+    }
+    Ok((from.clone(), to.clone()))
+}
+
+/// Both sides can be chosen, the tabs compare the `From` deployment to each of the others.
+fn render_pair_selector(
+    deployments: &[DeploymentId],
+    (from_idx, to_idx): (usize, usize),
+    on_select: &Callback<(usize, usize)>,
+) -> Html {
+    let options = |selected: usize| {
+        deployments
+            .iter()
+            .enumerate()
+            .map(|(idx, deployment_id)| {
+                html! {
+                    <option value={idx.to_string()} selected={idx == selected}>{ &deployment_id.id }</option>
+                }
+            })
+            .collect::<Html>()
+    };
+    let on_from_change = {
+        let on_select = on_select.clone();
+        Callback::from(move |event: Event| {
+            let select: HtmlSelectElement = event.target_unchecked_into();
+            if let Ok(from_idx) = select.value().parse() {
+                on_select.emit((from_idx, to_idx));
+            }
+        })
+    };
+    let on_to_change = {
+        let on_select = on_select.clone();
+        Callback::from(move |event: Event| {
+            let select: HtmlSelectElement = event.target_unchecked_into();
+            if let Ok(to_idx) = select.value().parse() {
+                on_select.emit((from_idx, to_idx));
+            }
+        })
+    };
+    let on_swap = {
+        let on_select = on_select.clone();
+        Callback::from(move |_| on_select.emit((to_idx, from_idx)))
+    };
+    let from_pairs = (0..deployments.len())
+        .filter(|idx| deployments.get(*idx) != deployments.get(from_idx))
+        .map(|idx| {
+            let on_select = on_select.clone();
+            html! {
+                <button
+                    class={classes!((to_idx == idx).then_some("active"))}
+                    onclick={Callback::from(move |_| on_select.emit((from_idx, idx)))}
+                >
+                    { format!("→ {}", deployments[idx].id) }
+                </button>
+            }
+        });
+    html! {
+        <div class="deployment-pair-selector">
+            if deployments.len() > 2 {
+                <div class="view-tabs">
+                    { for from_pairs }
+                </div>
+            }
+            <label>
+                {"From "}
+                <select onchange={on_from_change}>{ options(from_idx) }</select>
+            </label>
+            <button onclick={on_swap} title="Swap the compared deployments">{"⇄"}</button>
+            <label>
+                {"To "}
+                <select onchange={on_to_change}>{ options(to_idx) }</select>
+            </label>
+        </div>
+    }
+}
+
 #[component(DeploymentDiffPage)]
 pub fn deployment_diff_page(
-    DeploymentDiffPageProps { from, to }: &DeploymentDiffPageProps,
+    DeploymentDiffPageProps { deployments }: &DeploymentDiffPageProps,
 ) -> Html {
     let notifications =
         use_context::<NotificationContext>().expect("NotificationContext should be provided");
     let diff_state = use_state(|| None::<Result<DeploymentDiffData, String>>);
+    let cache: DeploymentInfoCache = use_mut_ref(HashMap::new);
+    // Indexes into `deployments` of the currently compared pair.
+    let selected_pair = use_state(|| (0usize, 1usize));
+    {
+        let selected_pair = selected_pair.clone();
+        use_effect_with(deployments.clone(), move |_| selected_pair.set((0, 1)));
+    }
+    let (from_idx, to_idx) = *selected_pair;
+    let pair = compared_pair(deployments, (from_idx, to_idx));
 
     {
         let diff_state = diff_state.clone();
         let notifications = notifications.clone();
-        use_effect_with((from.clone(), to.clone()), move |(from, to)| {
-            let from = from.clone();
-            let to = to.clone();
+        use_effect_with(pair.clone().ok(), move |pair| {
+            let Some((from, to)) = pair.clone() else {
+                return;
+            };
+            diff_state.set(None);
             spawn_local(async move {
-                let result = fetch_diff_data(from, to).await;
+                let result = fetch_diff_data(cache, from, to).await;
                 if let Err(err) = &result {
                     error!("Deployment diff failed: {err}");
                     notifications.push(Notification::error(err.clone()));
@@ -563,17 +976,18 @@ pub fn deployment_diff_page(
         None => html! { <p>{"Loading..."}</p> },
         Some(Err(err)) => html! { <p class="error">{ err }</p> },
         Some(Ok(diff_data)) => {
-            let sections: Vec<Html> = MANIFEST_SECTIONS
-                .iter()
-                .filter_map(|(key, title)| {
+            let sections: Vec<Html> = render_upgrade_hints(diff_data)
+                .into_iter()
+                .chain(MANIFEST_SECTIONS.iter().filter_map(|(key, title)| {
                     render_section_diff(
                         key,
                         title,
                         &components_by_name(&diff_data.from.sections, key),
                         &components_by_name(&diff_data.to.sections, key),
                         &diff_data.source_diffs,
+                        &diff_data.export_diffs,
                     )
-                })
+                }))
                 .collect();
             if sections.is_empty() {
                 html! { <p>{"The deployments have identical configurations."}</p> }
@@ -583,9 +997,29 @@ pub fn deployment_diff_page(
         }
     };
 
+    let on_pair_select = {
+        let selected_pair = selected_pair.clone();
+        Callback::from(move |pair| selected_pair.set(pair))
+    };
+    let pair_selector = (deployments.len() >= 2)
+        .then(|| render_pair_selector(deployments, (from_idx, to_idx), &on_pair_select));
+    let (from, to) = match pair {
+        Ok(pair) => pair,
+        Err(err) => {
+            return html! {
+                <>
+                    <h3>{"Deployment diff"}</h3>
+                    { pair_selector }
+                    <p class="error">{ err }</p>
+                </>
+            };
+        }
+    };
+
     html! {
         <>
             <h3>{"Deployment diff"}</h3>
+            { pair_selector }
             <p>
                 <span class="diff-del">
                     <Link<Route> to={Route::DeploymentDetail { deployment_id: from.clone() }}>
@@ -603,3 +1037,78 @@ pub fn deployment_diff_page(
         </>
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fn_detail(function_name: &str, param_type: &str, return_type: &str) -> FunctionDetail {
+        FunctionDetail {
+            function_name: Some(grpc_client::FunctionName {
+                interface_name: "ns:pkg/ifc".to_string(),
+                function_name: function_name.to_string(),
+            }),
+            params: vec![grpc_client::FunctionParameter {
+                name: "input".to_string(),
+                r#type: Some(wit_type(param_type)),
+            }],
+            return_type: Some(wit_type(return_type)),
+            ..Default::default()
+        }
+    }
+
+    fn wit_type(ty: &str) -> grpc_client::WitType {
+        grpc_client::WitType {
+            wit_type: ty.to_string(),
+            wit_type_inline: ty.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn export_diff_marks_only_added_functions_as_upgrade_safe() {
+        let from = vec![
+            fn_detail("kept", "string", "result"),
+            fn_detail("changed", "string", "result"),
+            fn_detail("removed", "u32", "result"),
+        ];
+        let to = vec![
+            fn_detail("kept", "string", "result"),
+            fn_detail("changed", "u64", "result"),
+            fn_detail("added", "u32", "result"),
+        ];
+
+        let diffs = diff_exports(&from, &to);
+
+        let summary = diffs
+            .iter()
+            .map(|diff| {
+                (
+                    diff.ffqn.function_name.as_str(),
+                    diff.change.is_upgrade_safe(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![("added", true), ("changed", false), ("removed", false)]
+        );
+        assert!(matches!(
+            diffs[1].change,
+            ExportChange::SignatureChanged { .. }
+        ));
+    }
+
+    #[test]
+    fn a_deployment_is_not_compared_to_itself() {
+        let deployment = |id: &str| DeploymentId { id: id.to_string() };
+        let deployments = vec![deployment("D_1"), deployment("D_2"), deployment("D_1")];
+        assert_eq!(
+            Ok((deployment("D_2"), deployment("D_1"))),
+            compared_pair(&deployments, (1, 0))
+        );
+        assert!(compared_pair(&deployments, (1, 1)).is_err());
+        assert!(compared_pair(&deployments, (0, 2)).is_err());
+        assert!(compared_pair(&deployments[..1], (0, 1)).is_err());
+    }
+}
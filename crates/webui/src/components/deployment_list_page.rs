@@ -1,5 +1,5 @@
 use crate::{
-    app::{AppState, Route},
+    app::{AppState, Route, query::DeploymentIdList},
     components::{
         execution_list_page::{ExecutionQuery, StatusFilterList},
         notification::{Notification, NotificationContext},
@@ -387,13 +387,20 @@ pub fn deployment_list_page() -> Html {
             .collect::<Vec<_>>();
 
         // Deployment IDs are `Dep_<ULID>` so the lexicographic order matches creation order;
-        // diff from the older to the newer deployment.
+        // diff from the older to the newer deployment. With more than two selected, the oldest
+        // one becomes the base and the diff page offers a selector for any pair.
         let diff_route = {
-            let mut selected = selected_for_diff.iter();
-            match (selected.next(), selected.next(), selected.next()) {
-                (Some(older), Some(newer), None) => Some(Route::DeploymentDiff {
-                    from: DeploymentId { id: older.clone() },
-                    to: DeploymentId { id: newer.clone() },
+            let selected = selected_for_diff
+                .iter()
+                .map(|id| DeploymentId { id: id.clone() })
+                .collect::<Vec<_>>();
+            match selected.as_slice() {
+                [older, newer] => Some(Route::DeploymentDiff {
+                    from: older.clone(),
+                    to: newer.clone(),
+                }),
+                [_, _, ..] => Some(Route::DeploymentCompare {
+                    deployments: DeploymentIdList(selected),
                 }),
                 _ => None,
             }
@@ -452,7 +459,7 @@ pub fn deployment_list_page() -> Html {
                             {"Compare selected"}
                         </button>
                     } else {
-                        <button disabled={true} title="Select exactly two deployments">
+                        <button disabled={true} title="Select two or more deployments">
                             {"Compare selected"}
                         </button>
                     }
@@ -2,6 +2,7 @@
 
 use crate::{
     app::{AppState, Route},
+    auth::use_read_only,
     components::notification::{Notification, NotificationContext},
     grpc::{
         ffqn::FunctionFqn,
@@ -94,6 +95,7 @@ pub fn replay_button(props: &ReplayButtonProps) -> Html {
     let notifications =
         use_context::<NotificationContext>().expect("NotificationContext should be provided");
     let loading_state = use_state(|| false);
+    let read_only = use_read_only();
 
     let onclick = {
         let execution_id = props.execution_id.clone();
@@ -132,7 +134,8 @@ pub fn replay_button(props: &ReplayButtonProps) -> Html {
             <button
                 class="action-button replay-button"
                 onclick={onclick}
-                disabled={is_loading}
+                disabled={is_loading || read_only.is_some()}
+                title={read_only.clone()}
             >
                 if is_loading {
                     {"Replaying..."}
@@ -162,6 +165,7 @@ pub fn advance_button(props: &AdvanceButtonProps) -> Html {
     let notifications =
         use_context::<NotificationContext>().expect("NotificationContext should be provided");
     let loading_state = use_state(|| false);
+    let read_only = use_read_only();
 
     let onclick = {
         let execution_id = props.execution_id.clone();
@@ -205,7 +209,8 @@ pub fn advance_button(props: &AdvanceButtonProps) -> Html {
             <button
                 class="action-button advance-button"
                 onclick={onclick}
-                disabled={is_loading}
+                disabled={is_loading || read_only.is_some()}
+                title={read_only.clone()}
             >
                 if is_loading {
                     {"Loading..."}
@@ -252,6 +257,7 @@ pub fn upgrade_form(props: &UpgradeFormProps) -> Html {
         use_context::<NotificationContext>().expect("NotificationContext should be provided");
 
     let loading_state = use_state(|| false);
+    let read_only = use_read_only();
     let skip_determinism_state = use_state(|| false);
     let show_modal_state = use_state(|| false);
     // Tracks the digest after a successful upgrade, so we can disable the button
@@ -291,6 +297,10 @@ pub fn upgrade_form(props: &UpgradeFormProps) -> Html {
             "No component exporting this function found in the current deployment",
         )
     };
+    let (button_disabled, button_title) = match &read_only {
+        Some(reason) => (true, reason.clone()),
+        None => (button_disabled, AttrValue::from(button_title)),
+    };
 
     let on_open_modal = {
         let show_modal_state = show_modal_state.clone();
@@ -532,6 +542,7 @@ pub fn cancel_execution_button(props: &CancelExecutionButtonProps) -> Html {
     let notifications =
         use_context::<NotificationContext>().expect("NotificationContext should be provided");
     let loading_state = use_state(|| false);
+    let read_only = use_read_only();
 
     let onclick = {
         let execution_id = props.execution_id.clone();
@@ -595,7 +606,8 @@ pub fn cancel_execution_button(props: &CancelExecutionButtonProps) -> Html {
             <button
                 class="action-button cancel-button"
                 onclick={onclick}
-                disabled={is_loading}
+                disabled={is_loading || read_only.is_some()}
+                title={read_only.clone()}
             >
                 if is_loading {
                     {"Cancelling..."}
@@ -623,6 +635,7 @@ pub fn pause_button(props: &PauseButtonProps) -> Html {
     let notifications =
         use_context::<NotificationContext>().expect("NotificationContext should be provided");
     let loading_state = use_state(|| false);
+    let read_only = use_read_only();
 
     let onclick = {
         let execution_id = props.execution_id.clone();
@@ -662,7 +675,7 @@ pub fn pause_button(props: &PauseButtonProps) -> Html {
     };
 
     let is_loading = *loading_state;
-    let is_disabled = is_loading || props.is_paused;
+    let is_disabled = is_loading || props.is_paused || read_only.is_some();
 
     html! {
         <div class="action-container pause-action">
@@ -670,6 +683,7 @@ pub fn pause_button(props: &PauseButtonProps) -> Html {
                 class="action-button pause-button"
                 onclick={onclick}
                 disabled={is_disabled}
+                title={read_only.clone()}
             >
                 if is_loading {
                     {"Pausing..."}
@@ -697,6 +711,7 @@ pub fn unpause_button(props: &UnpauseButtonProps) -> Html {
     let notifications =
         use_context::<NotificationContext>().expect("NotificationContext should be provided");
     let loading_state = use_state(|| false);
+    let read_only = use_read_only();
 
     let onclick = {
         let execution_id = props.execution_id.clone();
@@ -737,7 +752,7 @@ pub fn unpause_button(props: &UnpauseButtonProps) -> Html {
     };
 
     let is_loading = *loading_state;
-    let is_disabled = is_loading || !props.is_paused;
+    let is_disabled = is_loading || !props.is_paused || read_only.is_some();
 
     html! {
         <div class="action-container unpause-action">
@@ -745,6 +760,7 @@ pub fn unpause_button(props: &UnpauseButtonProps) -> Html {
                 class="action-button unpause-button"
                 onclick={onclick}
                 disabled={is_disabled}
+                title={read_only.clone()}
             >
                 if is_loading {
                     {"Unpausing..."}
@@ -770,6 +786,7 @@ pub fn cancel_delay_button(props: &CancelDelayButtonProps) -> Html {
     let notifications =
         use_context::<NotificationContext>().expect("NotificationContext should be provided");
     let loading_state = use_state(|| false);
+    let read_only = use_read_only();
 
     let onclick = {
         let delay_id = props.delay_id.clone();
@@ -827,7 +844,8 @@ pub fn cancel_delay_button(props: &CancelDelayButtonProps) -> Html {
             <button
                 class="action-button cancel-delay-button"
                 onclick={onclick}
-                disabled={is_loading}
+                disabled={is_loading || read_only.is_some()}
+                title={read_only.clone()}
             >
                 if is_loading {
                     {"Cancelling..."}
@@ -853,6 +871,7 @@ pub fn pause_delay_button(props: &PauseDelayButtonProps) -> Html {
     let notifications =
         use_context::<NotificationContext>().expect("NotificationContext should be provided");
     let loading_state = use_state(|| false);
+    let read_only = use_read_only();
 
     let onclick = {
         let delay_id = props.delay_id.clone();
@@ -910,7 +929,8 @@ pub fn pause_delay_button(props: &PauseDelayButtonProps) -> Html {
             <button
                 class="action-button pause-delay-button"
                 onclick={onclick}
-                disabled={is_loading}
+                disabled={is_loading || read_only.is_some()}
+                title={read_only.clone()}
             >
                 if is_loading {
                     {"Pausing..."}
@@ -936,6 +956,7 @@ pub fn unpause_delay_button(props: &UnpauseDelayButtonProps) -> Html {
     let notifications =
         use_context::<NotificationContext>().expect("NotificationContext should be provided");
     let loading_state = use_state(|| false);
+    let read_only = use_read_only();
 
     let onclick = {
         let delay_id = props.delay_id.clone();
@@ -993,7 +1014,8 @@ pub fn unpause_delay_button(props: &UnpauseDelayButtonProps) -> Html {
             <button
                 class="action-button unpause-delay-button"
                 onclick={onclick}
-                disabled={is_loading}
+                disabled={is_loading || read_only.is_some()}
+                title={read_only.clone()}
             >
                 if is_loading {
                     {"Unpausing..."}
@@ -1019,6 +1041,16 @@ pub struct SubmitStubButtonProps {
 /// Should only be rendered when the execution is an unfinished ActivityStub.
 #[component(SubmitStubButton)]
 pub fn submit_stub_button(props: &SubmitStubButtonProps) -> Html {
+    let read_only = use_read_only();
+    if let Some(reason) = read_only {
+        return html! {
+            <div class="action-container submit-stub-action">
+                <button class="action-button submit-stub-button" disabled=true title={reason}>
+                    {"Submit Stub Response"}
+                </button>
+            </div>
+        };
+    }
     html! {
         <div class="action-container submit-stub-action">
             <Link<Route>
@@ -0,0 +1,492 @@
+//! Narrowing down an execution's event history by kind, join set, version range and text.
+
+use crate::grpc::{
+    ffqn::FunctionFqn,
+    grpc_client::{
+        ExecutionEvent, FunctionName, JoinSetId, JoinSetResponseEvent, SupportedFunctionResult,
+        execution_event::{
+            self,
+            history_event::{
+                Event as HistoryEventEnum, JoinNext, JoinNextTooMany, JoinNextTry, JoinSetCreated,
+                JoinSetRequest, join_set_request,
+            },
+        },
+        join_set_response_event, supported_function_result,
+    },
+    version::VersionType,
+};
+use hashbrown::{HashMap, HashSet};
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, derive_more::Display)]
+pub enum EventKind {
+    Created,
+    Locked,
+    Unlocked,
+    TemporarilyFailed,
+    TemporarilyTimedOut,
+    Finished,
+    Paused,
+    Unpaused,
+    CancellationRequested,
+    ComponentUpgradeFinished,
+    Persist,
+    JoinSetCreated,
+    JoinSetRequest,
+    JoinNext,
+    JoinNextTry,
+    JoinNextTooMany,
+    Schedule,
+    Stub,
+}
+impl EventKind {
+    pub const ALL: [EventKind; 18] = [
+        EventKind::Created,
+        EventKind::Locked,
+        EventKind::Unlocked,
+        EventKind::TemporarilyFailed,
+        EventKind::TemporarilyTimedOut,
+        EventKind::Finished,
+        EventKind::Paused,
+        EventKind::Unpaused,
+        EventKind::CancellationRequested,
+        EventKind::ComponentUpgradeFinished,
+        EventKind::Persist,
+        EventKind::JoinSetCreated,
+        EventKind::JoinSetRequest,
+        EventKind::JoinNext,
+        EventKind::JoinNextTry,
+        EventKind::JoinNextTooMany,
+        EventKind::Schedule,
+        EventKind::Stub,
+    ];
+}
+
+pub fn event_kind(event: &ExecutionEvent) -> Option<EventKind> {
+    Some(match event.event.as_ref()? {
+        execution_event::Event::Created(_) => EventKind::Created,
+        execution_event::Event::Locked(_) => EventKind::Locked,
+        execution_event::Event::Unlocked(_) => EventKind::Unlocked,
+        execution_event::Event::TemporarilyFailed(_) => EventKind::TemporarilyFailed,
+        execution_event::Event::TemporarilyTimedOut(_) => EventKind::TemporarilyTimedOut,
+        execution_event::Event::Finished(_) => EventKind::Finished,
+        execution_event::Event::Paused(_) => EventKind::Paused,
+        execution_event::Event::Unpaused(_) => EventKind::Unpaused,
+        execution_event::Event::CancellationRequested(_) => EventKind::CancellationRequested,
+        execution_event::Event::ComponentUpgradeFinished(_) => EventKind::ComponentUpgradeFinished,
+        execution_event::Event::HistoryVariant(history) => match history.event.as_ref()? {
+            HistoryEventEnum::Persist(_) => EventKind::Persist,
+            HistoryEventEnum::JoinSetCreated(_) => EventKind::JoinSetCreated,
+            HistoryEventEnum::JoinSetRequest(_) => EventKind::JoinSetRequest,
+            HistoryEventEnum::JoinNext(_) => EventKind::JoinNext,
+            HistoryEventEnum::JoinNextTry(_) => EventKind::JoinNextTry,
+            HistoryEventEnum::JoinNextTooMany(_) => EventKind::JoinNextTooMany,
+            HistoryEventEnum::Schedule(_) => EventKind::Schedule,
+            HistoryEventEnum::Stub(_) => EventKind::Stub,
+        },
+    })
+}
+
+pub fn event_join_set_id(event: &ExecutionEvent) -> Option<&JoinSetId> {
+    let Some(execution_event::Event::HistoryVariant(history)) = &event.event else {
+        return None;
+    };
+    match history.event.as_ref()? {
+        HistoryEventEnum::JoinSetRequest(JoinSetRequest { join_set_id, .. })
+        | HistoryEventEnum::JoinNext(JoinNext { join_set_id, .. })
+        | HistoryEventEnum::JoinSetCreated(JoinSetCreated { join_set_id })
+        | HistoryEventEnum::JoinNextTry(JoinNextTry { join_set_id, .. })
+        | HistoryEventEnum::JoinNextTooMany(JoinNextTooMany { join_set_id, .. }) => {
+            join_set_id.as_ref()
+        }
+        _ => None,
+    }
+}
+
+fn push_any(text: &mut String, any: Option<&prost_wkt_types::Any>) {
+    if let Some(any) = any {
+        text.push('\n');
+        text.push_str(&String::from_utf8_lossy(&any.value));
+    }
+}
+
+fn push_function(text: &mut String, function_name: Option<&FunctionName>) {
+    if let Some(function_name) = function_name {
+        text.push('\n');
+        text.push_str(&FunctionFqn::from(function_name.clone()).to_string());
+    }
+}
+
+fn push_result(text: &mut String, result: Option<&SupportedFunctionResult>) {
+    match result.and_then(|result| result.value.as_ref()) {
+        Some(supported_function_result::Value::Ok(ok)) => push_any(text, ok.return_value.as_ref()),
+        Some(supported_function_result::Value::Error(error)) => {
+            push_any(text, error.return_value.as_ref());
+        }
+        Some(supported_function_result::Value::ExecutionFailure(failure)) => {
+            for part in [&failure.reason, &failure.detail].into_iter().flatten() {
+                text.push('\n');
+                text.push_str(part);
+            }
+        }
+        None => {}
+    }
+}
+
+/// Lowercased text that the search box is matched against: function names, params,
+/// results, error reasons and the ids involved.
+pub fn searchable_text(
+    event: &ExecutionEvent,
+    join_next_version_to_response: &HashMap<VersionType, &JoinSetResponseEvent>,
+) -> String {
+    let mut text = event_kind(event)
+        .map(|kind| kind.to_string())
+        .unwrap_or_default();
+    if let Some(join_set_id) = event_join_set_id(event) {
+        text.push('\n');
+        text.push_str(&join_set_id.to_string());
+    }
+    match event.event.as_ref() {
+        Some(execution_event::Event::Created(created)) => {
+            push_function(&mut text, created.function_name.as_ref());
+            push_any(&mut text, created.params.as_ref());
+        }
+        Some(execution_event::Event::Finished(finished)) => {
+            push_result(&mut text, finished.value.as_ref());
+        }
+        Some(execution_event::Event::TemporarilyFailed(failed)) => {
+            text.push('\n');
+            text.push_str(&failed.reason);
+            if let Some(detail) = &failed.detail {
+                text.push('\n');
+                text.push_str(detail);
+            }
+        }
+        Some(execution_event::Event::Unlocked(unlocked)) => {
+            text.push('\n');
+            text.push_str(&unlocked.reason);
+        }
+        Some(execution_event::Event::HistoryVariant(history)) => match &history.event {
+            Some(HistoryEventEnum::Persist(persist)) => push_any(&mut text, persist.data.as_ref()),
+            Some(HistoryEventEnum::JoinSetRequest(JoinSetRequest {
+                join_set_request: Some(request),
+                ..
+            })) => match request {
+                join_set_request::JoinSetRequest::ChildExecutionRequest(child) => {
+                    if let Some(child_execution_id) = &child.child_execution_id {
+                        text.push('\n');
+                        text.push_str(&child_execution_id.id);
+                    }
+                    push_function(&mut text, child.function_name.as_ref());
+                    push_any(&mut text, child.params.as_ref());
+                }
+                join_set_request::JoinSetRequest::DelayRequest(delay) => {
+                    if let Some(delay_id) = &delay.delay_id {
+                        text.push('\n');
+                        text.push_str(&delay_id.id);
+                    }
+                }
+            },
+            Some(HistoryEventEnum::JoinNext(join_next)) => {
+                push_function(&mut text, join_next.function.as_ref());
+                match join_next_version_to_response
+                    .get(&event.version)
+                    .and_then(|response| response.response.as_ref())
+                {
+                    Some(join_set_response_event::Response::ChildExecutionFinished(finished)) => {
+                        if let Some(child_execution_id) = &finished.child_execution_id {
+                            text.push('\n');
+                            text.push_str(&child_execution_id.id);
+                        }
+                        push_result(&mut text, finished.value.as_ref());
+                    }
+                    Some(join_set_response_event::Response::DelayFinished(delay)) => {
+                        if let Some(delay_id) = &delay.delay_id {
+                            text.push('\n');
+                            text.push_str(&delay_id.id);
+                        }
+                    }
+                    None => {}
+                }
+            }
+            Some(HistoryEventEnum::Schedule(schedule)) => {
+                if let Some(execution_id) = &schedule.execution_id {
+                    text.push('\n');
+                    text.push_str(&execution_id.id);
+                }
+            }
+            Some(HistoryEventEnum::Stub(stub)) => {
+                if let Some(execution_id) = &stub.execution_id {
+                    text.push('\n');
+                    text.push_str(&execution_id.id);
+                }
+            }
+            _ => {}
+        },
+        _ => {}
+    }
+    text.to_lowercase()
+}
+
+/// An empty set of kinds means all kinds.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EventFilter {
+    pub kinds: HashSet<EventKind>,
+    /// Rendered `JoinSetId`.
+    pub join_set: Option<String>,
+    pub version_from: Option<VersionType>,
+    pub version_to: Option<VersionType>,
+    pub text: String,
+}
+impl EventFilter {
+    pub fn is_active(&self) -> bool {
+        *self != Self::default()
+    }
+
+    pub fn matches(
+        &self,
+        event: &ExecutionEvent,
+        join_next_version_to_response: &HashMap<VersionType, &JoinSetResponseEvent>,
+    ) -> bool {
+        if self.version_from.is_some_and(|from| event.version < from)
+            || self.version_to.is_some_and(|to| event.version > to)
+        {
+            return false;
+        }
+        if !self.kinds.is_empty()
+            && !event_kind(event).is_some_and(|kind| self.kinds.contains(&kind))
+        {
+            return false;
+        }
+        if let Some(join_set) = &self.join_set
+            && !event_join_set_id(event)
+                .is_some_and(|join_set_id| join_set_id.to_string() == *join_set)
+        {
+            return false;
+        }
+        let needle = self.text.trim().to_lowercase();
+        needle.is_empty() || searchable_text(event, join_next_version_to_response).contains(&needle)
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct EventFilterBarProps {
+    /// Kinds present in the history with their event counts.
+    pub kind_counts: Vec<(EventKind, usize)>,
+    /// Rendered `JoinSetId`s present in the history.
+    pub join_sets: Vec<String>,
+    pub filter: EventFilter,
+    pub on_change: Callback<EventFilter>,
+    pub shown: usize,
+    pub total: usize,
+}
+
+#[component(EventFilterBar)]
+pub fn event_filter_bar(
+    EventFilterBarProps {
+        kind_counts,
+        join_sets,
+        filter,
+        on_change,
+        shown,
+        total,
+    }: &EventFilterBarProps,
+) -> Html {
+    let update = |apply: fn(&mut EventFilter, String)| {
+        let filter = filter.clone();
+        let on_change = on_change.clone();
+        move |value: String| {
+            let mut filter = filter.clone();
+            apply(&mut filter, value);
+            on_change.emit(filter);
+        }
+    };
+    let on_text_input = {
+        let update = update(|filter, value| filter.text = value);
+        Callback::from(move |e: InputEvent| {
+            update(e.target_unchecked_into::<HtmlInputElement>().value());
+        })
+    };
+    let on_join_set_change = {
+        let update =
+            update(|filter, value| filter.join_set = Some(value).filter(|v| !v.is_empty()));
+        Callback::from(move |e: Event| {
+            update(e.target_unchecked_into::<HtmlSelectElement>().value());
+        })
+    };
+    let on_version_from_change = {
+        let update = update(|filter, value| filter.version_from = value.trim().parse().ok());
+        Callback::from(move |e: Event| {
+            update(e.target_unchecked_into::<HtmlInputElement>().value());
+        })
+    };
+    let on_version_to_change = {
+        let update = update(|filter, value| filter.version_to = value.trim().parse().ok());
+        Callback::from(move |e: Event| {
+            update(e.target_unchecked_into::<HtmlInputElement>().value());
+        })
+    };
+    let on_clear = {
+        let on_change = on_change.clone();
+        Callback::from(move |_: MouseEvent| on_change.emit(EventFilter::default()))
+    };
+    let kind_chips = kind_counts.iter().map(|(kind, count)| {
+        let kind = *kind;
+        let onchange = {
+            let filter = filter.clone();
+            let on_change = on_change.clone();
+            Callback::from(move |e: Event| {
+                let mut filter = filter.clone();
+                if e.target_unchecked_into::<HtmlInputElement>().checked() {
+                    filter.kinds.insert(kind);
+                } else {
+                    filter.kinds.remove(&kind);
+                }
+                on_change.emit(filter);
+            })
+        };
+        let checked = filter.kinds.contains(&kind);
+        html! {
+            <label class={classes!("event-kind-chip", checked.then_some("is-selected"))}>
+                <input type="checkbox" {checked} {onchange} />
+                { format!("{kind} ({count})") }
+            </label>
+        }
+    });
+    let version_value =
+        |version: Option<VersionType>| version.map(|v| v.to_string()).unwrap_or_default();
+    html! {
+        <div class="event-filter-bar">
+            <div class="event-filter-fields">
+                <input
+                    type="search"
+                    placeholder="Search params, results, errors, ids"
+                    value={filter.text.clone()}
+                    oninput={on_text_input}
+                />
+                if !join_sets.is_empty() {
+                    <label>
+                        {"Join set "}
+                        <select onchange={on_join_set_change}>
+                            <option value="" selected={filter.join_set.is_none()}>{"All"}</option>
+                            { for join_sets.iter().map(|join_set| html! {
+                                <option
+                                    value={join_set.clone()}
+                                    selected={filter.join_set.as_ref() == Some(join_set)}
+                                >
+                                    { join_set }
+                                </option>
+                            }) }
+                        </select>
+                    </label>
+                }
+                <label>
+                    {"Versions "}
+                    <input
+                        type="number"
+                        min="0"
+                        placeholder="from"
+                        value={version_value(filter.version_from)}
+                        onchange={on_version_from_change}
+                    />
+                    {" – "}
+                    <input
+                        type="number"
+                        min="0"
+                        placeholder="to"
+                        value={version_value(filter.version_to)}
+                        onchange={on_version_to_change}
+                    />
+                </label>
+                <span class="secondary-text">
+                    { format!("Showing {shown} of {total} events") }
+                </span>
+                if filter.is_active() {
+                    <button onclick={on_clear}>{"Clear filters"}</button>
+                }
+            </div>
+            <div class="event-kind-chips">
+                { for kind_chips }
+            </div>
+        </div>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grpc::grpc_client::{ExecutionId, execution_event::HistoryEvent};
+
+    fn event(version: VersionType, event: execution_event::Event) -> ExecutionEvent {
+        ExecutionEvent {
+            version,
+            event: Some(event),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn filter_combines_kind_version_and_text() {
+        let child_request = event(
+            3,
+            execution_event::Event::HistoryVariant(HistoryEvent {
+                event: Some(HistoryEventEnum::JoinSetRequest(JoinSetRequest {
+                    join_set_id: None,
+                    join_set_request: Some(
+                        join_set_request::JoinSetRequest::ChildExecutionRequest(
+                            join_set_request::ChildExecutionRequest {
+                                child_execution_id: Some(ExecutionId {
+                                    id: "E_01.o:1_1".to_string(),
+                                }),
+                                params: Some(prost_wkt_types::Any {
+                                    type_url: String::new(),
+                                    value: br#"["Berlin"]"#.to_vec(),
+                                }),
+                                ..Default::default()
+                            },
+                        ),
+                    ),
+                })),
+            }),
+        );
+        let unlocked = event(
+            4,
+            execution_event::Event::Unlocked(execution_event::Unlocked {
+                reason: "lock extension failed".to_string(),
+                ..Default::default()
+            }),
+        );
+        let responses = HashMap::new();
+        let filter = |filter: EventFilter| {
+            [&child_request, &unlocked]
+                .into_iter()
+                .filter(|event| filter.matches(event, &responses))
+                .map(|event| event.version)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(vec![3, 4], filter(EventFilter::default()));
+        assert_eq!(
+            vec![3],
+            filter(EventFilter {
+                text: " berlin".to_string(),
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            vec![4],
+            filter(EventFilter {
+                kinds: HashSet::from([EventKind::Unlocked, EventKind::Locked]),
+                ..Default::default()
+            })
+        );
+        assert!(
+            filter(EventFilter {
+                version_to: Some(3),
+                text: "lock".to_string(),
+                ..Default::default()
+            })
+            .is_empty()
+        );
+    }
+}
@@ -1,7 +1,18 @@
-use crate::grpc::grpc_client;
+use crate::app::Route;
+use crate::components::copy_button::CopyButton;
+use crate::grpc::{
+    execution_id::EXECUTION_ID_INFIX,
+    grpc_client::{self, ExecutionEvent, execution_event},
+    version::VersionType,
+};
 use crate::tree::{Icon, InsertBehavior, Node, NodeData, NodeId};
-use chrono::DateTime;
+use crate::util::time::format_date;
+use chrono::{DateTime, TimeDelta, Utc};
 use id_tree::Tree;
+use std::collections::{BTreeMap, BTreeSet};
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+use yew_router::prelude::Link;
 
 pub fn attach_http_traces(
     tree: &mut Tree<NodeData<u32>>,
@@ -108,3 +119,518 @@ pub fn attach_http_traces(
         }
     }
 }
+
+/// One `HttpClientTrace` of an execution, flattened for the HTTP trace panel.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpTraceRow {
+    pub execution_id: grpc_client::ExecutionId,
+    /// Version of the event that carried the trace.
+    pub version: VersionType,
+    pub method: String,
+    pub uri: String,
+    pub sent_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub result: Option<grpc_client::http_client_trace::Result>,
+}
+impl HttpTraceRow {
+    pub fn host(&self) -> &str {
+        let rest = self
+            .uri
+            .split_once("://")
+            .map_or(self.uri.as_str(), |(_, rest)| rest);
+        let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+        authority
+            .rsplit_once('@')
+            .map_or(authority, |(_, host)| host)
+    }
+
+    pub fn duration(&self) -> Option<TimeDelta> {
+        self.finished_at
+            .map(|finished_at| finished_at - self.sent_at)
+    }
+
+    pub fn status_class(&self) -> StatusClass {
+        match &self.result {
+            Some(grpc_client::http_client_trace::Result::Status(status)) => match status {
+                200..=299 => StatusClass::Success,
+                300..=399 => StatusClass::Redirect,
+                400..=499 => StatusClass::ClientError,
+                500..=599 => StatusClass::ServerError,
+                _ => StatusClass::Other,
+            },
+            Some(grpc_client::http_client_trace::Result::Error(_)) => StatusClass::Error,
+            None => StatusClass::NoResponse,
+        }
+    }
+
+    pub fn to_curl(&self) -> String {
+        let uri = format!("'{}'", self.uri.replace('\'', r"'\''"));
+        if self.method.eq_ignore_ascii_case("GET") {
+            format!("curl {uri}")
+        } else {
+            format!("curl -X {} {uri}", self.method)
+        }
+    }
+}
+
+/// HTTP client traces of every finished, failed or timed out run of the loaded executions,
+/// in the order they were sent.
+pub fn collect_http_traces<'a>(
+    events: impl IntoIterator<Item = (&'a grpc_client::ExecutionId, &'a Vec<ExecutionEvent>)>,
+) -> Vec<HttpTraceRow> {
+    let mut rows: Vec<_> = events
+        .into_iter()
+        .flat_map(|(execution_id, events)| {
+            events.iter().flat_map(move |event| {
+                let traces = match &event.event {
+                    Some(
+                        execution_event::Event::Finished(execution_event::Finished {
+                            http_client_traces,
+                            ..
+                        })
+                        | execution_event::Event::TemporarilyFailed(
+                            execution_event::TemporarilyFailed {
+                                http_client_traces, ..
+                            },
+                        )
+                        | execution_event::Event::TemporarilyTimedOut(
+                            execution_event::TemporarilyTimedOut {
+                                http_client_traces, ..
+                            },
+                        ),
+                    ) => http_client_traces.as_slice(),
+                    _ => &[],
+                };
+                traces.iter().map(move |trace| HttpTraceRow {
+                    execution_id: execution_id.clone(),
+                    version: event.version,
+                    method: trace.method.clone(),
+                    uri: trace.uri.clone(),
+                    sent_at: DateTime::from(
+                        trace
+                            .sent_at
+                            .expect("HttpClientTrace.sent_at is always sent"),
+                    ),
+                    finished_at: trace.finished_at.map(DateTime::from),
+                    result: trace.result.clone(),
+                })
+            })
+        })
+        .collect();
+    rows.sort_by_key(|row| row.sent_at);
+    rows
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, derive_more::Display)]
+pub enum StatusClass {
+    #[display("2xx")]
+    Success,
+    #[display("3xx")]
+    Redirect,
+    #[display("4xx")]
+    ClientError,
+    #[display("5xx")]
+    ServerError,
+    /// Informational 1xx, which is never final, or a code outside of the HTTP ranges.
+    #[display("Other")]
+    Other,
+    #[display("Error")]
+    Error,
+    #[display("No response")]
+    NoResponse,
+}
+impl StatusClass {
+    const ALL: [StatusClass; 7] = [
+        StatusClass::Success,
+        StatusClass::Redirect,
+        StatusClass::ClientError,
+        StatusClass::ServerError,
+        StatusClass::Other,
+        StatusClass::Error,
+        StatusClass::NoResponse,
+    ];
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SortColumn {
+    SentAt,
+    Execution,
+    Method,
+    Uri,
+    Status,
+    Duration,
+}
+
+/// Latency summary of the completed requests to a single host.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HostStats {
+    pub host: String,
+    pub requests: usize,
+    pub failures: usize,
+    /// p50, p90 and p99 using the nearest-rank method.
+    pub percentiles: Option<[TimeDelta; 3]>,
+    pub max: Option<TimeDelta>,
+}
+
+fn percentile(sorted: &[TimeDelta], percentile: usize) -> TimeDelta {
+    let rank = (percentile * sorted.len()).div_ceil(100).max(1);
+    sorted[rank - 1]
+}
+
+pub fn host_stats(rows: &[&HttpTraceRow]) -> Vec<HostStats> {
+    let mut by_host: BTreeMap<&str, Vec<&HttpTraceRow>> = BTreeMap::new();
+    for row in rows {
+        by_host.entry(row.host()).or_default().push(row);
+    }
+    by_host
+        .into_iter()
+        .map(|(host, rows)| {
+            let mut durations: Vec<_> = rows.iter().filter_map(|row| row.duration()).collect();
+            durations.sort();
+            HostStats {
+                host: host.to_string(),
+                requests: rows.len(),
+                failures: rows
+                    .iter()
+                    .filter(|row| {
+                        !matches!(
+                            row.status_class(),
+                            StatusClass::Success | StatusClass::Redirect
+                        )
+                    })
+                    .count(),
+                percentiles: (!durations.is_empty()).then(|| {
+                    [
+                        percentile(&durations, 50),
+                        percentile(&durations, 90),
+                        percentile(&durations, 99),
+                    ]
+                }),
+                max: durations.last().copied(),
+            }
+        })
+        .collect()
+}
+
+fn format_duration(duration: Option<TimeDelta>) -> String {
+    duration
+        .and_then(|duration| duration.to_std().ok())
+        .map_or_else(|| "-".to_string(), |duration| format!("{duration:?}"))
+}
+
+#[derive(Properties, PartialEq)]
+pub struct HttpTracePanelProps {
+    pub traces: Vec<HttpTraceRow>,
+    /// The execution whose descendants made the requests, their ids are shown relative to it.
+    pub execution_id: grpc_client::ExecutionId,
+}
+
+/// Sortable and filterable table of HTTP client traces with per-host latency percentiles.
+#[component(HttpTracePanel)]
+pub fn http_trace_panel(
+    HttpTracePanelProps {
+        traces,
+        execution_id,
+    }: &HttpTracePanelProps,
+) -> Html {
+    let host_filter = use_state(|| None::<String>);
+    let status_filter = use_state(|| None::<StatusClass>);
+    let min_duration_millis = use_state(|| 0_i64);
+    let sort = use_state(|| (SortColumn::SentAt, false));
+
+    let rows: Vec<&HttpTraceRow> = {
+        let mut rows: Vec<_> = traces
+            .iter()
+            .filter(|row| host_filter.as_deref().is_none_or(|host| row.host() == host))
+            .filter(|row| status_filter.is_none_or(|class| row.status_class() == class))
+            .filter(|row| {
+                *min_duration_millis == 0
+                    || row
+                        .duration()
+                        .is_some_and(|duration| duration.num_milliseconds() >= *min_duration_millis)
+            })
+            .collect();
+        let (column, descending) = *sort;
+        rows.sort_by(|a, b| {
+            let ordering = match column {
+                SortColumn::SentAt => a.sent_at.cmp(&b.sent_at),
+                SortColumn::Execution => a.execution_id.id.cmp(&b.execution_id.id),
+                SortColumn::Method => a.method.cmp(&b.method),
+                SortColumn::Uri => a.uri.cmp(&b.uri),
+                SortColumn::Status => (a.status_class() as u8, status_code(a))
+                    .cmp(&(b.status_class() as u8, status_code(b))),
+                SortColumn::Duration => a.duration().cmp(&b.duration()),
+            };
+            if descending {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        });
+        rows
+    };
+
+    let hosts: BTreeSet<&str> = traces.iter().map(HttpTraceRow::host).collect();
+    let on_host_change = {
+        let host_filter = host_filter.clone();
+        Callback::from(move |e: Event| {
+            let target: HtmlSelectElement = e.target_unchecked_into();
+            let value = target.value();
+            host_filter.set((!value.is_empty()).then_some(value));
+        })
+    };
+    let on_status_change = {
+        let status_filter = status_filter.clone();
+        Callback::from(move |e: Event| {
+            let target: HtmlSelectElement = e.target_unchecked_into();
+            let value = target.value();
+            status_filter.set(
+                StatusClass::ALL
+                    .into_iter()
+                    .find(|class| class.to_string() == value),
+            );
+        })
+    };
+    let on_min_duration_change = {
+        let min_duration_millis = min_duration_millis.clone();
+        Callback::from(move |e: Event| {
+            let target: HtmlInputElement = e.target_unchecked_into();
+            min_duration_millis.set(target.value().parse().unwrap_or_default());
+        })
+    };
+    let header = |label: &'static str, column: SortColumn| {
+        let (current, descending) = *sort;
+        let indicator = match (current == column, descending) {
+            (false, _) => "",
+            (true, false) => " ▲",
+            (true, true) => " ▼",
+        };
+        let onclick = {
+            let sort = sort.clone();
+            Callback::from(move |_: MouseEvent| {
+                let (current, descending) = *sort;
+                sort.set((column, current == column && !descending));
+            })
+        };
+        html! {
+            <th class="sortable" {onclick}>{ label }{ indicator }</th>
+        }
+    };
+
+    let stats = host_stats(&rows);
+    let stats_table = html! {
+        <table class="config-table http-trace-stats">
+            <thead>
+                <tr>
+                    <th>{"Host"}</th>
+                    <th>{"Requests"}</th>
+                    <th>{"Failures"}</th>
+                    <th>{"p50"}</th>
+                    <th>{"p90"}</th>
+                    <th>{"p99"}</th>
+                    <th>{"Max"}</th>
+                </tr>
+            </thead>
+            <tbody>
+                { for stats.iter().map(|stats| {
+                    let [p50, p90, p99] = stats.percentiles.map_or([None; 3], |p| p.map(Some));
+                    html! {
+                        <tr>
+                            <td>{ &stats.host }</td>
+                            <td>{ stats.requests }</td>
+                            <td class={classes!((stats.failures > 0).then_some("http-trace-failed"))}>
+                                { stats.failures }
+                            </td>
+                            <td>{ format_duration(p50) }</td>
+                            <td>{ format_duration(p90) }</td>
+                            <td>{ format_duration(p99) }</td>
+                            <td>{ format_duration(stats.max) }</td>
+                        </tr>
+                    }
+                }) }
+            </tbody>
+        </table>
+    };
+
+    let trace_rows = rows.iter().map(|row| {
+        let relative_id = row
+            .execution_id
+            .id
+            .strip_prefix(execution_id.id.as_str())
+            .map(|suffix| suffix.trim_start_matches(EXECUTION_ID_INFIX))
+            .filter(|suffix| !suffix.is_empty())
+            .unwrap_or("(this execution)")
+            .to_string();
+        let status = match &row.result {
+            Some(grpc_client::http_client_trace::Result::Status(status)) => status.to_string(),
+            Some(grpc_client::http_client_trace::Result::Error(error)) => format!("Error: {error}"),
+            None => StatusClass::NoResponse.to_string(),
+        };
+        let is_failed = !matches!(
+            row.status_class(),
+            StatusClass::Success | StatusClass::Redirect
+        );
+        html! {
+            <tr class={classes!(is_failed.then_some("http-trace-failed"))}>
+                <td title={row.sent_at.to_string()}>{ format_date(row.sent_at) }</td>
+                <td title={row.execution_id.to_string()}>
+                    <Link<Route> to={Route::ExecutionTrace { execution_id: row.execution_id.clone() }}>
+                        { relative_id }
+                    </Link<Route>>
+                    { format!(" @{}", row.version) }
+                </td>
+                <td>{ &row.method }</td>
+                <td class="http-trace-uri" title={row.uri.clone()}>{ &row.uri }</td>
+                <td>{ status }</td>
+                <td>{ format_duration(row.duration()) }</td>
+                <td><CopyButton text={row.to_curl()} label="curl" /></td>
+            </tr>
+        }
+    });
+
+    html! {
+        <div class="http-trace-panel">
+            <div class="http-trace-filters">
+                <label>
+                    {"Host "}
+                    <select onchange={on_host_change}>
+                        <option value="" selected={host_filter.is_none()}>{"All"}</option>
+                        { for hosts.iter().map(|host| html! {
+                            <option
+                                value={host.to_string()}
+                                selected={host_filter.as_deref() == Some(*host)}
+                            >
+                                { host.to_string() }
+                            </option>
+                        }) }
+                    </select>
+                </label>
+                <label>
+                    {"Status "}
+                    <select onchange={on_status_change}>
+                        <option value="" selected={status_filter.is_none()}>{"All"}</option>
+                        { for StatusClass::ALL.into_iter().map(|class| html! {
+                            <option
+                                value={class.to_string()}
+                                selected={*status_filter == Some(class)}
+                            >
+                                { class.to_string() }
+                            </option>
+                        }) }
+                    </select>
+                </label>
+                <label>
+                    {"Slower than "}
+                    <input
+                        type="number"
+                        min="0"
+                        value={min_duration_millis.to_string()}
+                        onchange={on_min_duration_change}
+                    />
+                    {" ms"}
+                </label>
+                <span class="secondary-text">
+                    { format!("{} of {} requests", rows.len(), traces.len()) }
+                </span>
+            </div>
+            if traces.is_empty() {
+                <p class="secondary-text">{"No HTTP client traces were recorded."}</p>
+            } else {
+                { stats_table }
+                <table class="config-table http-trace-table">
+                    <thead>
+                        <tr>
+                            { header("Sent at", SortColumn::SentAt) }
+                            { header("Execution", SortColumn::Execution) }
+                            { header("Method", SortColumn::Method) }
+                            { header("URI", SortColumn::Uri) }
+                            { header("Status", SortColumn::Status) }
+                            { header("Duration", SortColumn::Duration) }
+                            <th></th>
+                        </tr>
+                    </thead>
+                    <tbody>
+                        { for trace_rows }
+                    </tbody>
+                </table>
+            }
+        </div>
+    }
+}
+
+fn status_code(row: &HttpTraceRow) -> u32 {
+    match row.result {
+        Some(grpc_client::http_client_trace::Result::Status(status)) => status,
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(uri: &str, millis: Option<i64>) -> HttpTraceRow {
+        let sent_at = DateTime::from_timestamp(0, 0).unwrap();
+        HttpTraceRow {
+            execution_id: grpc_client::ExecutionId {
+                id: "E_01".to_string(),
+            },
+            version: 2,
+            method: "POST".to_string(),
+            uri: uri.to_string(),
+            sent_at,
+            finished_at: millis.map(|millis| sent_at + TimeDelta::milliseconds(millis)),
+            result: millis.map(|_| grpc_client::http_client_trace::Result::Status(200)),
+        }
+    }
+
+    #[test]
+    fn host_stats_use_nearest_rank_percentiles() {
+        let rows: Vec<_> = (1..=10)
+            .map(|millis| {
+                row(
+                    "https://user@api.example.com:8443/v1?q=1",
+                    Some(millis * 10),
+                )
+            })
+            .chain([row("http://other.test", None)])
+            .collect();
+        let stats = host_stats(&rows.iter().collect::<Vec<_>>());
+        assert_eq!(
+            vec!["api.example.com:8443", "other.test"],
+            stats
+                .iter()
+                .map(|stats| stats.host.as_str())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            Some([50, 90, 100].map(TimeDelta::milliseconds)),
+            stats[0].percentiles
+        );
+        assert_eq!(
+            (1, 1, None),
+            (stats[1].requests, stats[1].failures, stats[1].percentiles)
+        );
+        assert_eq!(
+            r"curl -X POST 'http://it'\''s.test'",
+            row("http://it's.test", None).to_curl()
+        );
+    }
+
+    #[test]
+    fn status_class_of_codes_outside_of_the_http_ranges() {
+        let class = |status| {
+            HttpTraceRow {
+                result: Some(grpc_client::http_client_trace::Result::Status(status)),
+                ..row("http://api.test", Some(1))
+            }
+            .status_class()
+        };
+        assert_eq!(StatusClass::Other, class(101));
+        assert_eq!(StatusClass::Success, class(204));
+        assert_eq!(StatusClass::ServerError, class(503));
+        assert_eq!(StatusClass::ServerError, class(599));
+        assert_eq!(StatusClass::Other, class(600));
+        assert_eq!(StatusClass::Other, class(0));
+    }
+}
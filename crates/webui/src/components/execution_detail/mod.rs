@@ -1,11 +1,13 @@
 pub mod cancellation_requested;
 pub mod component_upgrade_finished;
 pub mod created;
+pub mod event_filter;
 pub mod finished;
 pub mod history;
 pub mod http_trace;
 pub mod locked;
 pub mod paused;
+pub mod retry_summary;
 pub mod temporarily_failed;
 pub mod timed_out;
 pub mod tree_component;
@@ -0,0 +1,403 @@
+//! Retry summary of an execution: every run between a `Locked` event and the event that ended
+//! it, with the backoff that followed and the time each kind of failure cost.
+
+use crate::grpc::{
+    grpc_client::{
+        ExecutionEvent,
+        execution_event::{self, history_event::Event as HistoryEventEnum},
+    },
+    version::VersionType,
+};
+use crate::util::time::{TimeGranularity, human_formatted_timedelta};
+use chrono::{DateTime, TimeDelta, Utc};
+use yew::prelude::*;
+
+#[derive(Debug, Clone, PartialEq, Eq, derive_more::Display)]
+pub enum AttemptOutcome {
+    /// No event has ended the run yet.
+    #[display("Running")]
+    Running,
+    #[display("Finished")]
+    Finished,
+    #[display("Temporarily failed")]
+    Failed { reason: String },
+    /// The lock expired before the run reported back.
+    #[display("Lock expired")]
+    TimedOut,
+    #[display("Unlocked")]
+    Unlocked { reason: String },
+    /// The run stopped at a join-next and the execution waited for a response.
+    #[display("Blocked by join set")]
+    Blocked,
+    /// A different run locked the execution without anything ending this one.
+    #[display("Lost")]
+    Lost,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Attempt {
+    pub run_id: String,
+    /// Version of the first `Locked` event of the run.
+    pub locked_version: VersionType,
+    pub locked_at: DateTime<Utc>,
+    pub outcome: AttemptOutcome,
+    /// Version of the event that ended the run.
+    pub ended_version: Option<VersionType>,
+    pub ended_at: Option<DateTime<Utc>>,
+    /// Backoff requested by a temporary failure, timeout or unlock.
+    pub backoff_expires_at: Option<DateTime<Utc>>,
+    /// Time between the end of this run and the next `Locked` event.
+    pub wait_before_next: Option<TimeDelta>,
+}
+impl Attempt {
+    pub fn run_duration(&self) -> Option<TimeDelta> {
+        self.ended_at.map(|ended_at| ended_at - self.locked_at)
+    }
+
+    /// Run time and the wait that followed.
+    fn lost(&self) -> TimeDelta {
+        self.run_duration().unwrap_or_default() + self.wait_before_next.unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReasonGroup {
+    pub reason: String,
+    pub versions: Vec<VersionType>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RetrySummary {
+    pub attempts: Vec<Attempt>,
+}
+impl RetrySummary {
+    pub fn from_events(events: &[ExecutionEvent]) -> Self {
+        let mut attempts: Vec<Attempt> = Vec::new();
+        // Whether the last event of the open run was a join-next.
+        let mut last_was_join_next = false;
+        for event in events {
+            let (Some(inner), Some(created_at)) = (&event.event, event.created_at) else {
+                continue;
+            };
+            let created_at = DateTime::from(created_at);
+            let open = attempts
+                .last_mut()
+                .filter(|attempt| attempt.outcome == AttemptOutcome::Running);
+            let (outcome, backoff_expires_at) = match inner {
+                execution_event::Event::Locked(locked) => {
+                    if let Some(open) = open {
+                        // Lock extensions keep the run id.
+                        if open.run_id == locked.run_id {
+                            continue;
+                        }
+                        open.outcome = if last_was_join_next {
+                            AttemptOutcome::Blocked
+                        } else {
+                            AttemptOutcome::Lost
+                        };
+                    }
+                    if let Some(previous) = attempts.last_mut() {
+                        previous.wait_before_next = previous
+                            .ended_at
+                            .map(|ended_at| created_at - ended_at)
+                            .filter(|wait| *wait > TimeDelta::zero());
+                    }
+                    attempts.push(Attempt {
+                        run_id: locked.run_id.clone(),
+                        locked_version: event.version,
+                        locked_at: created_at,
+                        outcome: AttemptOutcome::Running,
+                        ended_version: None,
+                        ended_at: None,
+                        backoff_expires_at: None,
+                        wait_before_next: None,
+                    });
+                    last_was_join_next = false;
+                    continue;
+                }
+                execution_event::Event::HistoryVariant(history) => {
+                    last_was_join_next =
+                        matches!(history.event, Some(HistoryEventEnum::JoinNext(_)));
+                    if let Some(open) = open {
+                        open.ended_version = Some(event.version);
+                        open.ended_at = Some(created_at);
+                    }
+                    continue;
+                }
+                execution_event::Event::TemporarilyFailed(failed) => (
+                    AttemptOutcome::Failed {
+                        reason: failed.reason.clone(),
+                    },
+                    failed.backoff_expires_at,
+                ),
+                execution_event::Event::TemporarilyTimedOut(timed_out) => {
+                    (AttemptOutcome::TimedOut, timed_out.backoff_expires_at)
+                }
+                execution_event::Event::Unlocked(unlocked) => (
+                    AttemptOutcome::Unlocked {
+                        reason: unlocked.reason.clone(),
+                    },
+                    unlocked.backoff_expires_at,
+                ),
+                execution_event::Event::Finished(_) => (AttemptOutcome::Finished, None),
+                _ => continue,
+            };
+            if let Some(open) = open {
+                open.outcome = outcome;
+                open.ended_version = Some(event.version);
+                open.ended_at = Some(created_at);
+                open.backoff_expires_at = backoff_expires_at.map(DateTime::from);
+            }
+        }
+        Self { attempts }
+    }
+
+    fn count(&self, predicate: impl Fn(&AttemptOutcome) -> bool) -> usize {
+        self.attempts
+            .iter()
+            .filter(|attempt| predicate(&attempt.outcome))
+            .count()
+    }
+
+    fn lost(&self, predicate: impl Fn(&AttemptOutcome) -> bool) -> TimeDelta {
+        self.attempts
+            .iter()
+            .filter(|attempt| predicate(&attempt.outcome))
+            .map(Attempt::lost)
+            .sum()
+    }
+
+    pub fn has_retries(&self) -> bool {
+        self.attempts.iter().any(|attempt| {
+            matches!(
+                attempt.outcome,
+                AttemptOutcome::Failed { .. }
+                    | AttemptOutcome::TimedOut
+                    | AttemptOutcome::Unlocked { .. }
+                    | AttemptOutcome::Lost
+            )
+        })
+    }
+
+    /// Failure and unlock reasons, most frequent first.
+    pub fn reasons(&self) -> Vec<ReasonGroup> {
+        let mut groups: Vec<ReasonGroup> = Vec::new();
+        for attempt in &self.attempts {
+            let reason = match &attempt.outcome {
+                AttemptOutcome::Failed { reason } | AttemptOutcome::Unlocked { reason } => {
+                    reason.clone()
+                }
+                AttemptOutcome::TimedOut => attempt.outcome.to_string(),
+                _ => continue,
+            };
+            let version = attempt.ended_version.unwrap_or(attempt.locked_version);
+            match groups.iter_mut().find(|group| group.reason == reason) {
+                Some(group) => group.versions.push(version),
+                None => groups.push(ReasonGroup {
+                    reason,
+                    versions: vec![version],
+                }),
+            }
+        }
+        groups.sort_by_key(|group| std::cmp::Reverse(group.versions.len()));
+        groups
+    }
+}
+
+fn format_delta(delta: TimeDelta) -> String {
+    human_formatted_timedelta(delta, TimeGranularity::Fine)
+}
+
+#[derive(Properties, PartialEq)]
+pub struct RetrySummaryPanelProps {
+    pub summary: RetrySummary,
+}
+
+#[component(RetrySummaryPanel)]
+pub fn retry_summary_panel(RetrySummaryPanelProps { summary }: &RetrySummaryPanelProps) -> Html {
+    let failures = summary.count(|outcome| matches!(outcome, AttemptOutcome::Failed { .. }));
+    let timeouts = summary.count(|outcome| *outcome == AttemptOutcome::TimedOut);
+    let unlocks = summary.count(|outcome| matches!(outcome, AttemptOutcome::Unlocked { .. }));
+    let lost_to_failures = summary.lost(|outcome| matches!(outcome, AttemptOutcome::Failed { .. }));
+    let lost_to_timeouts =
+        summary.lost(|outcome| matches!(outcome, AttemptOutcome::TimedOut | AttemptOutcome::Lost));
+    let lost_to_unlocks =
+        summary.lost(|outcome| matches!(outcome, AttemptOutcome::Unlocked { .. }));
+    let attempt_rows = summary.attempts.iter().enumerate().map(|(idx, attempt)| {
+        let is_error = matches!(
+            attempt.outcome,
+            AttemptOutcome::Failed { .. } | AttemptOutcome::TimedOut | AttemptOutcome::Lost
+        );
+        let outcome = match &attempt.outcome {
+            AttemptOutcome::Failed { reason } | AttemptOutcome::Unlocked { reason } => {
+                format!("{}: {reason}", attempt.outcome)
+            }
+            outcome => outcome.to_string(),
+        };
+        let versions = match attempt.ended_version {
+            Some(ended_version) => format!("{}–{ended_version}", attempt.locked_version),
+            None => format!("{}–", attempt.locked_version),
+        };
+        let backoff = attempt
+            .backoff_expires_at
+            .zip(attempt.ended_at)
+            .map(|(expires_at, ended_at)| format_delta(expires_at - ended_at));
+        html! {
+            <tr class={classes!(is_error.then_some("retry-attempt-error"))}>
+                <td>{ idx + 1 }</td>
+                <td>{ versions }</td>
+                <td><code>{ &attempt.run_id }</code></td>
+                <td>{ attempt.run_duration().map(format_delta).unwrap_or_default() }</td>
+                <td class="retry-outcome" title={outcome.clone()}>{ outcome }</td>
+                <td>{ backoff.unwrap_or_default() }</td>
+                <td>{ attempt.wait_before_next.map(format_delta).unwrap_or_default() }</td>
+            </tr>
+        }
+    });
+    let reason_rows = summary.reasons().into_iter().map(|group| {
+        let versions = group
+            .versions
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+        html! {
+            <tr>
+                <td>{ group.versions.len() }</td>
+                <td class="retry-outcome" title={group.reason.clone()}>{ group.reason }</td>
+                <td>{ versions }</td>
+            </tr>
+        }
+    });
+    html! {
+        <details class="retry-summary" open=true>
+            <summary>
+                { format!(
+                    "Retries: {} attempt{}, {failures} failed, {timeouts} timed out, {unlocks} unlocked",
+                    summary.attempts.len(),
+                    if summary.attempts.len() == 1 { "" } else { "s" },
+                ) }
+            </summary>
+            <p class="secondary-text">
+                { format!(
+                    "Time lost to failures: {}, to lock expiry: {}",
+                    format_delta(lost_to_failures),
+                    format_delta(lost_to_timeouts),
+                ) }
+                if unlocks > 0 {
+                    { format!(", to unlocks: {}", format_delta(lost_to_unlocks)) }
+                }
+                {" (run time plus the wait until the next attempt)"}
+            </p>
+            <table class="config-table">
+                <thead>
+                    <tr>
+                        <th>{"#"}</th>
+                        <th>{"Versions"}</th>
+                        <th>{"Run ID"}</th>
+                        <th>{"Ran for"}</th>
+                        <th>{"Outcome"}</th>
+                        <th>{"Backoff"}</th>
+                        <th>{"Waited"}</th>
+                    </tr>
+                </thead>
+                <tbody>
+                    { for attempt_rows }
+                </tbody>
+            </table>
+            <table class="config-table">
+                <thead>
+                    <tr>
+                        <th>{"Count"}</th>
+                        <th>{"Reason"}</th>
+                        <th>{"Versions"}</th>
+                    </tr>
+                </thead>
+                <tbody>
+                    { for reason_rows }
+                </tbody>
+            </table>
+        </details>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(version: VersionType, secs: i64, event: execution_event::Event) -> ExecutionEvent {
+        ExecutionEvent {
+            version,
+            created_at: Some(prost_wkt_types::Timestamp {
+                seconds: secs,
+                nanos: 0,
+            }),
+            event: Some(event),
+            ..Default::default()
+        }
+    }
+
+    fn locked(run_id: &str) -> execution_event::Event {
+        execution_event::Event::Locked(execution_event::Locked {
+            run_id: run_id.to_string(),
+            ..Default::default()
+        })
+    }
+
+    fn failed(reason: &str) -> execution_event::Event {
+        execution_event::Event::TemporarilyFailed(execution_event::TemporarilyFailed {
+            reason: reason.to_string(),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn attempts_are_split_by_run_id() {
+        let summary = RetrySummary::from_events(&[
+            event(1, 0, locked("run_a")),
+            event(2, 10, failed("connection refused")),
+            event(3, 70, locked("run_b")),
+            // Lock extension of the same run.
+            event(4, 80, locked("run_b")),
+            event(
+                5,
+                100,
+                execution_event::Event::TemporarilyTimedOut(Default::default()),
+            ),
+            event(6, 160, locked("run_c")),
+            event(7, 165, failed("connection refused")),
+            event(8, 200, locked("run_d")),
+        ]);
+        let attempts: Vec<_> = summary
+            .attempts
+            .iter()
+            .map(|attempt| {
+                (
+                    attempt.run_id.as_str(),
+                    attempt.outcome.clone(),
+                    attempt.run_duration().map(|delta| delta.num_seconds()),
+                    attempt.wait_before_next.map(|delta| delta.num_seconds()),
+                )
+            })
+            .collect();
+        let failed = AttemptOutcome::Failed {
+            reason: "connection refused".to_string(),
+        };
+        assert_eq!(
+            vec![
+                ("run_a", failed.clone(), Some(10), Some(60)),
+                ("run_b", AttemptOutcome::TimedOut, Some(30), Some(60)),
+                ("run_c", failed, Some(5), Some(35)),
+                ("run_d", AttemptOutcome::Running, None, None),
+            ],
+            attempts
+        );
+        assert_eq!(
+            TimeDelta::seconds(110),
+            summary.lost(|outcome| matches!(outcome, AttemptOutcome::Failed { .. }))
+        );
+        let reasons = summary.reasons();
+        assert_eq!("connection refused", reasons[0].reason);
+        assert_eq!(vec![2, 7], reasons[0].versions);
+    }
+}
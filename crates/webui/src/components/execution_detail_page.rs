@@ -1,3 +1,7 @@
+use crate::components::execution_detail::event_filter::{
+    EventFilter, EventFilterBar, event_join_set_id, event_kind,
+};
+use crate::components::execution_detail::retry_summary::{RetrySummary, RetrySummaryPanel};
 use crate::components::execution_detail::utils::{compute_join_next_to_response, event_to_detail};
 use crate::components::execution_header::{ExecutionHeader, ExecutionLink};
 use crate::components::notification::{Notification, NotificationContext};
@@ -8,10 +12,7 @@ use crate::components::trace::trace_view::{
 use crate::grpc::grpc_client::{
     self, ExecutionEvent, ExecutionId, JoinSetId, JoinSetResponseEvent, ResponseWithCursor,
     execution_event,
-    execution_event::history_event::{
-        Event as HistoryEventEnum, JoinNext, JoinNextTooMany, JoinNextTry, JoinSetCreated,
-        JoinSetRequest, join_set_request,
-    },
+    execution_event::history_event::{Event as HistoryEventEnum, JoinSetRequest, join_set_request},
 };
 use crate::util::time::{
     TimeGranularity, format_date, human_formatted_timedelta, relative_time_if_significant,
@@ -19,7 +20,7 @@ use crate::util::time::{
 use assert_matches::assert_matches;
 use chrono::DateTime;
 use gloo::timers::future::TimeoutFuture;
-use hashbrown::HashMap;
+use hashbrown::{HashMap, HashSet};
 use log::{error, trace};
 use std::rc::Rc;
 use wasm_bindgen::JsCast;
@@ -168,13 +169,16 @@ impl Reducible for ExecutionLogState {
 #[component(ExecutionLogPage)]
 pub fn execution_log_page(ExecutionLogPageProps { execution_id }: &ExecutionLogPageProps) -> Html {
     let log_state = use_reducer_eq(ExecutionLogState::default);
+    let filter = use_state(EventFilter::default);
     let notifications =
         use_context::<NotificationContext>().expect("NotificationContext should be provided");
 
     use_effect_with(execution_id.clone(), {
         let log_state = log_state.clone();
+        let filter = filter.clone();
         move |execution_id| {
             log_state.dispatch(ExecutionLogAction::AddExecutionId(execution_id.clone()));
+            filter.set(EventFilter::default());
         }
     });
 
@@ -233,10 +237,17 @@ pub fn execution_log_page(ExecutionLogPageProps { execution_id }: &ExecutionLogP
             .filter_map(|(id, created)| created.clone().map(|created| (id.clone(), created)))
             .collect();
 
+    let shown_versions: HashSet<u32> = events
+        .iter()
+        .filter(|event| filter.matches(event, &join_next_version_to_response))
+        .map(|event| event.version)
+        .collect();
+
     let details_html = if !events.is_empty() {
         render_execution_details(
             execution_id,
             events,
+            &shown_versions,
             &join_next_version_to_response,
             &submit_await_version_groups,
             &child_created_events,
@@ -245,10 +256,43 @@ pub fn execution_log_page(ExecutionLogPageProps { execution_id }: &ExecutionLogP
         html! { <div class="loading-details">{"Loading execution details..."}</div> }
     };
 
+    let mut kind_counts: HashMap<_, usize> = HashMap::new();
+    let mut join_sets = HashSet::new();
+    for event in events {
+        if let Some(kind) = event_kind(event) {
+            *kind_counts.entry(kind).or_default() += 1;
+        }
+        if let Some(join_set_id) = event_join_set_id(event) {
+            join_sets.insert(join_set_id.to_string());
+        }
+    }
+    let mut kind_counts: Vec<_> = kind_counts.into_iter().collect();
+    kind_counts.sort_unstable();
+    let mut join_sets: Vec<_> = join_sets.into_iter().collect();
+    join_sets.sort_unstable();
+    let retry_summary = RetrySummary::from_events(events);
+    let on_filter_change = {
+        let filter = filter.clone();
+        Callback::from(move |new_filter| filter.set(new_filter))
+    };
+
     html! {
         <>
             <ExecutionHeader execution_id={execution_id.clone()} link={ExecutionLink::ExecutionLog} />
             <div class="timeline-container">
+                if retry_summary.has_retries() {
+                    <RetrySummaryPanel summary={retry_summary} />
+                }
+                if !events.is_empty() {
+                    <EventFilterBar
+                        {kind_counts}
+                        {join_sets}
+                        filter={(*filter).clone()}
+                        on_change={on_filter_change}
+                        shown={shown_versions.len()}
+                        total={events.len()}
+                    />
+                }
                 {details_html}
             </div>
         </>
@@ -389,6 +433,8 @@ fn on_state_change(
 fn render_execution_details(
     current_execution_id: &ExecutionId,
     events: &[ExecutionEvent],
+    // Versions that pass the filter; durations still use the full history.
+    shown_versions: &HashSet<u32>,
     join_next_version_to_response: &HashMap<u32, &JoinSetResponseEvent>,
     submit_await_version_groups: &HashMap<u32, Vec<u32>>,
     child_created_events: &HashMap<ExecutionId, execution_event::Created>,
@@ -450,6 +496,7 @@ fn render_execution_details(
 
     let rows: Vec<_> = events
         .iter()
+        .filter(|event| shown_versions.contains(&event.version))
         .map(|event| {
             let detail = event_to_detail(
                 event,
@@ -467,7 +514,7 @@ fn render_execution_details(
             let event_inner = event.event.as_ref().unwrap();
             let mut circle_class = "version-circle";
             let mut circle_color_style = "".to_string();
-            let mut join_set_id = None;
+            let join_set_id = event_join_set_id(event);
 
             match event_inner {
                 execution_event::Event::Created(_) => circle_class = "version-circle is-created",
@@ -475,22 +522,11 @@ fn render_execution_details(
                 execution_event::Event::TemporarilyFailed(_) | execution_event::Event::TemporarilyTimedOut(_) => {
                     circle_class = "version-circle is-error"
                 },
-                execution_event::Event::HistoryVariant(h) => {
-                    if let Some(history_event) = &h.event {
-                        join_set_id = match history_event {
-                            HistoryEventEnum::JoinSetRequest(JoinSetRequest { join_set_id: Some(jid), .. }) => Some(jid),
-                            HistoryEventEnum::JoinNext(JoinNext { join_set_id: Some(jid), .. }) => Some(jid),
-                            HistoryEventEnum::JoinSetCreated(JoinSetCreated { join_set_id: Some(jid), .. }) => Some(jid),
-                            HistoryEventEnum::JoinNextTry(JoinNextTry { join_set_id: Some(jid), .. }) => Some(jid),
-                            HistoryEventEnum::JoinNextTooMany(JoinNextTooMany { join_set_id: Some(jid), .. }) => Some(jid),
-                            _ => None
-                        };
-
-                        if let Some(join_set_id) = join_set_id {
-                            circle_class = "version-circle is-join";
-                            let color = join_set_id.color();
-                            circle_color_style = format!("border-color: {0}; color: {0};", color);
-                        }
+                execution_event::Event::HistoryVariant(_) => {
+                    if let Some(join_set_id) = join_set_id {
+                        circle_class = "version-circle is-join";
+                        let color = join_set_id.color();
+                        circle_color_style = format!("border-color: {0}; color: {0};", color);
                     }
                 },
                 _ => {}
@@ -500,7 +536,7 @@ fn render_execution_details(
             let scroll_button = if let Some((target_version, color)) = event_links.get(&event.version) {
                 let diff = (*target_version).abs_diff(event.version);
 
-                if diff > 1 {
+                if diff > 1 && shown_versions.contains(target_version) {
                     let label = if *target_version > event.version {
                         "Go to Await ↓"
                     } else {
@@ -622,5 +658,8 @@ fn render_execution_details(
         })
         .collect();
 
+    if rows.is_empty() {
+        return html! { <div class="loading-details">{"No events match the filters."}</div> };
+    }
     html! { <>{rows}</> }
 }
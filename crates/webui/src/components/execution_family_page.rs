@@ -0,0 +1,407 @@
+use crate::components::execution_header::{ExecutionHeader, ExecutionLink};
+use crate::components::execution_status::status_to_string;
+use crate::components::notification::{Notification, NotificationContext};
+use crate::grpc::{
+    ffqn::FunctionFqn,
+    finished_result_kind::FinishedResultKind,
+    grpc_client::{
+        self, ExecutionId, ExecutionSummary,
+        execution_repository_client::ExecutionRepositoryClient,
+        execution_status::{self, Finished},
+        list_executions_request::{Cursor, OlderThan, Pagination, cursor},
+    },
+};
+use crate::util::time::{TimeGranularity, human_formatted_timedelta};
+use chrono::DateTime;
+use hashbrown::{HashMap, HashSet};
+use log::error;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+const PAGE_SIZE: u32 = 200;
+/// Stop paging after this many executions so that huge families do not freeze the tab.
+const MAX_EXECUTIONS: usize = 5_000;
+
+#[derive(Properties, PartialEq)]
+pub struct ExecutionFamilyPageProps {
+    pub execution_id: ExecutionId,
+}
+
+#[derive(Clone, PartialEq)]
+struct FamilyNode {
+    summary: ExecutionSummary,
+    children: Vec<FamilyNode>,
+    /// The execution itself or any of its descendants failed.
+    has_failure: bool,
+    descendants: usize,
+}
+
+fn is_failed(summary: &ExecutionSummary) -> bool {
+    finished_result_kind(summary).is_some_and(|kind| kind != FinishedResultKind::Ok)
+}
+
+fn finished_result_kind(summary: &ExecutionSummary) -> Option<FinishedResultKind> {
+    match summary.current_status.as_ref()?.status.as_ref()? {
+        execution_status::Status::Finished(Finished { result_kind, .. }) => result_kind
+            .as_ref()?
+            .value
+            .as_ref()
+            .map(FinishedResultKind::from),
+        _ => None,
+    }
+}
+
+/// Arrange the summaries into trees using the `ExecutionId` hierarchy. Executions whose
+/// parent was not listed hang under their closest listed ancestor.
+fn build_family(summaries: Vec<ExecutionSummary>) -> Vec<FamilyNode> {
+    let ids: HashSet<String> = summaries
+        .iter()
+        .filter_map(|summary| summary.execution_id.as_ref())
+        .map(|execution_id| execution_id.id.clone())
+        .collect();
+    let mut children_of: HashMap<Option<String>, Vec<ExecutionSummary>> = HashMap::new();
+    for summary in summaries {
+        let Some(execution_id) = summary.execution_id.as_ref() else {
+            continue;
+        };
+        let mut parent = execution_id.parent_id();
+        while let Some(parent_id) = &parent
+            && !ids.contains(&parent_id.id)
+        {
+            parent = parent_id.parent_id();
+        }
+        children_of
+            .entry(parent.map(|parent| parent.id))
+            .or_default()
+            .push(summary);
+    }
+
+    fn assemble(
+        parent: Option<String>,
+        children_of: &mut HashMap<Option<String>, Vec<ExecutionSummary>>,
+    ) -> Vec<FamilyNode> {
+        let mut summaries = children_of.remove(&parent).unwrap_or_default();
+        summaries.sort_by_key(|summary| summary.created_at.map(DateTime::from));
+        summaries
+            .into_iter()
+            .map(|summary| {
+                let id = summary.execution_id.as_ref().map(|id| id.id.clone());
+                let children = assemble(id, children_of);
+                FamilyNode {
+                    has_failure: is_failed(&summary)
+                        || children.iter().any(|child| child.has_failure),
+                    descendants: children.iter().map(|child| 1 + child.descendants).sum(),
+                    summary,
+                    children,
+                }
+            })
+            .collect()
+    }
+    assemble(None, &mut children_of)
+}
+
+#[derive(Clone, PartialEq)]
+enum FetchState {
+    Loading(usize),
+    Done {
+        truncated: bool,
+    },
+    /// Listing stopped with the error, the executions fetched until then are shown.
+    Failed(String),
+}
+
+#[component(ExecutionFamilyPage)]
+pub fn execution_family_page(
+    ExecutionFamilyPageProps { execution_id }: &ExecutionFamilyPageProps,
+) -> Html {
+    let notifications =
+        use_context::<NotificationContext>().expect("NotificationContext should be provided");
+    let summaries = use_state(Vec::<ExecutionSummary>::new);
+    let fetch_state = use_state(|| FetchState::Loading(0));
+    let collapsed = use_state(HashSet::<String>::new);
+    let failing_only = use_state(|| false);
+
+    let root_id = execution_id
+        .as_hierarchy()
+        .into_iter()
+        .next()
+        .map(|(_, root_id)| root_id)
+        .expect("execution id has at least one part");
+
+    use_effect_with(root_id.clone(), {
+        let summaries = summaries.clone();
+        let fetch_state = fetch_state.clone();
+        let collapsed = collapsed.clone();
+        move |root_id: &ExecutionId| {
+            summaries.set(Vec::new());
+            fetch_state.set(FetchState::Loading(0));
+            collapsed.set(HashSet::new());
+            let root_id = root_id.clone();
+            spawn_local(async move {
+                let mut client = ExecutionRepositoryClient::new(crate::auth::client());
+                let mut fetched: Vec<ExecutionSummary> = Vec::new();
+                let done = loop {
+                    #[allow(deprecated)]
+                    let request = grpc_client::ListExecutionsRequest {
+                        function_name_prefix: None,
+                        top_level_only: false,
+                        hide_finished: false,
+                        execution_id_prefix: Some(root_id.id.clone()),
+                        pagination: Some(Pagination::OlderThan(OlderThan {
+                            length: PAGE_SIZE,
+                            cursor: fetched
+                                .last()
+                                .and_then(|summary| summary.execution_id.clone())
+                                .map(|execution_id| Cursor {
+                                    cursor: Some(cursor::Cursor::ExecutionId(execution_id)),
+                                }),
+                            including_cursor: false,
+                        })),
+                        component_digest: None,
+                        deployment_id: None,
+                        function_filter: None,
+                        state_filters: Vec::new(),
+                    };
+                    match client.list_executions(request).await {
+                        Ok(response) => {
+                            let page = response.into_inner().executions;
+                            let is_last = page.len() < PAGE_SIZE as usize;
+                            fetched.extend(page);
+                            if is_last {
+                                break FetchState::Done { truncated: false };
+                            }
+                            if fetched.len() >= MAX_EXECUTIONS {
+                                break FetchState::Done { truncated: true };
+                            }
+                            fetch_state.set(FetchState::Loading(fetched.len()));
+                        }
+                        Err(e) => {
+                            error!("Failed to list the execution family: {e:?}");
+                            notifications.push(Notification::error(format!(
+                                "Failed to list the execution family: {}",
+                                e.message()
+                            )));
+                            break FetchState::Failed(e.message().to_string());
+                        }
+                    }
+                };
+                summaries.set(fetched);
+                fetch_state.set(done);
+            });
+        }
+    });
+
+    let roots = build_family((*summaries).clone());
+    let total: usize = roots.iter().map(|root| 1 + root.descendants).sum();
+    let failed = summaries
+        .iter()
+        .filter(|summary| is_failed(summary))
+        .count();
+
+    let on_failing_only = {
+        let failing_only = failing_only.clone();
+        Callback::from(move |e: Event| {
+            failing_only.set(e.target_unchecked_into::<HtmlInputElement>().checked());
+        })
+    };
+    let on_expand_all = {
+        let collapsed = collapsed.clone();
+        Callback::from(move |_: MouseEvent| collapsed.set(HashSet::new()))
+    };
+    let on_collapse_all = {
+        let collapsed = collapsed.clone();
+        let parents: HashSet<String> = summaries
+            .iter()
+            .filter_map(|summary| summary.execution_id.as_ref()?.parent_id())
+            .map(|parent_id| parent_id.id)
+            .collect();
+        Callback::from(move |_: MouseEvent| collapsed.set(parents.clone()))
+    };
+
+    let status_line = match &*fetch_state {
+        FetchState::Loading(count) => format!("Loading executions... {count}"),
+        FetchState::Failed(message) => format!(
+            "Failed to list the execution family after {} executions: {message}",
+            summaries.len()
+        ),
+        FetchState::Done { truncated } => format!(
+            "{total} execution{}, {failed} failed{}",
+            if total == 1 { "" } else { "s" },
+            if *truncated {
+                format!(", showing the first {}", summaries.len())
+            } else {
+                String::new()
+            }
+        ),
+    };
+
+    html! {
+        <>
+            <ExecutionHeader execution_id={execution_id.clone()} link={ExecutionLink::Family} />
+            <div class="family-tree-page">
+                <div class="family-tree-options">
+                    <label>
+                        <input type="checkbox" checked={*failing_only} onchange={on_failing_only} />
+                        {"Failing branches only"}
+                    </label>
+                    <button onclick={on_expand_all}>{"Expand all"}</button>
+                    <button onclick={on_collapse_all}>{"Collapse all"}</button>
+                    <span class={if matches!(*fetch_state, FetchState::Failed(_)) { "validation-error" } else { "secondary-text" }}>
+                        { status_line }
+                    </span>
+                </div>
+                <ul class="family-tree">
+                    { for roots.iter().map(|node| render_node(node, execution_id, &collapsed, *failing_only)) }
+                </ul>
+            </div>
+        </>
+    }
+}
+
+fn render_node(
+    node: &FamilyNode,
+    current_execution_id: &ExecutionId,
+    collapsed: &UseStateHandle<HashSet<String>>,
+    failing_only: bool,
+) -> Html {
+    if failing_only && !node.has_failure {
+        return Html::default();
+    }
+    let Some(execution_id) = node.summary.execution_id.clone() else {
+        return Html::default();
+    };
+    let is_collapsed = collapsed.contains(&execution_id.id);
+    let toggle = {
+        let collapsed = collapsed.clone();
+        let id = execution_id.id.clone();
+        Callback::from(move |_: MouseEvent| {
+            let mut new_collapsed = (*collapsed).clone();
+            if !new_collapsed.remove(&id) {
+                new_collapsed.insert(id.clone());
+            }
+            collapsed.set(new_collapsed);
+        })
+    };
+    let ffqn = node
+        .summary
+        .function_name
+        .clone()
+        .map(|function_name| FunctionFqn::from(function_name).to_string())
+        .unwrap_or_default();
+    let status = node
+        .summary
+        .current_status
+        .as_ref()
+        .and_then(|status| status.status.as_ref());
+    let duration = match status {
+        Some(execution_status::Status::Finished(Finished {
+            finished_at: Some(finished_at),
+            ..
+        })) => node.summary.created_at.map(|created_at| {
+            human_formatted_timedelta(
+                DateTime::from(*finished_at) - DateTime::from(created_at),
+                TimeGranularity::Fine,
+            )
+        }),
+        _ => None,
+    };
+    let result_class = finished_result_kind(&node.summary).map(|kind| match kind {
+        FinishedResultKind::Ok => "family-ok",
+        FinishedResultKind::Error | FinishedResultKind::Failed => "family-failed",
+    });
+    html! {
+        <li>
+            <div class={classes!(
+                "family-node",
+                result_class,
+                (execution_id == *current_execution_id).then_some("is-current"),
+            )}>
+                if node.children.is_empty() {
+                    <span class="family-caret"></span>
+                } else {
+                    <button class="family-caret" onclick={toggle}>
+                        { if is_collapsed { "▸" } else { "▾" } }
+                    </button>
+                }
+                <span class="family-id">
+                    { execution_id.render_execution_parts(true, ExecutionLink::Trace) }
+                </span>
+                <span class="family-ffqn">{ ffqn }</span>
+                <span class="family-status">
+                    { status.map(status_to_string).unwrap_or_default() }
+                </span>
+                if let Some(duration) = duration {
+                    <span class="family-duration">{ duration }</span>
+                }
+                if is_collapsed {
+                    <span class="secondary-text">
+                        { format!("{} descendant{}", node.descendants, if node.descendants == 1 { "" } else { "s" }) }
+                    </span>
+                }
+            </div>
+            if !is_collapsed && !node.children.is_empty() {
+                <ul>
+                    { for node.children.iter().map(|child| render_node(child, current_execution_id, collapsed, failing_only)) }
+                </ul>
+            }
+        </li>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grpc::grpc_client::{ExecutionStatus, ResultKind, result_kind};
+
+    fn summary(id: &str, result: Option<result_kind::Value>) -> ExecutionSummary {
+        ExecutionSummary {
+            execution_id: Some(ExecutionId { id: id.to_string() }),
+            current_status: Some(ExecutionStatus {
+                status: Some(match result {
+                    Some(value) => execution_status::Status::Finished(Finished {
+                        result_kind: Some(ResultKind { value: Some(value) }),
+                        ..Default::default()
+                    }),
+                    None => execution_status::Status::Paused(Default::default()),
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn orphans_attach_to_the_closest_listed_ancestor() {
+        let roots = build_family(vec![
+            summary(
+                "E_1.g:1_1",
+                Some(result_kind::Value::Ok(Default::default())),
+            ),
+            // Its parent `E_1.g:2_1` was not listed.
+            summary(
+                "E_1.g:2_1.h:1_1",
+                Some(result_kind::Value::Error(Default::default())),
+            ),
+            summary("E_1", None),
+        ]);
+        assert_eq!(1, roots.len());
+        let root = &roots[0];
+        assert_eq!(2, root.descendants);
+        assert!(root.has_failure);
+        let child_ids: Vec<_> = root
+            .children
+            .iter()
+            .map(|child| {
+                (
+                    child.summary.execution_id.as_ref().unwrap().id.as_str(),
+                    child.has_failure,
+                )
+            })
+            .collect();
+        assert_eq!(
+            vec![("E_1.g:1_1", false), ("E_1.g:2_1.h:1_1", true)],
+            child_ids
+        );
+    }
+}
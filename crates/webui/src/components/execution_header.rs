@@ -247,6 +247,7 @@ pub fn execution_header(
                     { ExecutionLink::ExecutionLog.link(execution_id.clone(), "Execution Log") }
                     { ExecutionLink::Debug.link(execution_id.clone(), "Debugger") }
                     { ExecutionLink::Logs.link(execution_id.clone(), "App Logs") }
+                    { ExecutionLink::Family.link(execution_id.clone(), "Family Tree") }
                 </div>
             </div>
 
@@ -516,6 +517,7 @@ pub enum ExecutionLink {
     ExecutionLog,
     Debug,
     Logs,
+    Family,
 }
 
 impl ExecutionLink {
@@ -541,6 +543,11 @@ impl ExecutionLink {
                     {title}
                 </Link<Route>>
             },
+            ExecutionLink::Family => html! {
+                <Link<Route> to={Route::ExecutionFamily { execution_id }}>
+                    {title}
+                </Link<Route>>
+            },
         }
     }
 }
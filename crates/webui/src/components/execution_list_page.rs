@@ -1,6 +1,8 @@
 use crate::{
     app::{AppState, Route},
+    auth::use_read_only,
     components::{
+        debugger::persist_backtraces::persist_backtraces,
         execution_status::{ExecutionStatus, StatusCacheContext, StatusState},
         ffqn_with_links::FfqnWithLinks,
         notification::{Notification, NotificationContext},
@@ -179,6 +181,37 @@ impl StatusFilter {
     }
 }
 impl ExecutionQuery {
+    fn to_grpc_request(
+        &self,
+        pagination: Option<Pagination>,
+    ) -> grpc_client::ListExecutionsRequest {
+        #[allow(deprecated)]
+        grpc_client::ListExecutionsRequest {
+            function_name_prefix: None,
+            top_level_only: !self.show_derived,
+            pagination,
+            hide_finished: self.hide_finished,
+            function_filter: self.ffqn_prefix.clone().map(grpc_execution_function_filter),
+            execution_id_prefix: self.execution_id_prefix.clone().filter(|s| !s.is_empty()),
+            component_digest: self
+                .component_digest
+                .clone()
+                .filter(|s| !s.is_empty())
+                .map(grpc_client::ContentDigest::from),
+            deployment_id: self
+                .deployment_id
+                .clone()
+                .filter(|s| !s.is_empty())
+                .map(grpc_client::DeploymentId::from),
+            state_filters: self
+                .status
+                .iter()
+                .flat_map(|list| list.0.iter())
+                .map(|status| status.to_grpc() as i32)
+                .collect(),
+        }
+    }
+
     fn flip(mut self, old_direction: Direction) -> ExecutionQuery {
         self.direction = Some(old_direction.flip());
         self.include_cursor = !self.include_cursor;
@@ -662,6 +695,178 @@ fn function_prefix_input(props: &FunctionPrefixInputProps) -> Html {
     }
 }
 
+/// Upper bound of executions processed by one batch run.
+const BATCH_MAX_EXECUTIONS: usize = 1_000;
+const BATCH_PAGE_SIZE: u32 = 100;
+
+#[derive(Clone, Copy, Default, PartialEq)]
+struct BatchProgress {
+    /// `None` while the filtered executions are being listed.
+    total: Option<usize>,
+    done: usize,
+    persisted: u32,
+    failed: usize,
+}
+
+#[derive(Properties, PartialEq)]
+struct BatchPersistBacktracesProps {
+    pub query: ExecutionQuery,
+}
+
+/// Persist missing backtraces of every execution matching the current filters.
+#[component(BatchPersistBacktraces)]
+fn batch_persist_backtraces(
+    BatchPersistBacktracesProps { query }: &BatchPersistBacktracesProps,
+) -> Html {
+    let notifications =
+        use_context::<NotificationContext>().expect("NotificationContext should be provided");
+    let progress = use_state(|| None::<BatchProgress>);
+    // Bumped by every start, cancel and unmount, a run stops once it is no longer the latest.
+    let generation = use_mut_ref(|| 0_u64);
+    let read_only = use_read_only();
+
+    {
+        let generation = generation.clone();
+        use_effect_with((), move |()| move || *generation.borrow_mut() += 1);
+    }
+
+    let on_start = {
+        let query = query.clone();
+        let progress = progress.clone();
+        let generation = generation.clone();
+        Callback::from(move |_: MouseEvent| {
+            let query = query.clone();
+            let progress = progress.clone();
+            let generation = generation.clone();
+            let notifications = notifications.clone();
+            let run = {
+                let mut generation = generation.borrow_mut();
+                *generation += 1;
+                *generation
+            };
+            let is_current = move || *generation.borrow() == run;
+            progress.set(Some(BatchProgress::default()));
+            spawn_local(async move {
+                let mut execution_client = ExecutionRepositoryClient::new(crate::auth::client());
+                let mut execution_ids: Vec<ExecutionId> = Vec::new();
+                loop {
+                    let pagination = Some(Pagination::OlderThan(OlderThan {
+                        cursor: execution_ids.last().map(|execution_id| {
+                            grpc_client::list_executions_request::Cursor {
+                                cursor: Some(cursor::Cursor::ExecutionId(execution_id.clone())),
+                            }
+                        }),
+                        length: BATCH_PAGE_SIZE,
+                        including_cursor: false,
+                    }));
+                    let response = execution_client
+                        .list_executions(query.to_grpc_request(pagination))
+                        .await;
+                    if !is_current() {
+                        return;
+                    }
+                    match response {
+                        Ok(response) => {
+                            let page = response.into_inner().executions;
+                            let is_last = page.len() < BATCH_PAGE_SIZE as usize;
+                            execution_ids.extend(
+                                page.into_iter().filter_map(|summary| summary.execution_id),
+                            );
+                            if is_last || execution_ids.len() >= BATCH_MAX_EXECUTIONS {
+                                break;
+                            }
+                        }
+                        Err(err) => {
+                            error!("Failed to list executions: {err:?}");
+                            notifications.push(Notification::error(format!(
+                                "Failed to list executions: {}",
+                                err.message()
+                            )));
+                            progress.set(None);
+                            return;
+                        }
+                    }
+                }
+                execution_ids.truncate(BATCH_MAX_EXECUTIONS);
+
+                let mut current = BatchProgress {
+                    total: Some(execution_ids.len()),
+                    ..Default::default()
+                };
+                progress.set(Some(current));
+                let mut stopped = false;
+                for execution_id in execution_ids {
+                    match persist_backtraces(execution_id.clone()).await {
+                        Ok(count) => current.persisted += count,
+                        Err(err) => {
+                            error!("Failed to persist backtraces for {execution_id}: {err:?}");
+                            current.failed += 1;
+                        }
+                    }
+                    current.done += 1;
+                    // Cancelled, restarted or unmounted, the progress belongs to another run.
+                    if !is_current() {
+                        stopped = true;
+                        break;
+                    }
+                    progress.set(Some(current));
+                }
+                let mut message = format!(
+                    "Persisted {} backtraces of {} executions",
+                    current.persisted, current.done
+                );
+                if stopped {
+                    message.push_str(" before stopping");
+                }
+                if current.failed > 0 {
+                    notifications.push(Notification::error(format!(
+                        "{message}, {} executions failed",
+                        current.failed
+                    )));
+                } else {
+                    notifications.push(Notification::success(message));
+                }
+                if !stopped {
+                    progress.set(None);
+                }
+            });
+        })
+    };
+    let on_cancel = {
+        let progress = progress.clone();
+        Callback::from(move |_: MouseEvent| {
+            *generation.borrow_mut() += 1;
+            progress.set(None);
+        })
+    };
+
+    html! {
+        <div class="batch-persist-backtraces">
+            if let Some(progress) = *progress {
+                <span class="secondary-text">
+                    if let Some(total) = progress.total {
+                        { format!("Persisting backtraces {}/{total}", progress.done) }
+                        if progress.failed > 0 {
+                            { format!(", {} failed", progress.failed) }
+                        }
+                    } else {
+                        {"Listing executions..."}
+                    }
+                </span>
+                <button onclick={on_cancel}>{"Cancel"}</button>
+            } else {
+                <button
+                    onclick={on_start}
+                    disabled={read_only.is_some()}
+                    title={read_only.clone().unwrap_or(AttrValue::Static("Replay every execution matching the filters and persist any missing call-site backtraces"))}
+                >
+                    {"Populate backtraces"}
+                </button>
+            }
+        </div>
+    }
+}
+
 #[component(ExecutionListPage)]
 pub fn execution_list_page() -> Html {
     let app_state =
@@ -743,30 +948,7 @@ pub fn execution_list_page() -> Html {
                     })),
                 };
 
-                // Send request
-                #[allow(deprecated)]
-                let req = grpc_client::ListExecutionsRequest {
-                    function_name_prefix: None,
-                    top_level_only: !query_params.show_derived,
-                    pagination,
-                    hide_finished: query_params.hide_finished,
-                    function_filter: query_params.ffqn_prefix.map(grpc_execution_function_filter),
-                    execution_id_prefix: query_params.execution_id_prefix.filter(|s| !s.is_empty()),
-                    component_digest: query_params
-                        .component_digest
-                        .filter(|s| !s.is_empty())
-                        .map(grpc_client::ContentDigest::from),
-                    deployment_id: query_params
-                        .deployment_id
-                        .filter(|s| !s.is_empty())
-                        .map(grpc_client::DeploymentId::from),
-                    state_filters: query_params
-                        .status
-                        .iter()
-                        .flat_map(|list| list.0.iter())
-                        .map(|status| status.to_grpc() as i32)
-                        .collect(),
-                };
+                let req = query_params.to_grpc_request(pagination);
                 debug!("Fetching executions with query: {req:?}");
                 let response = execution_client.list_executions(req).await;
 
@@ -1125,6 +1307,7 @@ pub fn execution_list_page() -> Html {
                                 {"Clear"}
                             </Link<Route, ExecutionQuery>>
                         }
+                        <BatchPersistBacktraces query={query.clone()} />
                     </div>
                 </details>
 
@@ -1,5 +1,6 @@
 use crate::{
     app::{AppState, Route},
+    auth::use_read_only,
     components::{
         code::code_block::CodeBlock,
         execution_header::{ExecutionHeader, ExecutionLink},
@@ -55,6 +56,7 @@ pub fn execution_stub_result_page(
         use_context::<NotificationContext>().expect("NotificationContext should be provided");
     // disable the submit button while a request is inflight
     let request_processing_state = use_state(|| false);
+    let read_only = use_read_only();
     let input_ref = use_node_ref();
     let validation_err_state = use_state(|| None::<String>);
     let type_hint_expanded = use_state(|| false);
@@ -224,7 +226,12 @@ pub fn execution_stub_result_page(
                     <pre class="wit-type-inline">{ format_wit_type(&return_type.wit_type_inline) }</pre>
                 }
             </div>
-            <button type="submit" id={SUBMIT_RETVAL} disabled={*request_processing_state || validation_err_state.is_some()}>
+            <button
+                type="submit"
+                id={SUBMIT_RETVAL}
+                disabled={*request_processing_state || validation_err_state.is_some() || read_only.is_some()}
+                title={read_only.clone()}
+            >
                 {"Submit execution result"}
             </button>
         </form>
@@ -235,7 +242,10 @@ pub fn execution_stub_result_page(
     </>}
 }
 
-fn validate_response(return_type: &grpc_client::WitType, value: &str) -> Result<(), String> {
+pub(crate) fn validate_response(
+    return_type: &grpc_client::WitType,
+    value: &str,
+) -> Result<(), String> {
     match serde_json::from_str::<serde_json::Value>(value) {
         Ok(value) => {
             let wit_type_inline = return_type.wit_type_inline.as_str();
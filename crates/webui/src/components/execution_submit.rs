@@ -1,5 +1,6 @@
 use crate::{
     app::Route,
+    auth::use_read_only,
     components::notification::{Notification, NotificationContext},
     grpc::{
         ffqn::FunctionFqn,
@@ -95,6 +96,7 @@ pub fn execution_submit_form(
 
     // disable the submit button while a request is inflight
     let request_processing_state = use_state(|| false);
+    let read_only = use_read_only();
     // Initialize form state with default values
     let form_data_state = use_state(|| FormData {
         param_refs: std::iter::repeat_with(NodeRef::default)
@@ -285,7 +287,11 @@ pub fn execution_submit_form(
                     {" Create paused"}
                 </label>
             </div>
-            <button type="submit" disabled={*request_processing_state || validation_err_state.is_some()}>
+            <button
+                type="submit"
+                disabled={*request_processing_state || validation_err_state.is_some() || read_only.is_some()}
+                title={read_only.clone()}
+            >
                 {"Submit"}
             </button>
         </form>
@@ -1,5 +1,6 @@
 use crate::{
     app::{AppState, Route},
+    auth::use_read_only,
     components::{
         code::code_block::CodeBlock,
         notification::{Notification, NotificationContext},
@@ -166,6 +167,7 @@ pub fn execution_submit_page(ExecutionSubmitPageProps { ffqn }: &ExecutionSubmit
 
     // Form state
     let request_processing_state = use_state(|| false);
+    let read_only = use_read_only();
     let form_data_state = use_state(|| FormData {
         param_refs: std::iter::repeat_with(NodeRef::default)
             .take(function_detail.params.len())
@@ -627,7 +629,8 @@ pub fn execution_submit_page(ExecutionSubmitPageProps { ffqn }: &ExecutionSubmit
 
             <button
                 type="submit"
-                disabled={*request_processing_state || validation_err_state.is_some()}
+                disabled={*request_processing_state || validation_err_state.is_some() || read_only.is_some()}
+                title={read_only.clone()}
             >
                 if *schedule_enabled {
                     {"Schedule"}
@@ -1,4 +1,6 @@
 pub mod advance_modal;
+pub mod annotations;
+pub mod annotations_page;
 pub mod code;
 pub mod component_list_page;
 pub mod component_tree;
@@ -12,6 +14,7 @@ pub mod deployment_list_page;
 pub mod execution_actions;
 pub mod execution_detail;
 pub mod execution_detail_page;
+pub mod execution_family_page;
 pub mod execution_header;
 pub mod execution_list_page;
 pub mod execution_logs_page;
@@ -25,4 +28,6 @@ pub mod function_signature;
 pub mod json_tree;
 pub mod not_found;
 pub mod notification;
+pub mod stub_inbox_page;
 pub mod trace;
+pub mod wit_diff_page;
@@ -0,0 +1,437 @@
+use crate::{
+    app::{AppState, Route},
+    auth::use_read_only,
+    components::{
+        execution_header::ExecutionLink,
+        execution_stub_submit_page::validate_response,
+        ffqn_with_links::FfqnWithLinks,
+        notification::{Notification, NotificationContext},
+    },
+    grpc::{
+        ffqn::FunctionFqn,
+        function_detail::{InterfaceFilter, map_interfaces_to_fn_details},
+        grpc_client::{
+            self, ComponentType, ExecutionId, ExecutionSummary,
+            execution_repository_client::ExecutionRepositoryClient,
+            list_executions_request::{
+                Cursor, ExecutionFunctionFilter, ExecutionStateFilter, OlderThan, Pagination,
+                cursor, execution_function_filter::Scope,
+            },
+        },
+        ifc_fqn::IfcFqn,
+    },
+    util::time::RelativeAgo,
+};
+use chrono::{DateTime, Utc};
+use futures::StreamExt as _;
+use log::{debug, error};
+use std::ops::Deref;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlTextAreaElement;
+use yew::prelude::*;
+use yew_router::prelude::Link;
+
+/// Executions listed per request, all pages of each stub interface are fetched.
+const INBOX_PAGE_SIZE: u32 = 100;
+/// Requests for the `Created` events sent at once.
+const MAX_IN_FLIGHT: usize = 6;
+
+#[derive(Clone, Debug, PartialEq)]
+struct PendingStub {
+    execution_id: ExecutionId,
+    ffqn: FunctionFqn,
+    waiting_since: DateTime<Utc>,
+    /// Workflow that submitted the stub activity.
+    caller: Option<ExecutionId>,
+    /// The error when the `Created` event could not be loaded, the stub can still be answered.
+    params: Result<Vec<(String, serde_json::Value)>, String>,
+}
+
+/// Exported interfaces of `activity_stub` components, without extensions.
+fn stub_interfaces(app_state: &AppState) -> Vec<IfcFqn> {
+    let mut interfaces: Vec<IfcFqn> = app_state
+        .components_by_id
+        .values()
+        .filter(|component| component.as_type() == ComponentType::ActivityStub)
+        .flat_map(|component| {
+            map_interfaces_to_fn_details(&component.exports, InterfaceFilter::WithoutExtensions)
+                .into_keys()
+        })
+        .collect();
+    interfaces.sort();
+    interfaces.dedup();
+    interfaces
+}
+
+async fn list_pending(
+    client: &mut ExecutionRepositoryClient<crate::auth::AuthenticatedClient>,
+    ifc_fqn: &IfcFqn,
+) -> Result<Vec<ExecutionSummary>, tonic::Status> {
+    let mut fetched: Vec<ExecutionSummary> = Vec::new();
+    loop {
+        #[allow(deprecated)]
+        let request = grpc_client::ListExecutionsRequest {
+            function_name_prefix: None,
+            top_level_only: false,
+            hide_finished: true,
+            execution_id_prefix: None,
+            pagination: Some(Pagination::OlderThan(OlderThan {
+                cursor: fetched
+                    .last()
+                    .and_then(|summary| summary.execution_id.clone())
+                    .map(|execution_id| Cursor {
+                        cursor: Some(cursor::Cursor::ExecutionId(execution_id)),
+                    }),
+                length: INBOX_PAGE_SIZE,
+                including_cursor: false,
+            })),
+            component_digest: None,
+            deployment_id: None,
+            function_filter: Some(ExecutionFunctionFilter {
+                scope: Some(Scope::InterfaceName(ifc_fqn.to_string())),
+            }),
+            state_filters: vec![ExecutionStateFilter::Pending as i32],
+        };
+        let page = client
+            .list_executions(request)
+            .await?
+            .into_inner()
+            .executions;
+        let is_last = page.len() < INBOX_PAGE_SIZE as usize;
+        fetched.extend(page);
+        if is_last {
+            return Ok(fetched);
+        }
+    }
+}
+
+/// Params and caller are only available in the `Created` event.
+async fn pending_stub(
+    mut client: ExecutionRepositoryClient<crate::auth::AuthenticatedClient>,
+    app_state: &AppState,
+    summary: ExecutionSummary,
+) -> PendingStub {
+    let execution_id = summary.execution_id.expect("`execution_id` is sent");
+    let ffqn = FunctionFqn::from(summary.function_name.expect("`function_name` is sent"));
+    let waiting_since = DateTime::from(
+        summary
+            .first_scheduled_at
+            .or(summary.created_at)
+            .expect("`created_at` is sent by the server"),
+    );
+    let created =
+        match client
+            .list_execution_events(grpc_client::ListExecutionEventsRequest {
+                execution_id: Some(execution_id.clone()),
+                version_from: 0,
+                length: 1,
+                include_backtrace_id: false,
+            })
+            .await
+        {
+            Ok(response) => response
+                .into_inner()
+                .events
+                .into_iter()
+                .find_map(|event| match event.event {
+                    Some(grpc_client::execution_event::Event::Created(created)) => Some(created),
+                    _ => None,
+                }),
+            Err(err) => {
+                error!("Failed to get the created event of {execution_id}: {err:?}");
+                return PendingStub {
+                    caller: execution_id.parent_id(),
+                    execution_id,
+                    ffqn,
+                    waiting_since,
+                    params: Err(err.message().to_string()),
+                };
+            }
+        };
+    let raw_params: Vec<serde_json::Value> = created
+        .as_ref()
+        .and_then(|created| created.params.as_ref())
+        .and_then(|params| serde_json::from_slice(&params.value).ok())
+        .unwrap_or_default();
+    let params = match app_state.ffqns_to_details.get(&ffqn) {
+        Some((function_detail, _)) if function_detail.params.len() == raw_params.len() => {
+            function_detail
+                .params
+                .iter()
+                .map(|param| param.name.clone())
+                .zip(raw_params)
+                .collect()
+        }
+        _ => raw_params
+            .into_iter()
+            .map(|value| ("(unknown)".to_string(), value))
+            .collect(),
+    };
+    let caller = created
+        .and_then(|created| created.parent_execution_id)
+        .or_else(|| execution_id.parent_id());
+    PendingStub {
+        execution_id,
+        ffqn,
+        waiting_since,
+        caller,
+        params: Ok(params),
+    }
+}
+
+#[component(StubInboxPage)]
+pub fn stub_inbox_page() -> Html {
+    let app_state =
+        use_context::<AppState>().expect("AppState context is set when starting the App");
+    let notifications =
+        use_context::<NotificationContext>().expect("NotificationContext should be provided");
+    let interfaces = stub_interfaces(&app_state);
+    // `None` while loading.
+    let pending_state = use_state(|| None::<Vec<PendingStub>>);
+    let reload_state = use_state(|| 0_u32);
+    // Bumped when the effect is cleaned up, a load only shows its result while it is the latest.
+    let generation = use_mut_ref(|| 0_u64);
+
+    use_effect_with((interfaces.clone(), *reload_state), {
+        let pending_state = pending_state.clone();
+        let app_state = app_state.clone();
+        let notifications = notifications.clone();
+        move |(interfaces, _reload)| {
+            pending_state.set(None);
+            let interfaces = interfaces.clone();
+            let load = *generation.borrow();
+            let is_current = {
+                let generation = generation.clone();
+                move || *generation.borrow() == load
+            };
+            spawn_local(async move {
+                let mut client = ExecutionRepositoryClient::new(crate::auth::client());
+                let mut summaries = Vec::new();
+                for ifc_fqn in &interfaces {
+                    let result = list_pending(&mut client, ifc_fqn).await;
+                    if !is_current() {
+                        return;
+                    }
+                    match result {
+                        Ok(page) => summaries.extend(page),
+                        Err(err) => {
+                            error!("Failed to list pending executions of {ifc_fqn}: {err:?}");
+                            notifications.push(Notification::error(format!(
+                                "Failed to list pending executions of {ifc_fqn}: {}",
+                                err.message()
+                            )));
+                        }
+                    }
+                }
+                let mut pending: Vec<PendingStub> = futures::stream::iter(summaries)
+                    .map(|summary| pending_stub(client.clone(), &app_state, summary))
+                    .buffer_unordered(MAX_IN_FLIGHT)
+                    .collect()
+                    .await;
+                if !is_current() {
+                    return;
+                }
+                pending.sort_by_key(|stub| stub.waiting_since);
+                debug!("Got {} pending stub executions", pending.len());
+                pending_state.set(Some(pending));
+            });
+            move || *generation.borrow_mut() += 1
+        }
+    });
+
+    let on_answered = {
+        let pending_state = pending_state.clone();
+        Callback::from(move |execution_id: ExecutionId| {
+            if let Some(pending) = pending_state.deref() {
+                let mut pending = pending.clone();
+                pending.retain(|stub| stub.execution_id != execution_id);
+                pending_state.set(Some(pending));
+            }
+        })
+    };
+    let on_refresh = {
+        let reload_state = reload_state.clone();
+        Callback::from(move |_: MouseEvent| reload_state.set(*reload_state + 1))
+    };
+
+    html! {
+        <div class="stub-inbox-page">
+            <h3>{"Stub inbox"}</h3>
+            <p class="secondary-text">
+                {"Pending executions of activity stubs, oldest first. Each one waits until its result is submitted."}
+            </p>
+            <div class="stub-inbox-actions">
+                <button onclick={on_refresh} disabled={pending_state.is_none()}>{"Refresh"}</button>
+            </div>
+            if interfaces.is_empty() {
+                <p class="stub-inbox-empty">{"The current deployment has no activity stubs."}</p>
+            } else {
+                {
+                    match pending_state.deref() {
+                        None => html! { <p>{"Loading..."}</p> },
+                        Some(pending) if pending.is_empty() => html! {
+                            <p class="stub-inbox-empty">{"No executions are waiting for a response."}</p>
+                        },
+                        Some(pending) => html! {
+                            <ul class="stub-inbox-list">
+                                { for pending.iter().map(|stub| html! {
+                                    <StubInboxItem
+                                        key={stub.execution_id.id.clone()}
+                                        stub={stub.clone()}
+                                        on_answered={on_answered.clone()}
+                                    />
+                                }) }
+                            </ul>
+                        },
+                    }
+                }
+            }
+        </div>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+struct StubInboxItemProps {
+    stub: PendingStub,
+    on_answered: Callback<ExecutionId>,
+}
+
+#[component(StubInboxItem)]
+fn stub_inbox_item(StubInboxItemProps { stub, on_answered }: &StubInboxItemProps) -> Html {
+    let app_state =
+        use_context::<AppState>().expect("AppState context is set when starting the App");
+    let notifications =
+        use_context::<NotificationContext>().expect("NotificationContext should be provided");
+    let read_only = use_read_only();
+    let input_ref = use_node_ref();
+    let validation_err_state = use_state(|| None::<String>);
+    // disable the submit button while a request is inflight
+    let request_processing_state = use_state(|| false);
+    let return_type = app_state
+        .ffqns_to_details
+        .get(&stub.ffqn)
+        .and_then(|(function_detail, _)| function_detail.return_type.clone());
+
+    let oninput = {
+        let validation_err_state = validation_err_state.clone();
+        let return_type = return_type.clone();
+        let input_ref = input_ref.clone();
+        Callback::from(move |_: InputEvent| {
+            let input = input_ref.cast::<HtmlTextAreaElement>().unwrap().value();
+            let result = match &return_type {
+                Some(return_type) => validate_response(return_type, &input),
+                None => Err("Return type of the function is unknown".to_string()),
+            };
+            validation_err_state.set(result.err());
+        })
+    };
+
+    let on_submit = {
+        let input_ref = input_ref.clone();
+        let validation_err_state = validation_err_state.clone();
+        let request_processing_state = request_processing_state.clone();
+        let execution_id = stub.execution_id.clone();
+        let ffqn = stub.ffqn.clone();
+        let on_answered = on_answered.clone();
+        let return_type = return_type.clone();
+        Callback::from(move |event: SubmitEvent| {
+            event.prevent_default();
+            let input = input_ref.cast::<HtmlTextAreaElement>().unwrap().value();
+            let Some(return_type) = &return_type else {
+                return;
+            };
+            if let Err(err) = validate_response(return_type, &input) {
+                validation_err_state.set(Some(err));
+                return;
+            }
+            let return_value = prost_wkt_types::Any {
+                type_url: format!("urn:obelisk:json:retval:{ffqn}"),
+                value: input.into_bytes(),
+            };
+            request_processing_state.set(true);
+            let execution_id = execution_id.clone();
+            let notifications = notifications.clone();
+            let request_processing_state = request_processing_state.clone();
+            let on_answered = on_answered.clone();
+            spawn_local(async move {
+                let mut client = ExecutionRepositoryClient::new(crate::auth::client());
+                let response = client
+                    .stub(grpc_client::StubRequest {
+                        execution_id: Some(execution_id.clone()),
+                        return_value: Some(return_value),
+                    })
+                    .await;
+                request_processing_state.set(false);
+                match response {
+                    Ok(_) => {
+                        notifications.push(Notification::success(format!(
+                            "Submitted the result of {execution_id}"
+                        )));
+                        on_answered.emit(execution_id);
+                    }
+                    Err(err) => {
+                        error!("Failed to stub {execution_id}: {err:?}");
+                        notifications.push(Notification::error(format!(
+                            "Cannot stub the execution result: {}",
+                            err.message()
+                        )));
+                    }
+                }
+            });
+        })
+    };
+
+    let placeholder = return_type
+        .as_ref()
+        .map(|return_type| return_type.wit_type.clone());
+    html! {
+        <li class="stub-inbox-item">
+            <div class="stub-inbox-summary">
+                <span class="execution-id">
+                    { ExecutionLink::Trace.link(stub.execution_id.clone(), &stub.execution_id.id) }
+                </span>
+                <FfqnWithLinks ffqn={stub.ffqn.clone()} hide_submit={true} />
+                if let Some(caller) = &stub.caller {
+                    <span class="stub-inbox-caller">
+                        {"Called by "}
+                        { ExecutionLink::Trace.link(caller.clone(), &caller.id) }
+                    </span>
+                }
+                <span class="stub-inbox-waiting" title={stub.waiting_since.to_rfc3339()}>
+                    {"Waiting since "}<RelativeAgo target={stub.waiting_since} />
+                </span>
+            </div>
+            {
+                match &stub.params {
+                    Ok(params) if !params.is_empty() => html! {
+                        <ul class="stub-inbox-params">
+                            { for params.iter().map(|(name, value)| html! {
+                                <li><span class="param-name">{name}{": "}</span><code>{value.to_string()}</code></li>
+                            }) }
+                        </ul>
+                    },
+                    Ok(_) => html! {},
+                    Err(err) => html! {
+                        <p class="stub-inbox-params-error" title={err.clone()}>{"Unable to load params"}</p>
+                    },
+                }
+            }
+            <form class="stub-inbox-answer" onsubmit={on_submit}>
+                <textarea rows="1" {placeholder} ref={input_ref} {oninput} />
+                <button
+                    type="submit"
+                    disabled={*request_processing_state || validation_err_state.is_some() || return_type.is_none() || read_only.is_some()}
+                    title={read_only.clone()}
+                >
+                    {"Submit result"}
+                </button>
+                <Link<Route> to={Route::ExecutionStubResult { ffqn: stub.ffqn.clone(), execution_id: stub.execution_id.clone() }}>
+                    {"Open"}
+                </Link<Route>>
+                if let Some(err) = validation_err_state.deref() {
+                    <span class="validation-error">{err}</span>
+                }
+            </form>
+        </li>
+    }
+}
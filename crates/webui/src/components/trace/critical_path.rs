@@ -0,0 +1,337 @@
+//! Critical path of a trace: the chain of child executions, delays and HTTP client traces
+//! that determined the root's end-to-end duration.
+//!
+//! The path is found by walking backwards from the end of a node: the child that finished
+//! last is on the path, then the search continues from that child's start. Children running
+//! in parallel with a chosen one are skipped. Time not covered by any chosen child is
+//! attributed to the node itself.
+
+use super::data::{BusyIntervalStatus, TraceData, TraceDataRoot};
+use super::timeline::format_offset;
+use chrono::{DateTime, Utc};
+use hashbrown::HashSet;
+use yew::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, derive_more::Display)]
+pub enum HopKind {
+    #[display("Execution")]
+    Execution,
+    #[display("Delay")]
+    Delay,
+    #[display("HTTP")]
+    HttpTrace,
+}
+
+/// Which part of a node's own time a segment covers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, derive_more::Display)]
+pub enum SegmentRole {
+    /// The node has no children on the path.
+    #[display("")]
+    Whole,
+    #[display("until the first submit")]
+    BeforeFirstSubmit,
+    /// Idle time of the parent between a join-next and the next submit.
+    #[display("between join-next and next submit")]
+    Gap,
+    #[display("after the last join-next")]
+    AfterLastJoinNext,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Segment {
+    pub node_key: String,
+    pub title: String,
+    pub kind: HopKind,
+    pub role: SegmentRole,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+}
+impl Segment {
+    pub fn duration_micros(&self) -> i64 {
+        self.finished_at.timestamp_micros() - self.started_at.timestamp_micros()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CriticalPath {
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    /// Chronologically ordered, adjacent segments covering the whole root.
+    pub segments: Vec<Segment>,
+}
+impl CriticalPath {
+    pub fn total_micros(&self) -> i64 {
+        self.finished_at.timestamp_micros() - self.started_at.timestamp_micros()
+    }
+
+    pub fn node_keys(&self) -> HashSet<String> {
+        self.segments
+            .iter()
+            .map(|segment| segment.node_key.clone())
+            .collect()
+    }
+}
+
+pub fn critical_path(root: &TraceDataRoot) -> CriticalPath {
+    let last_event_at = root.last_event_at;
+    let root = TraceData::Root(root.clone());
+    let (started_at, finished_at) =
+        span(&root, last_event_at).expect("the root is busy since it was scheduled");
+    CriticalPath {
+        started_at,
+        finished_at,
+        segments: walk(&root, finished_at, last_event_at),
+    }
+}
+
+pub fn hop_kind(data: &TraceData) -> HopKind {
+    match data {
+        TraceData::Root(_) => HopKind::Execution,
+        TraceData::Child(child) => match child.busy.first().map(|interval| &interval.status) {
+            Some(
+                BusyIntervalStatus::HttpTraceFinished(_)
+                | BusyIntervalStatus::HttpTraceNotResponded
+                | BusyIntervalStatus::HttpTraceError,
+            ) => HopKind::HttpTrace,
+            Some(
+                BusyIntervalStatus::DelayInProgress
+                | BusyIntervalStatus::DelayPaused
+                | BusyIntervalStatus::DelayOk
+                | BusyIntervalStatus::DelayCancelled,
+            ) => HopKind::Delay,
+            _ => HopKind::Execution,
+        },
+    }
+}
+
+/// Earliest start and latest end of the node's busy intervals; unfinished ones end at
+/// `last_event_at`.
+pub fn span(
+    data: &TraceData,
+    last_event_at: DateTime<Utc>,
+) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    let started_at = data
+        .busy()
+        .iter()
+        .map(|interval| interval.started_at)
+        .min()?;
+    let finished_at = data
+        .busy()
+        .iter()
+        .map(|interval| interval.finished_at.unwrap_or(last_event_at))
+        .max()?;
+    Some((started_at, finished_at.max(started_at)))
+}
+
+/// Segments of `data` up to `limit`, in chronological order.
+fn walk(data: &TraceData, limit: DateTime<Utc>, last_event_at: DateTime<Utc>) -> Vec<Segment> {
+    let Some((started_at, finished_at)) = span(data, last_event_at) else {
+        return Vec::new();
+    };
+    let own_segment = |role, from, to| Segment {
+        node_key: data.node_key().to_string(),
+        title: data.title().to_string(),
+        kind: hop_kind(data),
+        role,
+        started_at: from,
+        finished_at: to,
+    };
+    let mut candidates: Vec<_> = data
+        .children()
+        .iter()
+        .filter_map(|child| {
+            span(child, last_event_at).map(|(start, end)| (child, start.max(started_at), end))
+        })
+        .collect();
+    // Built backwards, each piece is chronologically ordered.
+    let mut pieces = Vec::new();
+    let mut cursor = finished_at.min(limit);
+    let mut chosen_any = false;
+    while let Some(idx) = candidates
+        .iter()
+        .enumerate()
+        .filter(|(_, (_, start, _))| *start < cursor)
+        .max_by_key(|(_, (_, start, end))| ((*end).min(cursor), std::cmp::Reverse(*start)))
+        .map(|(idx, _)| idx)
+    {
+        let (child, child_start, child_end) = candidates.swap_remove(idx);
+        let child_end = child_end.min(cursor);
+        if child_end < cursor {
+            let role = if chosen_any {
+                SegmentRole::Gap
+            } else {
+                SegmentRole::AfterLastJoinNext
+            };
+            pieces.push(vec![own_segment(role, child_end, cursor)]);
+        }
+        pieces.push(walk(child, child_end, last_event_at));
+        chosen_any = true;
+        cursor = child_start;
+    }
+    if cursor > started_at || !chosen_any {
+        let role = if chosen_any {
+            SegmentRole::BeforeFirstSubmit
+        } else {
+            SegmentRole::Whole
+        };
+        pieces.push(vec![own_segment(role, started_at, cursor)]);
+    }
+    pieces.into_iter().rev().flatten().collect()
+}
+
+#[derive(Properties, PartialEq)]
+pub struct CriticalPathSummaryProps {
+    pub path: CriticalPath,
+}
+
+/// Lists each hop of the critical path with its share of the end-to-end duration.
+#[component(CriticalPathSummary)]
+pub fn critical_path_summary(CriticalPathSummaryProps { path }: &CriticalPathSummaryProps) -> Html {
+    let total = path.total_micros().max(1);
+    let share = |micros: i64| format!("{:.1}%", 100.0 * micros as f64 / total as f64);
+    let gaps: Vec<_> = path
+        .segments
+        .iter()
+        .filter(|segment| segment.role == SegmentRole::Gap)
+        .collect();
+    let gaps_micros: i64 = gaps.iter().map(|segment| segment.duration_micros()).sum();
+    let rows = path.segments.iter().map(|segment| {
+        let offset = segment.started_at.timestamp_micros() - path.started_at.timestamp_micros();
+        html! {
+            <tr class={classes!((segment.role == SegmentRole::Gap).then_some("critical-path-gap"))}>
+                <td>{ format!("+{}", format_offset(offset)) }</td>
+                <td>{ segment.kind.to_string() }</td>
+                <td class="critical-path-title" title={segment.title.clone()}>
+                    { &segment.title }
+                    if segment.role != SegmentRole::Whole {
+                        <span class="secondary-text">{ format!(" ({})", segment.role) }</span>
+                    }
+                </td>
+                <td>{ format_offset(segment.duration_micros()) }</td>
+                <td>{ share(segment.duration_micros()) }</td>
+            </tr>
+        }
+    });
+    html! {
+        <div class="critical-path-summary">
+            <h4>{ format!("Critical path: {}", format_offset(path.total_micros())) }</h4>
+            if !gaps.is_empty() {
+                <p class="secondary-text">
+                    { format!(
+                        "{} idle gap{} between join-next and the next submit: {} ({})",
+                        gaps.len(),
+                        if gaps.len() == 1 { "" } else { "s" },
+                        format_offset(gaps_micros),
+                        share(gaps_micros),
+                    ) }
+                </p>
+            }
+            <table class="config-table">
+                <thead>
+                    <tr>
+                        <th>{"Start"}</th>
+                        <th>{"Kind"}</th>
+                        <th>{"Hop"}</th>
+                        <th>{"Duration"}</th>
+                        <th>{"Share"}</th>
+                    </tr>
+                </thead>
+                <tbody>
+                    { for rows }
+                </tbody>
+            </table>
+        </div>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::trace::data::{BusyInterval, TraceDataChild};
+
+    fn at(secs: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(secs, 0).unwrap()
+    }
+
+    fn child(node_key: &str, status: BusyIntervalStatus, from: i64, to: i64) -> TraceData {
+        TraceData::Child(TraceDataChild {
+            node_key: node_key.to_string(),
+            is_expanded: false,
+            can_expand: false,
+            name: Html::default(),
+            title: node_key.to_string(),
+            busy: vec![BusyInterval {
+                started_at: at(from),
+                finished_at: Some(at(to)),
+                title: None,
+                status,
+            }],
+            children: Vec::new(),
+            load_button: None,
+            link: None,
+        })
+    }
+
+    #[test]
+    fn parallel_children_are_skipped_and_gaps_reported() {
+        let root = TraceDataRoot {
+            node_key: "root".to_string(),
+            is_expanded: true,
+            can_expand: true,
+            name: Html::default(),
+            title: "root".to_string(),
+            scheduled_at: at(0),
+            last_event_at: at(10),
+            busy: vec![BusyInterval {
+                started_at: at(0),
+                finished_at: Some(at(10)),
+                title: None,
+                status: BusyIntervalStatus::ExecutionSinceScheduled,
+            }],
+            children: vec![
+                child("a", BusyIntervalStatus::HttpTraceFinished(200), 1, 4),
+                child("b", BusyIntervalStatus::DelayOk, 2, 3),
+                child("c", BusyIntervalStatus::DelayOk, 5, 9),
+            ],
+            load_button: None,
+            current_status: None,
+            link: None,
+        };
+        let path = critical_path(&root);
+        let segments: Vec<_> = path
+            .segments
+            .iter()
+            .map(|segment| {
+                (
+                    segment.node_key.as_str(),
+                    segment.kind,
+                    segment.role,
+                    segment.started_at.timestamp(),
+                    segment.finished_at.timestamp(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                (
+                    "root",
+                    HopKind::Execution,
+                    SegmentRole::BeforeFirstSubmit,
+                    0,
+                    1
+                ),
+                ("a", HopKind::HttpTrace, SegmentRole::Whole, 1, 4),
+                ("root", HopKind::Execution, SegmentRole::Gap, 4, 5),
+                ("c", HopKind::Delay, SegmentRole::Whole, 5, 9),
+                (
+                    "root",
+                    HopKind::Execution,
+                    SegmentRole::AfterLastJoinNext,
+                    9,
+                    10
+                ),
+            ],
+            segments
+        );
+    }
+}
@@ -148,15 +148,17 @@ pub struct BusyInterval {
     pub status: BusyIntervalStatus,
 }
 impl BusyInterval {
+    /// Offset and width of the interval in percent of the visible `view_start..view_end`.
+    /// Both may fall outside `0..=100` when the interval is partly or fully out of view.
     pub fn as_percentage(
         &self,
-        root_scheduled_at: DateTime<Utc>,
+        view_start: DateTime<Utc>,
+        view_end: DateTime<Utc>,
         root_last_event_at: DateTime<Utc>,
     ) -> (f64, f64) {
-        let total_duration_micros =
-            root_last_event_at.timestamp_micros() - root_scheduled_at.timestamp_micros();
+        let total_duration_micros = view_end.timestamp_micros() - view_start.timestamp_micros();
         let start_percentage = 100.0
-            * (self.started_at.timestamp_micros() - root_scheduled_at.timestamp_micros()) as f64
+            * (self.started_at.timestamp_micros() - view_start.timestamp_micros()) as f64
             / total_duration_micros as f64;
 
         let end_percentage = 100.0
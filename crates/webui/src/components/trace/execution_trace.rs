@@ -1,7 +1,10 @@
 use super::data::TraceData;
 use super::highlight::{highlighted_version_from_hash, set_highlight_hash};
+use super::timeline::{TRACK_CLASS, TimeWindow};
 use crate::grpc::version::VersionType;
 use chrono::{DateTime, Utc};
+use hashbrown::HashSet;
+use std::rc::Rc;
 use wasm_bindgen::JsCast;
 use yew::prelude::*;
 
@@ -67,8 +70,12 @@ pub fn scroll_linked_item(pane_id: &str, item_id: &str) {
 #[derive(Properties, PartialEq)]
 pub struct ExecutionStepProps {
     pub data: TraceData,
-    pub root_scheduled_at: DateTime<Utc>,
     pub root_last_event_at: DateTime<Utc>,
+    /// Visible part of the timeline that the duration bars are laid out against.
+    pub view: TimeWindow,
+    /// Node keys on the critical path, highlighted when non-empty.
+    #[prop_or_default]
+    pub critical_path: Rc<HashSet<String>>,
     pub on_toggle: Callback<String>,
 }
 
@@ -80,7 +87,7 @@ pub fn execution_trace(props: &ExecutionStepProps) -> Html {
         .iter()
         .map(|interval| {
             let (start_percentage, busy_percentage) =
-                interval.as_percentage(props.root_scheduled_at, props.root_last_event_at);
+                interval.as_percentage(props.view.start, props.view.end, props.root_last_event_at);
             html! {
                 <div
                 class={classes!("busy-duration-line", interval.status.get_css_class())}
@@ -99,8 +106,9 @@ pub fn execution_trace(props: &ExecutionStepProps) -> Html {
                         <ExecutionTrace
                         key={format!("{}:{}", child.node_key(), child.title())}
                         data={child.clone()}
-                        root_scheduled_at={props.root_scheduled_at}
                         root_last_event_at={props.root_last_event_at}
+                        view={props.view}
+                        critical_path={props.critical_path.clone()}
                         on_toggle={props.on_toggle.clone()}
                     />
                 })}
@@ -177,7 +185,13 @@ pub fn execution_trace(props: &ExecutionStepProps) -> Html {
 
     html! {
         <div class="execution-trace">
-            <div class="step-row" id={row_id}>
+            <div
+                class={classes!(
+                    "step-row",
+                    props.critical_path.contains(props.data.node_key()).then_some("trace-critical")
+                )}
+                id={row_id}
+            >
                 <span class="step-icon">
                     if has_children {
                         <span class={caret_class} onclick={toggle}>
@@ -197,7 +211,7 @@ pub fn execution_trace(props: &ExecutionStepProps) -> Html {
                         {status}
                     </span>
                 }
-                <div class="relative-duration-container">
+                <div class={classes!("relative-duration-container", TRACK_CLASS)}>
                     if !intervals.is_empty() {
                         <div class="total-duration-line" style="width: 100%" title={tooltip}>
                             {intervals}
@@ -0,0 +1,323 @@
+//! Export of the loaded trace tree for external tracing tools: OTLP-JSON as accepted by
+//! OpenTelemetry collectors, and the Chrome trace event format read by `about:tracing` and
+//! Perfetto.
+//!
+//! Every execution, delay and HTTP client trace becomes a span and its busy intervals become
+//! child spans. Ids are hashes of node keys, which embed the `ExecutionId` hierarchy, so an
+//! export of a child execution lines up with an export of its parent.
+
+use super::critical_path::{HopKind, hop_kind, span};
+use super::data::{BusyIntervalStatus, TraceData, TraceDataRoot};
+use crate::grpc::{execution_id::EXECUTION_ID_INFIX, finished_result_kind::FinishedResultKind};
+use chrono::{DateTime, Utc};
+use serde_json::{Value, json};
+
+struct ExportSpan {
+    span_id: u64,
+    parent_span_id: Option<u64>,
+    name: String,
+    /// Kind of the node the span belongs to.
+    kind: HopKind,
+    /// Set on the execution, delay and HTTP spans, `None` on their busy intervals.
+    node_key: Option<String>,
+    status: Option<String>,
+    is_error: bool,
+    started_at: DateTime<Utc>,
+    finished_at: DateTime<Utc>,
+    /// Chrome trace thread: one per node, shared with its busy intervals.
+    thread: u32,
+}
+
+/// FNV-1a, stable across builds so that repeated exports keep their ids.
+fn hash(value: &str) -> u64 {
+    value.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+fn is_error(status: &BusyIntervalStatus) -> bool {
+    match status {
+        BusyIntervalStatus::HttpTraceFinished(status_code) => *status_code >= 400,
+        BusyIntervalStatus::HttpTraceNotResponded
+        | BusyIntervalStatus::HttpTraceError
+        | BusyIntervalStatus::ExecutionTimeoutTemporary
+        | BusyIntervalStatus::ExecutionErrorTemporary
+        | BusyIntervalStatus::ExecutionFinished(
+            FinishedResultKind::Error | FinishedResultKind::Failed,
+        ) => true,
+        BusyIntervalStatus::ExecutionLocked
+        | BusyIntervalStatus::DelayInProgress
+        | BusyIntervalStatus::DelayPaused
+        | BusyIntervalStatus::DelayOk
+        | BusyIntervalStatus::DelayCancelled
+        | BusyIntervalStatus::ExecutionFinished(FinishedResultKind::Ok)
+        | BusyIntervalStatus::ExecutionUnfinishedWithoutPendingState
+        | BusyIntervalStatus::ExecutionSinceScheduled => false,
+    }
+}
+
+fn collect(
+    data: &TraceData,
+    parent_span_id: Option<u64>,
+    last_event_at: DateTime<Utc>,
+    spans: &mut Vec<ExportSpan>,
+) {
+    let Some((started_at, finished_at)) = span(data, last_event_at) else {
+        return;
+    };
+    let span_id = hash(data.node_key());
+    let kind = hop_kind(data);
+    let thread = u32::try_from(spans.len()).unwrap_or(u32::MAX);
+    let status = data.busy().last().map(|interval| &interval.status);
+    spans.push(ExportSpan {
+        span_id,
+        parent_span_id,
+        name: data.title().to_string(),
+        kind,
+        node_key: Some(data.node_key().to_string()),
+        status: status.map(ToString::to_string),
+        is_error: status.is_some_and(is_error),
+        started_at,
+        finished_at,
+        thread,
+    });
+    for (idx, interval) in data.busy().iter().enumerate() {
+        // Spans the whole node, which is already exported.
+        if interval.status == BusyIntervalStatus::ExecutionSinceScheduled {
+            continue;
+        }
+        spans.push(ExportSpan {
+            span_id: hash(&format!("{}:busy:{idx}", data.node_key())),
+            parent_span_id: Some(span_id),
+            name: interval
+                .title
+                .clone()
+                .unwrap_or_else(|| interval.status.to_string()),
+            kind,
+            node_key: None,
+            status: Some(interval.status.to_string()),
+            is_error: is_error(&interval.status),
+            started_at: interval.started_at,
+            finished_at: interval.finished_at.unwrap_or(last_event_at),
+            thread,
+        });
+    }
+    for child in data.children() {
+        collect(child, Some(span_id), last_event_at, spans);
+    }
+}
+
+fn collect_spans(root: &TraceDataRoot) -> Vec<ExportSpan> {
+    // A child execution opened on its own keeps pointing at its parent execution.
+    let parent_span_id = root
+        .node_key
+        .rsplit_once(EXECUTION_ID_INFIX)
+        .map(|(parent, _)| hash(parent));
+    let mut spans = Vec::new();
+    collect(
+        &TraceData::Root(root.clone()),
+        parent_span_id,
+        root.last_event_at,
+        &mut spans,
+    );
+    spans
+}
+
+/// 128-bit trace id shared by every execution of the same top-level execution.
+fn trace_id(root: &TraceDataRoot) -> String {
+    let top_level = root
+        .node_key
+        .split_once(EXECUTION_ID_INFIX)
+        .map_or(root.node_key.as_str(), |(top_level, _)| top_level);
+    format!(
+        "{:016x}{:016x}",
+        hash(top_level),
+        hash(&format!("{top_level}:trace"))
+    )
+}
+
+fn kind_attribute(kind: HopKind) -> &'static str {
+    match kind {
+        HopKind::Execution => "execution",
+        HopKind::Delay => "delay",
+        HopKind::HttpTrace => "http",
+    }
+}
+
+fn unix_nanos(instant: DateTime<Utc>) -> String {
+    (i128::from(instant.timestamp_micros()) * 1_000).to_string()
+}
+
+/// OTLP-JSON `ExportTraceServiceRequest`.
+pub fn to_otlp_json(root: &TraceDataRoot) -> Value {
+    let trace_id = trace_id(root);
+    let spans: Vec<_> = collect_spans(root)
+        .into_iter()
+        .map(|span| {
+            let mut attributes = vec![json!({
+                "key": "obelisk.kind",
+                "value": { "stringValue": kind_attribute(span.kind) },
+            })];
+            if let Some(status) = &span.status {
+                attributes.push(json!({
+                    "key": "obelisk.status",
+                    "value": { "stringValue": status },
+                }));
+            }
+            if let Some(node_key) = span.node_key.filter(|_| span.kind == HopKind::Execution) {
+                attributes.push(json!({
+                    "key": "obelisk.execution_id",
+                    "value": { "stringValue": node_key },
+                }));
+            }
+            let mut value = json!({
+                "traceId": trace_id,
+                "spanId": format!("{:016x}", span.span_id),
+                "name": span.name,
+                // SPAN_KIND_INTERNAL, or SPAN_KIND_CLIENT for outgoing HTTP requests.
+                "kind": if span.kind == HopKind::HttpTrace { 3 } else { 1 },
+                "startTimeUnixNano": unix_nanos(span.started_at),
+                "endTimeUnixNano": unix_nanos(span.finished_at),
+                "attributes": attributes,
+                // STATUS_CODE_ERROR or STATUS_CODE_UNSET.
+                "status": { "code": if span.is_error { 2 } else { 0 } },
+            });
+            if let Some(parent_span_id) = span.parent_span_id {
+                value["parentSpanId"] = json!(format!("{parent_span_id:016x}"));
+            }
+            value
+        })
+        .collect();
+    json!({
+        "resourceSpans": [{
+            "resource": {
+                "attributes": [{
+                    "key": "service.name",
+                    "value": { "stringValue": "obelisk" },
+                }],
+            },
+            "scopeSpans": [{
+                "scope": { "name": "obelisk-webui" },
+                "spans": spans,
+            }],
+        }],
+    })
+}
+
+/// Chrome trace event format, timestamps in microseconds since the root was scheduled.
+pub fn to_chrome_trace(root: &TraceDataRoot) -> Value {
+    let origin = root.scheduled_at.timestamp_micros();
+    let spans = collect_spans(root);
+    let thread_names = spans
+        .iter()
+        .filter(|span| span.node_key.is_some())
+        .map(|span| {
+            json!({
+                "name": "thread_name",
+                "ph": "M",
+                "pid": 1,
+                "tid": span.thread,
+                "args": { "name": span.name },
+            })
+        });
+    let events = spans.iter().map(|span| {
+        json!({
+            "name": span.name,
+            "cat": kind_attribute(span.kind),
+            "ph": "X",
+            "pid": 1,
+            "tid": span.thread,
+            "ts": span.started_at.timestamp_micros() - origin,
+            "dur": span.finished_at.timestamp_micros() - span.started_at.timestamp_micros(),
+            "args": {
+                "status": span.status,
+                "error": span.is_error,
+            },
+        })
+    });
+    let process_name = json!({
+        "name": "process_name",
+        "ph": "M",
+        "pid": 1,
+        "args": { "name": root.node_key },
+    });
+    json!({
+        "traceEvents": std::iter::once(process_name)
+            .chain(thread_names)
+            .chain(events)
+            .collect::<Vec<_>>(),
+        "displayTimeUnit": "ms",
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::trace::data::{BusyInterval, TraceDataChild};
+    use yew::Html;
+
+    fn at(secs: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(secs, 0).unwrap()
+    }
+
+    #[test]
+    fn otlp_spans_follow_the_execution_hierarchy() {
+        let root = TraceDataRoot {
+            node_key: "E_01.o:1_1".to_string(),
+            is_expanded: true,
+            can_expand: true,
+            name: Html::default(),
+            title: "child".to_string(),
+            scheduled_at: at(0),
+            last_event_at: at(10),
+            busy: vec![
+                BusyInterval {
+                    started_at: at(0),
+                    finished_at: Some(at(10)),
+                    title: None,
+                    status: BusyIntervalStatus::ExecutionSinceScheduled,
+                },
+                BusyInterval {
+                    started_at: at(1),
+                    finished_at: Some(at(10)),
+                    title: None,
+                    status: BusyIntervalStatus::ExecutionFinished(FinishedResultKind::Failed),
+                },
+            ],
+            children: vec![TraceData::Child(TraceDataChild {
+                node_key: "E_01.o:1_1:event:3:http:0".to_string(),
+                is_expanded: false,
+                can_expand: false,
+                name: Html::default(),
+                title: "GET /".to_string(),
+                busy: vec![BusyInterval {
+                    started_at: at(2),
+                    finished_at: Some(at(3)),
+                    title: None,
+                    status: BusyIntervalStatus::HttpTraceFinished(200),
+                }],
+                children: Vec::new(),
+                load_button: None,
+                link: None,
+            })],
+            load_button: None,
+            current_status: None,
+            link: None,
+        };
+        let otlp = to_otlp_json(&root);
+        let spans = otlp["resourceSpans"][0]["scopeSpans"][0]["spans"]
+            .as_array()
+            .unwrap();
+        let span_id = |node_key: &str| json!(format!("{:016x}", hash(node_key)));
+        // The execution, its failed busy interval, the HTTP trace and its busy interval.
+        assert_eq!(4, spans.len());
+        assert_eq!(span_id("E_01"), spans[0]["parentSpanId"]);
+        assert_eq!(json!(2), spans[0]["status"]["code"]);
+        assert_eq!(spans[0]["spanId"], spans[1]["parentSpanId"]);
+        assert_eq!(spans[0]["spanId"], spans[2]["parentSpanId"]);
+        assert_eq!(json!(3), spans[2]["kind"]);
+        assert_eq!(json!(0), spans[2]["status"]["code"]);
+        assert_eq!(json!("10000000000"), spans[1]["endTimeUnixNano"]);
+        assert_eq!(trace_id(&root), spans[3]["traceId"]);
+    }
+}
@@ -1,4 +1,7 @@
+pub mod critical_path;
 pub mod data;
 pub mod execution_trace;
+pub mod export;
 pub mod highlight;
+pub mod timeline;
 pub mod trace_view;
@@ -0,0 +1,496 @@
+//! Zoomable, pannable time axis shared by every duration bar of the trace tree.
+//!
+//! All bars are laid out against the same [`TimeWindow`]. Gestures are delegated from the
+//! tree pane: the wheel zooms around the cursor and dragging a bar pans, both on any
+//! element marked with [`TRACK_CLASS`]. Dragging the ruler instead draws a brush, a
+//! selected window that the detail pane uses to filter its events.
+
+use chrono::{DateTime, Utc};
+use gloo::events::{EventListener, EventListenerOptions};
+use std::rc::Rc;
+use wasm_bindgen::JsCast;
+use yew::prelude::*;
+
+/// Marks elements whose horizontal extent maps onto the current [`TimeWindow`].
+pub const TRACK_CLASS: &str = "trace-timeline-track";
+/// Marks the ruler, where dragging draws a brush instead of panning.
+const RULER_CLASS: &str = "trace-timeline-ruler";
+
+const MIN_WINDOW_MICROS: i64 = 1_000;
+const ZOOM_STEP: f64 = 1.25;
+/// Drags shorter than this are clicks: on the ruler they clear the brush.
+const MIN_DRAG_PX: i32 = 3;
+const MAX_TICKS: i64 = 4;
+
+const MILLI: i64 = 1_000;
+const SECOND: i64 = 1_000 * MILLI;
+const MINUTE: i64 = 60 * SECOND;
+const HOUR: i64 = 60 * MINUTE;
+const DAY: i64 = 24 * HOUR;
+const TICK_STEPS: &[i64] = &[
+    MILLI,
+    2 * MILLI,
+    5 * MILLI,
+    10 * MILLI,
+    20 * MILLI,
+    50 * MILLI,
+    100 * MILLI,
+    200 * MILLI,
+    500 * MILLI,
+    SECOND,
+    2 * SECOND,
+    5 * SECOND,
+    10 * SECOND,
+    15 * SECOND,
+    30 * SECOND,
+    MINUTE,
+    2 * MINUTE,
+    5 * MINUTE,
+    10 * MINUTE,
+    15 * MINUTE,
+    30 * MINUTE,
+    HOUR,
+    2 * HOUR,
+    3 * HOUR,
+    6 * HOUR,
+    12 * HOUR,
+    DAY,
+    2 * DAY,
+    7 * DAY,
+    30 * DAY,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimeWindow {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+impl TimeWindow {
+    pub fn new(start: DateTime<Utc>, end: DateTime<Utc>) -> Self {
+        Self {
+            start,
+            end: end.max(start),
+        }
+    }
+
+    fn from_micros(start: i64, end: i64) -> Self {
+        let at = |micros| DateTime::from_timestamp_micros(micros).unwrap_or_default();
+        Self::new(at(start), at(end))
+    }
+
+    pub fn duration_micros(&self) -> i64 {
+        self.end.timestamp_micros() - self.start.timestamp_micros()
+    }
+
+    /// Instant at `fraction` (0 = start, 1 = end) of the window.
+    pub fn at(&self, fraction: f64) -> DateTime<Utc> {
+        let offset = (fraction * self.duration_micros() as f64).round() as i64;
+        DateTime::from_timestamp_micros(self.start.timestamp_micros() + offset)
+            .unwrap_or(self.start)
+    }
+
+    /// Position of `instant` within the window in percent, unclamped.
+    pub fn percentage_of(&self, instant: DateTime<Utc>) -> f64 {
+        let duration = self.duration_micros().max(1);
+        100.0 * (instant.timestamp_micros() - self.start.timestamp_micros()) as f64
+            / duration as f64
+    }
+
+    pub fn contains(&self, instant: DateTime<Utc>) -> bool {
+        self.start <= instant && instant <= self.end
+    }
+
+    /// Scale the window by `factor` (below 1 zooms in) keeping the instant under `anchor` in place.
+    pub fn zoom(&self, anchor: f64, factor: f64, bounds: TimeWindow) -> TimeWindow {
+        let duration = self.duration_micros() as f64;
+        let new_duration = (duration * factor)
+            .min(bounds.duration_micros() as f64)
+            .max(MIN_WINDOW_MICROS as f64);
+        let anchor_micros = self.start.timestamp_micros() as f64 + anchor * duration;
+        let start = (anchor_micros - anchor * new_duration).round() as i64;
+        Self::from_micros(start, start + new_duration.round() as i64).clamp_to(bounds)
+    }
+
+    /// Shift the window by `delta` of its own width.
+    pub fn pan(&self, delta: f64, bounds: TimeWindow) -> TimeWindow {
+        let shift = (delta * self.duration_micros() as f64).round() as i64;
+        Self::from_micros(
+            self.start.timestamp_micros() + shift,
+            self.end.timestamp_micros() + shift,
+        )
+        .clamp_to(bounds)
+    }
+
+    /// Move the window inside `bounds`, shrinking it only when it does not fit.
+    pub fn clamp_to(&self, bounds: TimeWindow) -> TimeWindow {
+        let duration = self.duration_micros().min(bounds.duration_micros());
+        let start = self.start.timestamp_micros().clamp(
+            bounds.start.timestamp_micros(),
+            bounds.end.timestamp_micros() - duration,
+        );
+        Self::from_micros(start, start + duration)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tick {
+    pub percentage: f64,
+    pub label: String,
+}
+
+/// Evenly spaced ticks at round offsets from `origin`, labeled relative to it.
+pub fn ticks(window: TimeWindow, origin: DateTime<Utc>) -> Vec<Tick> {
+    let duration = window.duration_micros();
+    if duration <= 0 {
+        return Vec::new();
+    }
+    let step = TICK_STEPS
+        .iter()
+        .copied()
+        .find(|step| duration / step <= MAX_TICKS)
+        .unwrap_or_else(|| {
+            let largest = TICK_STEPS[TICK_STEPS.len() - 1];
+            largest * (duration / largest / MAX_TICKS + 1)
+        });
+    let origin = origin.timestamp_micros();
+    let start_offset = window.start.timestamp_micros() - origin;
+    let end_offset = window.end.timestamp_micros() - origin;
+    let mut offset = start_offset.div_euclid(step) * step;
+    if offset < start_offset {
+        offset += step;
+    }
+    let mut ticks = Vec::new();
+    while offset <= end_offset {
+        ticks.push(Tick {
+            percentage: 100.0 * (offset - start_offset) as f64 / duration as f64,
+            label: format_offset(offset),
+        });
+        offset += step;
+    }
+    ticks
+}
+
+/// Compact offset label such as `1h 30m` or `2s 500ms`.
+pub fn format_offset(micros: i64) -> String {
+    let sign = if micros < 0 { "-" } else { "" };
+    let mut rest = micros.unsigned_abs() / MILLI.unsigned_abs();
+    if rest == 0 {
+        return "0".to_string();
+    }
+    let mut parts = Vec::new();
+    for (unit, suffix) in [
+        (DAY, "d"),
+        (HOUR, "h"),
+        (MINUTE, "m"),
+        (SECOND, "s"),
+        (MILLI, "ms"),
+    ] {
+        let unit = (unit / MILLI).unsigned_abs();
+        let count = rest / unit;
+        rest %= unit;
+        if count > 0 {
+            parts.push(format!("{count}{suffix}"));
+        }
+    }
+    format!("{sign}{}", parts.join(" "))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DragKind {
+    Pan,
+    Brush,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Drag {
+    kind: DragKind,
+    bounds: TimeWindow,
+    view_at_start: TimeWindow,
+    origin_x: i32,
+    /// Fraction of the track under the cursor when the drag started.
+    anchor: f64,
+    width_px: f64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TimelineState {
+    /// Zoomed window, `None` shows the whole trace.
+    view: Option<TimeWindow>,
+    pub brush: Option<TimeWindow>,
+    drag: Option<Drag>,
+}
+impl TimelineState {
+    pub fn view(&self, bounds: TimeWindow) -> TimeWindow {
+        self.view.map_or(bounds, |view| view.clamp_to(bounds))
+    }
+
+    pub fn is_zoomed(&self) -> bool {
+        self.view.is_some()
+    }
+}
+
+pub enum TimelineAction {
+    Zoom {
+        bounds: TimeWindow,
+        anchor: f64,
+        zoom_in: bool,
+    },
+    StartDrag {
+        bounds: TimeWindow,
+        brush: bool,
+        client_x: i32,
+        anchor: f64,
+        width_px: f64,
+    },
+    DragTo(i32),
+    EndDrag(i32),
+    ResetZoom,
+    ClearBrush,
+}
+
+impl Reducible for TimelineState {
+    type Action = TimelineAction;
+
+    fn reduce(self: Rc<Self>, action: Self::Action) -> Rc<Self> {
+        let mut this = self.as_ref().clone();
+        match action {
+            TimelineAction::Zoom {
+                bounds,
+                anchor,
+                zoom_in,
+            } => {
+                let factor = if zoom_in { 1.0 / ZOOM_STEP } else { ZOOM_STEP };
+                let view = this.view(bounds).zoom(anchor, factor, bounds);
+                this.view = (view != bounds).then_some(view);
+            }
+            TimelineAction::StartDrag {
+                bounds,
+                brush,
+                client_x,
+                anchor,
+                width_px,
+            } => {
+                this.drag = Some(Drag {
+                    kind: if brush {
+                        DragKind::Brush
+                    } else {
+                        DragKind::Pan
+                    },
+                    bounds,
+                    view_at_start: this.view(bounds),
+                    origin_x: client_x,
+                    anchor,
+                    width_px,
+                });
+            }
+            TimelineAction::DragTo(client_x) => {
+                let Some(drag) = this.drag else {
+                    return self;
+                };
+                let delta = f64::from(client_x - drag.origin_x) / drag.width_px;
+                match drag.kind {
+                    DragKind::Pan => {
+                        let view = drag.view_at_start.pan(-delta, drag.bounds);
+                        this.view = (view != drag.bounds).then_some(view);
+                    }
+                    DragKind::Brush => {
+                        let other = (drag.anchor + delta).clamp(0.0, 1.0);
+                        this.brush = Some(TimeWindow::new(
+                            drag.view_at_start.at(drag.anchor.min(other)),
+                            drag.view_at_start.at(drag.anchor.max(other)),
+                        ));
+                    }
+                }
+            }
+            TimelineAction::EndDrag(client_x) => {
+                let Some(drag) = this.drag.take() else {
+                    return self;
+                };
+                if drag.kind == DragKind::Brush && (client_x - drag.origin_x).abs() < MIN_DRAG_PX {
+                    this.brush = None;
+                }
+            }
+            TimelineAction::ResetZoom => this.view = None,
+            TimelineAction::ClearBrush => this.brush = None,
+        }
+        Rc::new(this)
+    }
+}
+
+/// The track under the event's target, with the cursor's position along it (0..=1).
+fn track_position(event: &web_sys::MouseEvent) -> Option<(web_sys::Element, f64, f64)> {
+    let target = event.target()?.dyn_into::<web_sys::Element>().ok()?;
+    let track = target.closest(&format!(".{TRACK_CLASS}")).ok()??;
+    let rect = track.get_bounding_client_rect();
+    if rect.width() <= 0.0 {
+        return None;
+    }
+    let fraction = ((f64::from(event.client_x()) - rect.left()) / rect.width()).clamp(0.0, 1.0);
+    Some((track, fraction, rect.width()))
+}
+
+/// Install the wheel and drag listeners on `pane`, returning them so the caller can drop
+/// them once `bounds` change.
+pub fn listen_to_gestures(
+    pane: &web_sys::Element,
+    bounds: TimeWindow,
+    timeline: UseReducerHandle<TimelineState>,
+) -> Vec<EventListener> {
+    let Some(window) = web_sys::window() else {
+        return Vec::new();
+    };
+    // Non-passive, so that zooming does not scroll the pane at the same time.
+    let wheel = EventListener::new_with_options(
+        pane,
+        "wheel",
+        EventListenerOptions::enable_prevent_default(),
+        {
+            let timeline = timeline.clone();
+            move |event| {
+                let Some(event) = event.dyn_ref::<web_sys::WheelEvent>() else {
+                    return;
+                };
+                let Some((_, anchor, _)) = track_position(event) else {
+                    return;
+                };
+                event.prevent_default();
+                timeline.dispatch(TimelineAction::Zoom {
+                    bounds,
+                    anchor,
+                    zoom_in: event.delta_y() < 0.0,
+                });
+            }
+        },
+    );
+    let mousedown = EventListener::new_with_options(
+        pane,
+        "mousedown",
+        EventListenerOptions::enable_prevent_default(),
+        {
+            let timeline = timeline.clone();
+            move |event| {
+                let Some(event) = event.dyn_ref::<web_sys::MouseEvent>() else {
+                    return;
+                };
+                if event.button() != 0 {
+                    return;
+                }
+                let Some((track, anchor, width_px)) = track_position(event) else {
+                    return;
+                };
+                // Keep the drag from selecting the surrounding text.
+                event.prevent_default();
+                timeline.dispatch(TimelineAction::StartDrag {
+                    bounds,
+                    brush: track.class_list().contains(RULER_CLASS),
+                    client_x: event.client_x(),
+                    anchor,
+                    width_px,
+                });
+            }
+        },
+    );
+    let mousemove = EventListener::new(&window, "mousemove", {
+        let timeline = timeline.clone();
+        move |event| {
+            // Without an ongoing drag the reducer keeps its state, so nothing re-renders.
+            if let Some(event) = event.dyn_ref::<web_sys::MouseEvent>() {
+                timeline.dispatch(TimelineAction::DragTo(event.client_x()));
+            }
+        }
+    });
+    let mouseup = EventListener::new(&window, "mouseup", move |event| {
+        if let Some(event) = event.dyn_ref::<web_sys::MouseEvent>() {
+            timeline.dispatch(TimelineAction::EndDrag(event.client_x()));
+        }
+    });
+    vec![wheel, mousedown, mousemove, mouseup]
+}
+
+#[derive(Properties, PartialEq)]
+pub struct TimeAxisProps {
+    pub view: TimeWindow,
+    /// Start of the trace, the origin of the tick labels.
+    pub origin: DateTime<Utc>,
+    pub brush: Option<TimeWindow>,
+}
+
+/// Ruler aligned with the duration bars of the rows below it.
+#[component(TimeAxis)]
+pub fn time_axis(
+    TimeAxisProps {
+        view,
+        origin,
+        brush,
+    }: &TimeAxisProps,
+) -> Html {
+    let ticks = ticks(*view, *origin).into_iter().map(|tick| {
+        html! {
+            <span class="trace-timeline-tick" style={format!("left: {}%;", tick.percentage)}>
+                <span class="trace-timeline-tick-label">{ tick.label }</span>
+            </span>
+        }
+    });
+    let brush = brush.map(|brush| {
+        let start = view.percentage_of(brush.start).clamp(0.0, 100.0);
+        let end = view.percentage_of(brush.end).clamp(0.0, 100.0);
+        html! {
+            <div
+                class="trace-timeline-brush"
+                style={format!("left: {start}%; width: {}%;", end - start)}
+            />
+        }
+    });
+    html! {
+        <div class="step-row trace-timeline-axis">
+            <span class="step-name"></span>
+            <div
+                class={classes!("relative-duration-container", TRACK_CLASS, RULER_CLASS)}
+                title="Drag to select a time window, scroll to zoom. Drag the bars below to pan."
+            >
+                { for ticks }
+                { brush }
+            </div>
+            <span class="trace-link-button trace-link-button-placeholder" aria-hidden="true">
+                {"\u{21C4}"}
+            </span>
+        </div>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window(start_millis: i64, end_millis: i64) -> TimeWindow {
+        TimeWindow::from_micros(start_millis * MILLI, end_millis * MILLI)
+    }
+
+    #[test]
+    fn zoom_and_pan_stay_within_bounds() {
+        let bounds = window(0, 10_000);
+        let zoomed = bounds.zoom(0.0, 0.5, bounds);
+        assert_eq!(window(0, 5_000), zoomed);
+        assert_eq!(window(5_000, 10_000), zoomed.pan(2.0, bounds));
+        assert_eq!(bounds, zoomed.zoom(0.5, 4.0, bounds));
+        assert_eq!(
+            MIN_WINDOW_MICROS,
+            bounds.zoom(0.5, 1e-9, bounds).duration_micros()
+        );
+    }
+
+    #[test]
+    fn ticks_are_round_offsets_from_origin() {
+        let labels = |window| {
+            ticks(window, DateTime::default())
+                .into_iter()
+                .map(|tick| tick.label)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(vec!["0", "2s", "4s", "6s"], labels(window(0, 7_000)));
+        assert_eq!(
+            vec!["1h 30m", "2h", "2h 30m"],
+            labels(window(80 * 60_000, 170 * 60_000))
+        );
+    }
+}
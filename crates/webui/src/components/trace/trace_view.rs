@@ -2,17 +2,25 @@ use super::data::{BusyIntervalStatus, TraceData};
 use crate::{
     app::Route,
     components::{
+        annotations::AnnotationBox,
+        execution_detail::http_trace::{HttpTracePanel, collect_http_traces},
         execution_detail::utils::{compute_join_next_to_response, event_to_detail},
         execution_header::{ExecutionHeader, ExecutionLink},
         ffqn_with_links::FfqnWithLinks,
         notification::{Notification, NotificationContext},
         trace::{
+            critical_path::{CriticalPathSummary, critical_path},
             data::{BusyInterval, TraceDataChild, TraceDataRoot, TraceLink},
             execution_trace::ExecutionTrace,
+            export::{to_chrome_trace, to_otlp_json},
             highlight::{
                 BacktraceJump, apply_highlight_from_hash, highlighted_version_from_hash,
                 set_highlight_hash,
             },
+            timeline::{
+                TimeAxis, TimeWindow, TimelineAction, TimelineState, format_offset,
+                listen_to_gestures,
+            },
         },
     },
     grpc::{
@@ -30,6 +38,7 @@ use crate::{
         version::VersionType,
     },
     tree::Icon,
+    util::download::download,
 };
 use assert_matches::assert_matches;
 use chrono::{DateTime, Utc};
@@ -49,6 +58,9 @@ pub struct TraceViewProps {
 
 pub const PAGE: u32 = 500;
 pub const SLEEP_MILLIS: u32 = 2500;
+/// Upper bound of concurrent `ListExecutionEventsAndResponsesRequest`s.
+const MAX_IN_FLIGHT: usize = 6;
+const DEFAULT_EXPAND_DEPTH: u32 = 3;
 
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
 struct Cursors {
@@ -59,8 +71,40 @@ struct Cursors {
 #[derive(Clone, Copy, PartialEq)]
 enum ExecutionFetchState {
     Requested(Cursors),
+    // Request in flight.
     Pending,
+    // Page saved, the next one will be requested after a while.
+    Polling,
     Finished,
+    // Request failed, retried from the cursors when asked to.
+    Failed(Cursors),
+}
+
+/// Display toggles applied while building the tree. The critical path and the exports use
+/// [`TraceFilter::NONE`], so that they do not depend on what is shown.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct TraceFilter {
+    hide_finished: bool,
+    show_delays: bool,
+}
+impl TraceFilter {
+    const NONE: TraceFilter = TraceFilter {
+        hide_finished: false,
+        show_delays: true,
+    };
+
+    fn of(state: &TraceViewState) -> Self {
+        TraceFilter {
+            hide_finished: state.hide_finished,
+            show_delays: state.show_delays,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum DetailTab {
+    Events,
+    HttpTraces,
 }
 
 enum TraceviewStateAction {
@@ -72,6 +116,11 @@ enum TraceviewStateAction {
     ToggleExpanded(String),
     // About to fetch the data.
     SetPending(ExecutionId),
+    SetFailed {
+        execution_id: ExecutionId,
+        cursors: Cursors,
+    },
+    RetryFailed,
     // Got data
     SavePage {
         execution_id: ExecutionId,
@@ -86,6 +135,8 @@ enum TraceviewStateAction {
     },
     SetHideFinished(bool),
     SetShowDelays(bool),
+    SetShowCriticalPath(bool),
+    ExpandToDepth(u32),
 }
 
 #[derive(Clone, PartialEq)]
@@ -97,6 +148,9 @@ struct TraceViewState {
     expanded_nodes: HashMap<String, bool>,
     hide_finished: bool,
     show_delays: bool,
+    show_critical_path: bool,
+    // Child executions up to this depth are loaded and expanded unless collapsed explicitly.
+    expand_depth: Option<u32>,
 }
 impl Default for TraceViewState {
     fn default() -> Self {
@@ -108,6 +162,8 @@ impl Default for TraceViewState {
             expanded_nodes: HashMap::default(),
             hide_finished: false,
             show_delays: true,
+            show_critical_path: false,
+            expand_depth: None,
         }
     }
 }
@@ -142,6 +198,24 @@ impl Reducible for TraceViewState {
                     .insert(execution_id, ExecutionFetchState::Pending);
                 Rc::from(this)
             }
+            TraceviewStateAction::SetFailed {
+                execution_id,
+                cursors,
+            } => {
+                let mut this = self.as_ref().clone();
+                this.execution_ids_to_fetch_state
+                    .insert(execution_id, ExecutionFetchState::Failed(cursors));
+                Rc::from(this)
+            }
+            TraceviewStateAction::RetryFailed => {
+                let mut this = self.as_ref().clone();
+                for state in this.execution_ids_to_fetch_state.values_mut() {
+                    if let ExecutionFetchState::Failed(cursors) = *state {
+                        *state = ExecutionFetchState::Requested(cursors);
+                    }
+                }
+                Rc::from(this)
+            }
             TraceviewStateAction::RequestNextPage {
                 execution_id,
                 cursors,
@@ -180,7 +254,7 @@ impl Reducible for TraceViewState {
                 let new_fetch_state = if finished {
                     ExecutionFetchState::Finished
                 } else {
-                    ExecutionFetchState::Pending
+                    ExecutionFetchState::Polling
                     // Will be followed by ExecutionFetchState::Requested
                 };
                 this.execution_ids_to_fetch_state
@@ -203,6 +277,18 @@ impl Reducible for TraceViewState {
                 this.show_delays = show;
                 Rc::from(this)
             }
+            TraceviewStateAction::SetShowCriticalPath(show) => {
+                let mut this = self.as_ref().clone();
+                this.show_critical_path = show;
+                Rc::from(this)
+            }
+            TraceviewStateAction::ExpandToDepth(depth) => {
+                let mut this = self.as_ref().clone();
+                this.expand_depth = Some(depth);
+                // Reopen what was collapsed by hand, deeper nodes are left as they are.
+                this.expanded_nodes.retain(|_, expanded| *expanded);
+                Rc::from(this)
+            }
         }
     }
 }
@@ -210,6 +296,8 @@ impl Reducible for TraceViewState {
 #[component(TraceView)]
 pub fn trace_view(TraceViewProps { execution_id }: &TraceViewProps) -> Html {
     let trace_view_state = use_reducer_eq(TraceViewState::default);
+    let timeline = use_reducer(TimelineState::default);
+    let tree_pane_ref = use_node_ref();
     let notifications =
         use_context::<NotificationContext>().expect("NotificationContext should be provided");
     // Fill the current execution id
@@ -251,19 +339,26 @@ pub fn trace_view(TraceViewProps { execution_id }: &TraceViewProps) -> Html {
         compute_submit_await_version_groups(events, responses)
     };
 
+    let filter = TraceFilter::of(trace_view);
     let root_trace = {
         compute_root_trace(
             execution_id,
-            true,
+            0,
             &trace_view.events,
             &trace_view.responses,
             &trace_view.statuses,
-            &trace_view_state,
+            trace_view,
+            filter,
             &mut missing_executions.borrow_mut(),
             &mut expandable_missing_children.borrow_mut(),
             &version_to_group,
         )
     };
+    let unfiltered_trace = if filter == TraceFilter::NONE {
+        root_trace.clone()
+    } else {
+        unfiltered_root_trace(execution_id, trace_view, &version_to_group)
+    };
 
     let root_missing_children = expandable_missing_children
         .borrow()
@@ -289,6 +384,74 @@ pub fn trace_view(TraceViewProps { execution_id }: &TraceViewProps) -> Html {
         },
     );
 
+    let timeline_bounds = root_trace
+        .as_ref()
+        .map(|root_trace| TimeWindow::new(root_trace.scheduled_at, root_trace.last_event_at));
+    {
+        let timeline = timeline.clone();
+        let tree_pane_ref = tree_pane_ref.clone();
+        use_effect_with(timeline_bounds, move |bounds| {
+            let listeners = match (bounds, tree_pane_ref.cast::<web_sys::Element>()) {
+                (Some(bounds), Some(pane)) => listen_to_gestures(&pane, *bounds, timeline),
+                _ => Vec::new(),
+            };
+            move || drop(listeners)
+        });
+    }
+
+    // "Expand all": queue every discovered descendant within the depth, each loaded child
+    // reveals the next level until the depth is reached.
+    let descendants_to_load: Vec<ExecutionId> = trace_view
+        .expand_depth
+        .map(|expand_depth| {
+            missing_executions
+                .borrow()
+                .iter()
+                .filter(|child| descendant_depth(execution_id, child) <= expand_depth)
+                .cloned()
+                .collect()
+        })
+        .unwrap_or_default();
+    use_effect_with(descendants_to_load, {
+        let trace_view_state = trace_view_state.clone();
+        move |descendants_to_load| {
+            for execution_id in descendants_to_load {
+                trace_view_state
+                    .dispatch(TraceviewStateAction::AddExecutionId(execution_id.clone()));
+            }
+        }
+    });
+    let failed_count = trace_view
+        .execution_ids_to_fetch_state
+        .values()
+        .filter(|state| matches!(state, ExecutionFetchState::Failed(_)))
+        .count();
+    let on_retry_failed = {
+        let trace_view_state = trace_view_state.clone();
+        Callback::from(move |_: MouseEvent| {
+            trace_view_state.dispatch(TraceviewStateAction::RetryFailed);
+        })
+    };
+    let load_progress = {
+        let total = trace_view.execution_ids_to_fetch_state.len();
+        let loaded = trace_view
+            .execution_ids_to_fetch_state
+            .iter()
+            .filter(|(id, state)| {
+                matches!(state, ExecutionFetchState::Failed(_))
+                    || trace_view.events.contains_key(*id)
+            })
+            .count();
+        (trace_view.expand_depth.is_some() && loaded < total).then(|| {
+            html! {
+                <span class="trace-load-progress">
+                    <progress value={loaded.to_string()} max={total.to_string()} />
+                    {format!(" Loaded {loaded} of {total} executions")}
+                </span>
+            }
+        })
+    };
+
     let execution_log = {
         let all_events = &trace_view.events;
         let dummy_events = Vec::new();
@@ -326,6 +489,13 @@ pub fn trace_view(TraceViewProps { execution_id }: &TraceViewProps) -> Html {
                         })
                 )
             })
+            .filter(|event| {
+                timeline.brush.is_none_or(|brush| {
+                    brush.contains(DateTime::from(
+                        event.created_at.expect("event.created_at is sent"),
+                    ))
+                })
+            })
             .map(|event| {
                 let detail = event_to_detail(
                     event,
@@ -415,6 +585,59 @@ pub fn trace_view(TraceViewProps { execution_id }: &TraceViewProps) -> Html {
         })
     };
 
+    let expand_depth_input = use_state(|| DEFAULT_EXPAND_DEPTH);
+    let on_expand_depth_change = {
+        let expand_depth_input = expand_depth_input.clone();
+        Callback::from(move |e: Event| {
+            let target: HtmlInputElement = e.target_unchecked_into();
+            if let Ok(depth) = target.value().parse::<u32>() {
+                expand_depth_input.set(depth.max(1));
+            }
+        })
+    };
+    let on_expand_all = {
+        let trace_view_state = trace_view_state.clone();
+        let expand_depth_input = expand_depth_input.clone();
+        Callback::from(move |_: MouseEvent| {
+            trace_view_state.dispatch(TraceviewStateAction::ExpandToDepth(*expand_depth_input));
+        })
+    };
+
+    let on_show_critical_path_change = {
+        let trace_view_state = trace_view_state.clone();
+        Callback::from(move |e: Event| {
+            let target: HtmlInputElement = e.target_unchecked_into();
+            trace_view_state.dispatch(TraceviewStateAction::SetShowCriticalPath(target.checked()));
+        })
+    };
+
+    let critical_path = unfiltered_trace
+        .as_ref()
+        .filter(|_| trace_view.show_critical_path)
+        .map(critical_path);
+    let critical_path_nodes = Rc::new(
+        critical_path
+            .as_ref()
+            .map(|path| path.node_keys())
+            .unwrap_or_default(),
+    );
+
+    let on_export = |format: fn(&TraceDataRoot) -> serde_json::Value, suffix: &'static str| {
+        let root_trace = unfiltered_trace.clone();
+        let execution_id = execution_id.clone();
+        Callback::from(move |_: MouseEvent| {
+            if let Some(root_trace) = &root_trace {
+                download(
+                    &format!("{execution_id}.{suffix}.json"),
+                    "application/json",
+                    &format(root_trace).to_string(),
+                );
+            }
+        })
+    };
+    let on_export_otlp = on_export(to_otlp_json, "otlp");
+    let on_export_chrome = on_export(to_chrome_trace, "chrome-trace");
+
     let on_toggle_trace_node = {
         let trace_view_state = trace_view_state.clone();
         let expandable_missing_children = expandable_missing_children.clone();
@@ -433,13 +656,69 @@ pub fn trace_view(TraceViewProps { execution_id }: &TraceViewProps) -> Html {
         })
     };
 
+    let on_reset_zoom = {
+        let timeline = timeline.clone();
+        Callback::from(move |_: MouseEvent| timeline.dispatch(TimelineAction::ResetZoom))
+    };
+    let on_clear_brush = {
+        let timeline = timeline.clone();
+        Callback::from(move |_: MouseEvent| timeline.dispatch(TimelineAction::ClearBrush))
+    };
+    let detail_tab = use_state(|| DetailTab::Events);
+    let http_traces: Vec<_> = collect_http_traces(&trace_view.events)
+        .into_iter()
+        .filter(|row| {
+            timeline
+                .brush
+                .is_none_or(|brush| brush.contains(row.sent_at))
+        })
+        .collect();
+    let brush_summary = timeline_bounds.zip(timeline.brush).map(|(bounds, brush)| {
+        let offset = |instant: DateTime<Utc>| {
+            format_offset(
+                (instant - bounds.start)
+                    .num_microseconds()
+                    .unwrap_or_default(),
+            )
+        };
+        html! {
+            <div class="trace-brush-summary">
+                {format!(
+                    "Showing {} between +{} and +{}",
+                    match *detail_tab {
+                        DetailTab::Events => format!("{} events", execution_log.len()),
+                        DetailTab::HttpTraces => format!("{} HTTP requests", http_traces.len()),
+                    },
+                    offset(brush.start),
+                    offset(brush.end)
+                )}
+                <button type="button" onclick={on_clear_brush.clone()}>{"Clear selection"}</button>
+            </div>
+        }
+    });
+
+    let tab_button = |label: String, tab: DetailTab| {
+        let detail_tab = detail_tab.clone();
+        let class = classes!((*detail_tab == tab).then_some("active"));
+        html! {
+            <button type="button" {class} onclick={Callback::from(move |_| detail_tab.set(tab))}>
+                { label }
+            </button>
+        }
+    };
+
     html! {<>
         <ExecutionHeader execution_id={execution_id.clone()} link={ExecutionLink::Trace} />
+        <AnnotationBox
+            execution_id={execution_id.clone()}
+            route={Route::ExecutionTrace { execution_id: execution_id.clone() }}
+        />
 
         <div class="trace-layout-container">
             <div
                 id="trace-tree-pane"
                 class="trace-view"
+                ref={tree_pane_ref}
             >
                 <div class="trace-controls" style="margin-bottom: 10px; display: flex; gap: 15px;">
                     <label style="cursor: pointer; user-select: none;">
@@ -460,11 +739,63 @@ pub fn trace_view(TraceViewProps { execution_id }: &TraceViewProps) -> Html {
                         />
                         {"Show delays"}
                     </label>
+                    <label style="cursor: pointer; user-select: none;">
+                        <input
+                            type="checkbox"
+                            checked={trace_view.show_critical_path}
+                            onchange={on_show_critical_path_change}
+                            style="margin-right: 5px;"
+                        />
+                        {"Critical path"}
+                    </label>
+                    <span class="trace-expand-all">
+                        <button type="button" onclick={on_expand_all}>{"Expand all to depth"}</button>
+                        <input
+                            type="number"
+                            min="1"
+                            value={expand_depth_input.to_string()}
+                            onchange={on_expand_depth_change}
+                        />
+                    </span>
+                    {load_progress}
+                    if failed_count > 0 {
+                        <button type="button" onclick={on_retry_failed}>
+                            {format!("Retry {failed_count} failed")}
+                        </button>
+                    }
+                    <button type="button" onclick={on_reset_zoom} disabled={!timeline.is_zoomed()}>
+                        {"Reset zoom"}
+                    </button>
+                    <button type="button" onclick={on_clear_brush} disabled={timeline.brush.is_none()}>
+                        {"Clear selection"}
+                    </button>
+                    <button
+                        type="button"
+                        onclick={on_export_otlp}
+                        disabled={root_trace.is_none()}
+                        title="Download the loaded trace as OTLP-JSON spans"
+                    >
+                        {"Export OTLP"}
+                    </button>
+                    <button
+                        type="button"
+                        onclick={on_export_chrome}
+                        disabled={root_trace.is_none()}
+                        title="Download the loaded trace for about:tracing or Perfetto"
+                    >
+                        {"Export Chrome trace"}
+                    </button>
                 </div>
-                if let Some(root_trace) = root_trace {
+                if let (Some(root_trace), Some(bounds)) = (root_trace, timeline_bounds) {
+                    <TimeAxis
+                        view={timeline.view(bounds)}
+                        origin={bounds.start}
+                        brush={timeline.brush}
+                    />
                     <ExecutionTrace
-                        root_scheduled_at={root_trace.scheduled_at}
                         root_last_event_at={root_trace.last_event_at}
+                        view={timeline.view(bounds)}
+                        critical_path={critical_path_nodes}
                         data={TraceData::Root(root_trace)}
                         on_toggle={on_toggle_trace_node}
                     />
@@ -476,27 +807,56 @@ pub fn trace_view(TraceViewProps { execution_id }: &TraceViewProps) -> Html {
                 id="trace-detail-pane"
                 class="trace-detail"
             >
-                {execution_log}
+                <div class="view-tabs">
+                    { tab_button("Events".to_string(), DetailTab::Events) }
+                    { tab_button(format!("HTTP traces ({})", http_traces.len()), DetailTab::HttpTraces) }
+                </div>
+                {brush_summary}
+                if *detail_tab == DetailTab::HttpTraces {
+                    <HttpTracePanel traces={http_traces} execution_id={execution_id.clone()} />
+                } else {
+                    if let Some(path) = critical_path {
+                        <CriticalPathSummary {path} />
+                    }
+                    {execution_log}
+                }
             </div>
         </div>
 
     </>}
 }
 
+/// Requested executions to fetch now, keeping at most `MAX_IN_FLIGHT` requests pending.
+fn requests_to_send(
+    fetch_states: &HashMap<ExecutionId, ExecutionFetchState>,
+) -> Vec<(ExecutionId, Cursors)> {
+    let in_flight = fetch_states
+        .values()
+        .filter(|state| matches!(state, ExecutionFetchState::Pending))
+        .count();
+    let mut requested: Vec<_> = fetch_states
+        .iter()
+        .filter_map(|(id, state)| match state {
+            ExecutionFetchState::Requested(cursors) => Some((id.clone(), *cursors)),
+            ExecutionFetchState::Pending
+            | ExecutionFetchState::Polling
+            | ExecutionFetchState::Finished
+            | ExecutionFetchState::Failed(_) => None,
+        })
+        .collect();
+    // Shallower executions first, the rest is picked up once a request finishes.
+    requested.sort_by_key(|(id, _)| id.id.len());
+    requested.truncate(MAX_IN_FLIGHT.saturating_sub(in_flight));
+    requested
+}
+
 fn on_state_change(
     (trace_view_state, notifications): &(UseReducerHandle<TraceViewState>, NotificationContext),
 ) {
     trace!("Triggered use_effects");
-    for (execution_id, cursors) in trace_view_state
-        .execution_ids_to_fetch_state
-        .iter()
-        .filter_map(|(id, state)| match state {
-            ExecutionFetchState::Requested(cursors) => Some((id, *cursors)),
-            ExecutionFetchState::Pending | ExecutionFetchState::Finished => None,
-        })
+    for (execution_id, cursors) in requests_to_send(&trace_view_state.execution_ids_to_fetch_state)
     {
         trace_view_state.dispatch(TraceviewStateAction::SetPending(execution_id.clone()));
-        let execution_id = execution_id.clone();
         let trace_view_state = trace_view_state.clone();
         let notifications = notifications.clone();
         wasm_bindgen_futures::spawn_local(async move {
@@ -564,6 +924,10 @@ fn on_state_change(
                 }
                 Err(e) => {
                     error!("Failed to list execution events: {:?}", e);
+                    trace_view_state.dispatch(TraceviewStateAction::SetFailed {
+                        execution_id,
+                        cursors,
+                    });
                     notifications.push(Notification::error(format!(
                         "Failed to load trace data: {}",
                         e.message()
@@ -692,11 +1056,12 @@ pub(crate) fn compute_submit_await_version_groups(
 #[allow(clippy::too_many_arguments)]
 fn compute_root_trace(
     execution_id: &ExecutionId,
-    is_root: bool,
+    depth: u32,
     events_map: &HashMap<ExecutionId, Vec<ExecutionEvent>>,
     responses_map: &HashMap<ExecutionId, HashMap<JoinSetId, Vec<JoinSetResponseEvent>>>,
     statuses_map: &HashMap<ExecutionId, grpc_client::execution_status::Status>,
-    trace_view_state: &UseReducerHandle<TraceViewState>,
+    trace_view_state: &TraceViewState,
+    filter: TraceFilter,
     missing_ids: &mut Vec<ExecutionId>,
     expandable_missing_children: &mut HashMap<String, Vec<ExecutionId>>,
     version_to_group: &HashMap<VersionType, Vec<VersionType>>,
@@ -705,6 +1070,7 @@ fn compute_root_trace(
         Some(events) if !events.is_empty() => events,
         _ => return None,
     };
+    let is_root = depth == 0;
 
     // Only the root's direct children link to the detail panel: deeper descendants belong
     // to a child execution whose events are not shown on the right, and their versions
@@ -751,7 +1117,13 @@ fn compute_root_trace(
     let child_ids_to_results = compute_child_execution_id_to_child_execution_finished(responses);
     let delay_ids_to_finished = compute_delay_id_to_finished(responses);
 
-    let is_expanded = is_trace_node_expanded(trace_view_state, &node_key, false);
+    let is_expanded = is_trace_node_expanded(
+        trace_view_state,
+        &node_key,
+        trace_view_state
+            .expand_depth
+            .is_some_and(|expand_depth| depth < expand_depth),
+    );
 
     let children: Vec<TraceData> = events
             .iter()
@@ -862,11 +1234,11 @@ fn compute_root_trace(
 
                         // Based on responses to parent execution.
                         let is_finished = child_ids_to_results.contains_key(child_execution_id);
-                        if trace_view_state.hide_finished && is_finished {
+                        if filter.hide_finished && is_finished {
                             return None;
                         }
 
-                        if !trace_view_state.deref().execution_ids_to_fetch_state.contains_key(child_execution_id) {
+                        if !trace_view_state.execution_ids_to_fetch_state.contains_key(child_execution_id) {
                             missing_ids.push(child_execution_id.clone());
                             expandable_missing_children
                                 .entry(node_key.clone())
@@ -880,11 +1252,12 @@ fn compute_root_trace(
 
                         if let Some(mut child_root) = compute_root_trace(
                             child_execution_id,
-                            false,
+                            depth + 1,
                             events_map,
                             responses_map,
                             statuses_map,
                             trace_view_state,
+                            filter,
                             missing_ids,
                             expandable_missing_children,
                             version_to_group,
@@ -938,7 +1311,7 @@ fn compute_root_trace(
                                 },
                             )),
                     }) => {
-                        if !trace_view_state.show_delays {
+                        if !filter.show_delays {
                             return None;
                         }
                         let delay_id = delay_req.delay_id.as_ref().expect("`delay_id` is sent in `DelayRequest`");
@@ -947,7 +1320,7 @@ fn compute_root_trace(
 
                         // "Show finished" governs finished (OK/cancelled) delays too.
                         let delay_finished = delay_ids_to_finished.get(delay_id);
-                        if trace_view_state.hide_finished && delay_finished.is_some() {
+                        if filter.hide_finished && delay_finished.is_some() {
                             return None;
                         }
 
@@ -1148,8 +1521,37 @@ fn compute_root_trace(
     })
 }
 
+/// The tree of everything that was loaded, regardless of the display toggles.
+fn unfiltered_root_trace(
+    execution_id: &ExecutionId,
+    trace_view: &TraceViewState,
+    version_to_group: &HashMap<VersionType, Vec<VersionType>>,
+) -> Option<TraceDataRoot> {
+    compute_root_trace(
+        execution_id,
+        0,
+        &trace_view.events,
+        &trace_view.responses,
+        &trace_view.statuses,
+        trace_view,
+        TraceFilter::NONE,
+        &mut Vec::new(),
+        &mut HashMap::new(),
+        version_to_group,
+    )
+}
+
+/// Number of child-execution hops from `root` to its descendant `execution_id`.
+fn descendant_depth(root: &ExecutionId, execution_id: &ExecutionId) -> u32 {
+    let relative = execution_id
+        .id
+        .strip_prefix(root.id.as_str())
+        .unwrap_or(&execution_id.id);
+    u32::try_from(relative.matches(EXECUTION_ID_INFIX).count()).unwrap_or(u32::MAX)
+}
+
 fn is_trace_node_expanded(
-    trace_view_state: &UseReducerHandle<TraceViewState>,
+    trace_view_state: &TraceViewState,
     node_key: &str,
     default: bool,
 ) -> bool {
@@ -1369,4 +1771,253 @@ mod tests {
 
         assert!(groups.is_empty());
     }
+
+    fn execution_id(id: &str) -> ExecutionId {
+        ExecutionId { id: id.to_string() }
+    }
+
+    #[test]
+    fn descendant_depth_counts_child_hops() {
+        let root = execution_id("E_01");
+        assert_eq!(0, descendant_depth(&root, &root));
+        assert_eq!(1, descendant_depth(&root, &execution_id("E_01.o:1_1")));
+        assert_eq!(
+            2,
+            descendant_depth(&root, &execution_id("E_01.o:1_1.n:2_1"))
+        );
+        // Depth below a root that is a child itself.
+        let child = execution_id("E_01.o:1_1");
+        assert_eq!(
+            1,
+            descendant_depth(&child, &execution_id("E_01.o:1_1.n:2_1"))
+        );
+    }
+
+    #[test]
+    fn requests_are_bounded_and_shallowest_first() {
+        let cursors = Cursors {
+            version_from: 7,
+            responses_cursor_from: 3,
+        };
+        let mut fetch_states = HashMap::new();
+        fetch_states.insert(execution_id("E_01"), ExecutionFetchState::Finished);
+        for idx in 0..3 {
+            fetch_states.insert(
+                execution_id(&format!("E_01.o:1_{idx}.o:1_1")),
+                ExecutionFetchState::Requested(cursors),
+            );
+        }
+        for idx in 0..4 {
+            fetch_states.insert(
+                execution_id(&format!("E_01.o:1_{idx}")),
+                ExecutionFetchState::Requested(Cursors::default()),
+            );
+        }
+        fetch_states.insert(execution_id("E_01.o:2_1"), ExecutionFetchState::Pending);
+        fetch_states.insert(
+            execution_id("E_01.o:3_1"),
+            ExecutionFetchState::Failed(Cursors::default()),
+        );
+
+        let requests = requests_to_send(&fetch_states);
+        assert_eq!(MAX_IN_FLIGHT - 1, requests.len());
+        let (children, grandchildren) = requests.split_at(4);
+        assert!(
+            children.iter().all(
+                |(id, cursors)| descendant_depth(&execution_id("E_01"), id) == 1
+                    && *cursors == Cursors::default()
+            )
+        );
+        assert!(
+            grandchildren
+                .iter()
+                .all(|(_, requested)| *requested == cursors)
+        );
+
+        // Nothing more is sent while the limit is reached.
+        for (id, _) in &requests {
+            fetch_states.insert(id.clone(), ExecutionFetchState::Pending);
+        }
+        assert!(requests_to_send(&fetch_states).is_empty());
+    }
+
+    #[test]
+    fn failed_executions_are_retried_from_their_cursors() {
+        let cursors = Cursors {
+            version_from: 7,
+            responses_cursor_from: 3,
+        };
+        let state = Rc::new(TraceViewState::default()).reduce(TraceviewStateAction::SetFailed {
+            execution_id: execution_id("E_01"),
+            cursors,
+        });
+        assert!(requests_to_send(&state.execution_ids_to_fetch_state).is_empty());
+        let state = state.reduce(TraceviewStateAction::RetryFailed);
+        assert_eq!(
+            vec![(execution_id("E_01"), cursors)],
+            requests_to_send(&state.execution_ids_to_fetch_state)
+        );
+    }
+
+    fn at(secs: i64) -> Option<prost_wkt_types::Timestamp> {
+        Some(DateTime::<Utc>::from_timestamp(secs, 0).unwrap().into())
+    }
+
+    fn created(secs: i64) -> ExecutionEvent {
+        ExecutionEvent {
+            version: 0,
+            created_at: at(secs),
+            event: Some(Event::Created(execution_event::Created {
+                function_name: Some(grpc_client::FunctionName {
+                    interface_name: "ns:pkg/ifc".to_string(),
+                    function_name: "fn".to_string(),
+                }),
+                scheduled_at: at(secs),
+                ..Default::default()
+            })),
+            ..Default::default()
+        }
+    }
+
+    fn ok_result() -> Option<SupportedFunctionResult> {
+        Some(SupportedFunctionResult {
+            value: Some(supported_function_result::Value::Ok(Default::default())),
+            ..Default::default()
+        })
+    }
+
+    fn finished(version: VersionType, secs: i64) -> ExecutionEvent {
+        ExecutionEvent {
+            version,
+            created_at: at(secs),
+            event: Some(Event::Finished(Finished {
+                value: ok_result(),
+                ..Default::default()
+            })),
+            ..Default::default()
+        }
+    }
+
+    /// The root submits a child finishing at 2s and waits for a delay expiring at 4s.
+    fn child_and_delay_state() -> TraceViewState {
+        let root = execution_id("E_01");
+        let child = execution_id("E_01.o:1_1");
+        let join_set_id = join_set("js");
+        let delay_id = grpc_client::DelayId {
+            id: "E_01.o:1_2".to_string(),
+        };
+        let mut submit = child_submit(1, &join_set_id, &child.id);
+        submit.created_at = at(1);
+        let mut delay = history_event(
+            2,
+            history_event::Event::JoinSetRequest(history_event::JoinSetRequest {
+                join_set_id: Some(join_set_id.clone()),
+                join_set_request: Some(
+                    history_event::join_set_request::JoinSetRequest::DelayRequest(
+                        history_event::join_set_request::DelayRequest {
+                            delay_id: Some(delay_id.clone()),
+                            expires_at: at(4),
+                            ..Default::default()
+                        },
+                    ),
+                ),
+            }),
+        );
+        delay.created_at = at(1);
+        let responses = vec![
+            JoinSetResponseEvent {
+                created_at: at(2),
+                join_set_id: Some(join_set_id.clone()),
+                response: Some(join_set_response_event::Response::ChildExecutionFinished(
+                    join_set_response_event::ChildExecutionFinished {
+                        child_execution_id: Some(child.clone()),
+                        value: ok_result(),
+                    },
+                )),
+            },
+            JoinSetResponseEvent {
+                created_at: at(4),
+                join_set_id: Some(join_set_id.clone()),
+                response: Some(join_set_response_event::Response::DelayFinished(
+                    join_set_response_event::DelayFinished {
+                        delay_id: Some(delay_id),
+                        success: true,
+                    },
+                )),
+            },
+        ];
+        let mut state = TraceViewState::default();
+        state.events.insert(
+            root.clone(),
+            vec![created(0), submit, delay, finished(3, 5)],
+        );
+        state
+            .events
+            .insert(child.clone(), vec![created(1), finished(1, 2)]);
+        state
+            .responses
+            .insert(root, HashMap::from([(join_set_id, responses)]));
+        for id in [execution_id("E_01"), child] {
+            state
+                .execution_ids_to_fetch_state
+                .insert(id, ExecutionFetchState::Finished);
+        }
+        state
+    }
+
+    #[test]
+    fn critical_path_does_not_depend_on_display_toggles() {
+        let root = execution_id("E_01");
+        let shown = child_and_delay_state();
+        let hidden = TraceViewState {
+            hide_finished: true,
+            show_delays: false,
+            ..shown.clone()
+        };
+        let displayed = compute_root_trace(
+            &root,
+            0,
+            &hidden.events,
+            &hidden.responses,
+            &hidden.statuses,
+            &hidden,
+            TraceFilter::of(&hidden),
+            &mut Vec::new(),
+            &mut HashMap::new(),
+            &HashMap::new(),
+        )
+        .unwrap();
+        assert!(displayed.children.is_empty());
+
+        let path = |state: &TraceViewState| {
+            critical_path(&unfiltered_root_trace(&root, state, &HashMap::new()).unwrap())
+        };
+        let shown_path = path(&shown);
+        assert_eq!(shown_path, path(&hidden));
+        assert_ne!(shown_path, critical_path(&displayed));
+        assert_eq!(5_000_000, shown_path.total_micros());
+        // The delay ending last is on the path even when delays are hidden.
+        assert!(
+            shown_path
+                .segments
+                .iter()
+                .any(|segment| segment.node_key == "E_01:delay:E_01.o:1_2"),
+            "{shown_path:?}"
+        );
+    }
+
+    #[test]
+    fn export_does_not_depend_on_display_toggles() {
+        let root = execution_id("E_01");
+        let shown = child_and_delay_state();
+        let hidden = TraceViewState {
+            hide_finished: true,
+            show_delays: false,
+            ..shown.clone()
+        };
+        let export = |state: &TraceViewState| {
+            to_otlp_json(&unfiltered_root_trace(&root, state, &HashMap::new()).unwrap())
+        };
+        assert_eq!(export(&shown), export(&hidden));
+    }
 }
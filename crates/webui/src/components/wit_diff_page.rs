@@ -0,0 +1,269 @@
+use crate::{
+    app::Route,
+    components::{
+        component_list_page::ComponentQuery,
+        notification::{Notification, NotificationContext},
+    },
+    grpc::grpc_client::{self, ComponentId},
+    util::wit_diff::{Change, ItemDiff, TypeDiff, WitDiff, diff_wit},
+};
+use log::error;
+use serde::{Deserialize, Serialize};
+use std::ops::Deref;
+use wasm_bindgen_futures::spawn_local;
+use yew::prelude::*;
+use yew_router::prelude::*;
+
+/// Deployments the compared components belong to. Absent means the active deployment.
+#[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
+pub struct WitDiffQuery {
+    pub from_deployment_id: Option<String>,
+    pub to_deployment_id: Option<String>,
+}
+
+#[derive(Properties, PartialEq)]
+pub struct WitDiffPageProps {
+    pub from: ComponentId,
+    pub to: ComponentId,
+}
+
+async fn fetch_wit(
+    component_id: ComponentId,
+    deployment_id: Option<String>,
+) -> Result<String, String> {
+    let mut fn_client = grpc_client::function_repository_client::FunctionRepositoryClient::new(
+        crate::auth::client(),
+    );
+    fn_client
+        .get_wit(grpc_client::GetWitRequest {
+            component_digest: component_id.digest.clone(),
+            deployment_id: deployment_id.map(|id| grpc_client::DeploymentId { id }),
+        })
+        .await
+        .map_err(|err| format!("cannot get WIT of {}: {}", component_id.name, err.message()))?
+        .into_inner()
+        .content
+        .ok_or_else(|| format!("no WIT is associated with {}", component_id.name))
+}
+
+async fn fetch_wit_diff(
+    from: ComponentId,
+    to: ComponentId,
+    query: WitDiffQuery,
+) -> Result<WitDiff, String> {
+    let (from, to) = futures::future::join(
+        fetch_wit(from, query.from_deployment_id),
+        fetch_wit(to, query.to_deployment_id),
+    )
+    .await;
+    diff_wit(&from?, &to?).map_err(|err| format!("{err:#}"))
+}
+
+fn change_sign(change: Change) -> (&'static str, &'static str) {
+    match change {
+        Change::Added => ("diff-ins", "+ "),
+        Change::Removed => ("diff-del", "- "),
+        Change::Changed => ("diff-chg", "~ "),
+    }
+}
+
+fn line(change: Option<Change>, indent: usize, content: Html) -> Html {
+    let (class, sign) = change.map_or(("diff-eq", "  "), change_sign);
+    let indent = "    ".repeat(indent);
+    html! {
+        <span class={classes!("wit-diff-line", class)}>
+            { sign }{ indent }{ content }{ "\n" }
+        </span>
+    }
+}
+
+/// An item present on both sides is shown as the removed line followed by the added one.
+fn item_lines(item: &ItemDiff, indent: usize, render: impl Fn(&str, &str) -> Html) -> Html {
+    let from = item
+        .from
+        .as_deref()
+        .map(|from| line(Some(Change::Removed), indent, render(&item.name, from)));
+    let to = item
+        .to
+        .as_deref()
+        .map(|to| line(Some(Change::Added), indent, render(&item.name, to)));
+    html! { <>{ from }{ to }</> }
+}
+
+fn render_function(name: &str, signature: &str) -> Html {
+    let signature = signature.strip_prefix("func").unwrap_or(signature);
+    html! {<>
+        <span class="func">{ name }</span>{": "}<span class="keyword">{"func"}</span>{ signature }{";"}
+    </>}
+}
+
+fn render_member(kind: &str) -> impl Fn(&str, &str) -> Html {
+    let is_record = kind == "record";
+    move |name, rendered| {
+        if is_record {
+            html! {<><span class="param">{ name }</span>{": "}<span class="type">{ rendered }</span>{","}</>}
+        } else {
+            html! {<><span class="case">{ rendered }</span>{","}</>}
+        }
+    }
+}
+
+fn render_type(ty: &TypeDiff) -> Html {
+    let has_body = !ty.members.is_empty();
+    let header = |definition: &str| {
+        if !matches!(
+            definition,
+            "record" | "variant" | "enum" | "flags" | "resource"
+        ) {
+            html! {<>
+                <span class="keyword">{"type"}</span>{" "}<span class="type">{ &ty.name }</span>
+                {" = "}<span class="type">{ definition.to_string() }</span>{";"}
+            </>}
+        } else if has_body {
+            html! {<>
+                <span class="keyword">{ definition.to_string() }</span>{" "}
+                <span class="type">{ &ty.name }</span>{" {"}
+            </>}
+        } else {
+            html! {<>
+                <span class="keyword">{ definition.to_string() }</span>{" "}
+                <span class="type">{ &ty.name }</span>{";"}
+            </>}
+        }
+    };
+    // Lines around changed members are context, unless the whole type was added or removed.
+    let outer_change = match ty.change {
+        Change::Changed => None,
+        change => Some(change),
+    };
+    let header_lines = match &ty.definition {
+        Some(definition) => html! {<>
+            if let Some(from) = &definition.from {
+                { line(Some(Change::Removed), 1, header(from)) }
+            }
+            if let Some(to) = &definition.to {
+                { line(Some(Change::Added), 1, header(to)) }
+            }
+        </>},
+        None => line(None, 1, header(ty.kind)),
+    };
+    html! {<>
+        { header_lines }
+        { for ty.members.iter().map(|member| item_lines(member, 2, render_member(ty.kind))) }
+        if has_body {
+            { line(outer_change, 1, html! {"}"}) }
+        }
+    </>}
+}
+
+pub fn render_wit_diff(diff: &WitDiff) -> Html {
+    let interfaces = diff.interfaces.iter().map(|ifc| {
+        let change = match ifc.change {
+            Change::Changed => None,
+            change => Some(change),
+        };
+        html! {<>
+            { line(change, 0, html! {<>
+                <span class="keyword">{"interface"}</span>{" "}<span class="type">{ &ifc.name }</span>{" {"}
+            </>}) }
+            { for ifc.types.iter().map(render_type) }
+            { for ifc.functions.iter().map(|function| match function.change {
+                Change::Changed => item_lines(function, 1, render_function),
+                change => line(
+                    Some(change),
+                    1,
+                    render_function(
+                        &function.name,
+                        function.from.as_deref().or(function.to.as_deref()).unwrap_or_default(),
+                    ),
+                ),
+            }) }
+            { line(change, 0, html! {"}"}) }
+            { "\n" }
+        </>}
+    });
+    html! {
+        <div class="code-block wit-diff">
+            <pre>{ for interfaces }</pre>
+        </div>
+    }
+}
+
+#[component(WitDiffPage)]
+pub fn wit_diff_page(WitDiffPageProps { from, to }: &WitDiffPageProps) -> Html {
+    let notifications =
+        use_context::<NotificationContext>().expect("NotificationContext should be provided");
+    let location = use_location().expect("should be called inside a router");
+    let query = location.query::<WitDiffQuery>().unwrap_or_default();
+    let diff_state = use_state(|| None::<Result<WitDiff, String>>);
+
+    {
+        let diff_state = diff_state.clone();
+        use_effect_with(
+            (from.clone(), to.clone(), query.clone()),
+            move |(from, to, query)| {
+                let (from, to, query) = (from.clone(), to.clone(), query.clone());
+                diff_state.set(None);
+                spawn_local(async move {
+                    let result = fetch_wit_diff(from, to, query).await;
+                    if let Err(err) = &result {
+                        error!("WIT diff failed: {err}");
+                        notifications.push(Notification::error(err.clone()));
+                    }
+                    diff_state.set(Some(result));
+                });
+            },
+        );
+    }
+
+    let body = match diff_state.deref() {
+        None => html! { <p>{"Loading..."}</p> },
+        Some(Err(err)) => html! { <p class="error">{ err }</p> },
+        Some(Ok(diff)) if diff.is_empty() => {
+            html! { <p>{"The components have identical WIT interfaces."}</p> }
+        }
+        Some(Ok(diff)) => {
+            let breaking = diff.breaking_count();
+            html! {<>
+                <p class="secondary-text">
+                    if breaking > 0 {
+                        { format!("{breaking} removed or changed item{} may break existing callers.", if breaking == 1 { "" } else { "s" }) }
+                    } else {
+                        {"Only additions: existing callers are not affected."}
+                    }
+                </p>
+                { render_wit_diff(diff) }
+            </>}
+        }
+    };
+
+    let component_link = |component_id: &ComponentId, deployment_id: &Option<String>| {
+        let digest = component_id
+            .digest
+            .as_ref()
+            .map(|digest| digest.digest.clone())
+            .unwrap_or_default();
+        html! {
+            <Link<Route, ComponentQuery>
+                to={Route::Component { component_id: component_id.clone() }}
+                query={ComponentQuery {
+                    deployment_id: deployment_id.clone(),
+                }}
+            >
+                { digest }
+            </Link<Route, ComponentQuery>>
+        }
+    };
+
+    html! {
+        <>
+            <h3>{"WIT diff: "}{ &to.name }</h3>
+            <p>
+                <span class="diff-del">{ component_link(from, &query.from_deployment_id) }</span>
+                {" → "}
+                <span class="diff-ins">{ component_link(to, &query.to_deployment_id) }</span>
+            </p>
+            { body }
+        </>
+    }
+}
@@ -0,0 +1,220 @@
+//! Named Obelisk servers configured in `webui-proxy`, e.g. dev, staging and prod.
+//!
+//! The selected environment is the first path segment after `/env/`, so that links are
+//! unambiguous. The router uses the prefix as its basename, gRPC requests go to
+//! `/api/<environment>` and each environment keeps its own API token.
+//!
+//! All of it lives below the base path that `webui-proxy` announces in `index.html`.
+
+use crate::BASE_URL;
+use serde::Deserialize;
+use std::cell::RefCell;
+use web_sys::HtmlSelectElement;
+use yew::prelude::*;
+
+const PATH_PREFIX: &str = "/env/";
+const BASE_PATH_META: &str = "meta[name=obelisk-base-path]";
+
+thread_local! {
+    static CURRENT: RefCell<Option<String>> = const { RefCell::new(None) };
+    static CONFIGURED: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+#[derive(Deserialize)]
+struct EnvironmentsResponse {
+    environments: Vec<String>,
+}
+
+/// Path prefix of the UI, e.g. `/obelisk`, empty when served from the root.
+pub fn base_path() -> String {
+    web_sys::window()
+        .and_then(|window| window.document())
+        .and_then(|document| document.query_selector(BASE_PATH_META).ok().flatten())
+        .and_then(|meta| meta.get_attribute("content"))
+        .map(|base_path| base_path.trim_end_matches('/').to_string())
+        .unwrap_or_default()
+}
+
+/// Names of the configured environments, empty when the proxy offers none.
+pub async fn load_environments() -> Vec<String> {
+    let url = format!("{}{BASE_URL}/environments", base_path());
+    let response = match gloo::net::http::Request::get(&url).send().await {
+        Ok(response) if response.ok() => response,
+        _ => return Vec::new(),
+    };
+    // Servers without environments answer with something else, e.g. a gRPC error.
+    response
+        .json::<EnvironmentsResponse>()
+        .await
+        .map(|response| response.environments)
+        .unwrap_or_default()
+}
+
+fn location() -> Option<web_sys::Location> {
+    web_sys::window().map(|window| window.location())
+}
+
+/// The current path without the base path.
+fn app_path(location: &web_sys::Location) -> String {
+    let path = location.pathname().unwrap_or_default();
+    let base_path = base_path();
+    match path.strip_prefix(&base_path) {
+        Some(rest) if !base_path.is_empty() && (rest.is_empty() || rest.starts_with('/')) => {
+            rest.to_string()
+        }
+        _ => path,
+    }
+}
+
+/// Split `/env/<name>/rest` into the name and `/rest`.
+fn split_path(path: &str) -> Option<(&str, &str)> {
+    let rest = path.strip_prefix(PATH_PREFIX)?;
+    let (name, rest) = rest.find('/').map_or((rest, "/"), |idx| rest.split_at(idx));
+    (!name.is_empty()).then_some((name, rest))
+}
+
+/// Select the environment of the current location. Returns `false` when the page is being
+/// redirected to the first environment because the location names none.
+pub fn init(environments: &[String]) -> bool {
+    CONFIGURED.with(|configured| *configured.borrow_mut() = environments.to_vec());
+    let Some(location) = location() else {
+        return true;
+    };
+    let path = app_path(&location);
+    match split_path(&path) {
+        Some((name, _)) if environments.iter().any(|environment| environment == name) => {
+            CURRENT.with(|current| *current.borrow_mut() = Some(name.to_string()));
+            true
+        }
+        _ => {
+            let Some(first) = environments.first() else {
+                return true;
+            };
+            let rest = split_path(&path).map_or(path.as_str(), |(_, rest)| rest);
+            let _ = location.replace(&switch_url(first, rest, &location));
+            false
+        }
+    }
+}
+
+fn switch_url(name: &str, rest: &str, location: &web_sys::Location) -> String {
+    format!(
+        "{}{PATH_PREFIX}{name}{rest}{}{}",
+        base_path(),
+        location.search().unwrap_or_default(),
+        location.hash().unwrap_or_default()
+    )
+}
+
+pub fn current() -> Option<String> {
+    CURRENT.with(|current| current.borrow().clone())
+}
+
+/// Whether the proxy offers the environment.
+pub fn is_configured(name: &str) -> bool {
+    CONFIGURED.with(|configured| {
+        configured
+            .borrow()
+            .iter()
+            .any(|configured| configured == name)
+    })
+}
+
+/// URL of `path` of the UI served below `base_path`, in the environment `name`.
+pub fn app_url(base_path: &str, name: Option<&str>, path: &str) -> String {
+    match name {
+        Some(name) => format!("{base_path}{PATH_PREFIX}{name}{path}"),
+        None => format!("{base_path}{path}"),
+    }
+}
+
+/// Where gRPC-web requests are sent.
+pub fn base_url() -> String {
+    let base_path = base_path();
+    match current() {
+        Some(name) => format!("{base_path}{BASE_URL}/{name}"),
+        None => format!("{base_path}{BASE_URL}"),
+    }
+}
+
+/// Basename of the router, `None` when served from the root without environments.
+pub fn router_basename() -> Option<AttrValue> {
+    let base_path = base_path();
+    match current() {
+        Some(name) => Some(AttrValue::from(format!("{base_path}{PATH_PREFIX}{name}"))),
+        None if !base_path.is_empty() => Some(AttrValue::from(base_path)),
+        None => None,
+    }
+}
+
+/// Banner color of well-known environment names.
+fn color_class(name: &str) -> &'static str {
+    match name.to_ascii_lowercase().as_str() {
+        "prod" | "production" | "live" => "environment-prod",
+        "staging" | "stage" | "preprod" | "test" => "environment-staging",
+        "dev" | "development" | "local" => "environment-dev",
+        _ => "environment-other",
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct EnvironmentSwitcherProps {
+    pub environments: Vec<String>,
+}
+
+/// Colored banner with a selector reloading the UI against another environment.
+#[component(EnvironmentSwitcher)]
+pub fn environment_switcher(
+    EnvironmentSwitcherProps { environments }: &EnvironmentSwitcherProps,
+) -> Html {
+    let Some(current) = current() else {
+        return html! {};
+    };
+    let onchange = Callback::from(|e: Event| {
+        let name = e.target_unchecked_into::<HtmlSelectElement>().value();
+        let Some(location) = location() else {
+            return;
+        };
+        let path = app_path(&location);
+        let rest = split_path(&path).map_or("/", |(_, rest)| rest);
+        // A full reload drops the state and caches of the previous environment.
+        let _ = location.set_href(&switch_url(&name, rest, &location));
+    });
+    html! {
+        <div class={classes!("environment-banner", color_class(&current))}>
+            <label>
+                {"Environment "}
+                <select {onchange}>
+                    { for environments.iter().map(|name| html! {
+                        <option value={name.clone()} selected={*name == current}>{ name }</option>
+                    }) }
+                </select>
+            </label>
+        </div>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn environment_is_the_first_segment_after_the_prefix() {
+        assert_eq!(
+            Some(("prod", "/execution/E_1")),
+            split_path("/env/prod/execution/E_1")
+        );
+        assert_eq!(Some(("prod", "/")), split_path("/env/prod"));
+        assert_eq!(None, split_path("/execution/E_1"));
+        assert_eq!(None, split_path("/env/"));
+    }
+
+    #[test]
+    fn app_url_is_below_the_base_path_and_environment() {
+        assert_eq!(
+            "/obelisk/env/prod/execution/E_1",
+            app_url("/obelisk", Some("prod"), "/execution/E_1")
+        );
+        assert_eq!("/execution/E_1", app_url("", None, "/execution/E_1"));
+    }
+}
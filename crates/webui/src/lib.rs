@@ -1,6 +1,7 @@
 pub mod app;
 pub mod auth;
 pub mod components;
+pub mod environment;
 pub mod grpc;
 pub mod loader;
 pub mod tree;
@@ -1,11 +1,16 @@
 use webui::{
     app::{App, AppProps},
+    environment,
     loader::{get_current_deployment_id, load_components},
 };
 
 fn main() {
     init_logging();
     wasm_bindgen_futures::spawn_local(async move {
+        let environments = environment::load_environments().await;
+        if !environment::init(&environments) {
+            return; // redirecting
+        }
         let loaded = match load_components().await {
             Ok(loaded) => loaded,
             Err(status) if status.code() == tonic::Code::Unauthenticated => Default::default(),
@@ -16,6 +21,7 @@ fn main() {
         yew::Renderer::<App>::with_props(AppProps {
             initial_components: loaded,
             initial_deployment_id: deployment_id,
+            environments,
         })
         .render();
     });
@@ -0,0 +1,23 @@
+use gloo::file::{Blob, ObjectUrl};
+use gloo::timers::callback::Timeout;
+use wasm_bindgen::JsCast;
+
+/// Let the browser save `content` as a file named `file_name`.
+pub fn download(file_name: &str, mime_type: &str, content: &str) {
+    let Some(document) = web_sys::window().and_then(|window| window.document()) else {
+        return;
+    };
+    let Some(anchor) = document
+        .create_element("a")
+        .ok()
+        .and_then(|element| element.dyn_into::<web_sys::HtmlElement>().ok())
+    else {
+        return;
+    };
+    let url = ObjectUrl::from(Blob::new_with_options(content, Some(mime_type)));
+    let _ = anchor.set_attribute("href", &url);
+    let _ = anchor.set_attribute("download", file_name);
+    anchor.click();
+    // Revoking the URL right away can abort the download in some browsers.
+    Timeout::new(1_000, move || drop(url)).forget();
+}
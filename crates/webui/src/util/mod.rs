@@ -1,5 +1,7 @@
 pub mod color;
+pub mod download;
 pub mod time;
+pub mod wit_diff;
 pub mod wit_highlighter;
 pub mod wit_type_formatter;
 
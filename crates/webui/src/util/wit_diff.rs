@@ -0,0 +1,462 @@
+//! Structural diff of two WIT documents, e.g. two digests of the same component.
+//!
+//! Both documents are resolved with `wit-parser` and compared interface by interface:
+//! functions by their signatures, named types by their kind and members (record fields,
+//! variant and enum cases, flags). Named types referenced from signatures are compared by
+//! name only, their definition changes are reported on the type itself.
+
+use anyhow::Context;
+use std::{collections::BTreeMap, path::PathBuf};
+use wit_parser::{Function, Handle, Resolve, Type, TypeDefKind, TypeId, UnresolvedPackageGroup};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Change {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// A leaf difference: a function, a record field, a variant case and so on.
+/// `from` and `to` hold the rendered item, absent on the side where it does not exist.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ItemDiff {
+    pub name: String,
+    pub change: Change,
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TypeDiff {
+    pub name: String,
+    /// `record`, `variant`, `enum`... of the newer side, or of the older one when removed.
+    pub kind: &'static str,
+    pub change: Change,
+    /// The kind, or the aliased type for `type x = ...`. Set when the type was added or
+    /// removed, or when the definition changed, e.g. `record` → `variant`.
+    pub definition: Option<ItemDiff>,
+    pub members: Vec<ItemDiff>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct InterfaceDiff {
+    /// Fully qualified interface name, e.g. `ns:pkg/ifc@1.0.0`.
+    pub name: String,
+    pub change: Change,
+    pub functions: Vec<ItemDiff>,
+    pub types: Vec<TypeDiff>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WitDiff {
+    pub interfaces: Vec<InterfaceDiff>,
+}
+
+impl WitDiff {
+    pub fn is_empty(&self) -> bool {
+        self.interfaces.is_empty()
+    }
+
+    /// Everything but additions may break existing callers or running executions.
+    pub fn breaking_count(&self) -> usize {
+        self.interfaces
+            .iter()
+            .map(|ifc| {
+                ifc.functions
+                    .iter()
+                    .filter(|function| function.change != Change::Added)
+                    .count()
+                    + ifc
+                        .types
+                        .iter()
+                        .filter(|ty| ty.change != Change::Added)
+                        .count()
+            })
+            .sum()
+    }
+}
+
+/// Resolved interface contents, keyed by name so that both sides can be zipped.
+struct ResolvedInterface {
+    functions: BTreeMap<String, String>,
+    types: BTreeMap<String, ResolvedType>,
+}
+
+struct ResolvedType {
+    kind: &'static str,
+    /// The kind together with the aliased type, if any; compared as a whole.
+    definition: String,
+    members: BTreeMap<String, String>,
+}
+
+pub fn diff_wit(from: &str, to: &str) -> Result<WitDiff, anyhow::Error> {
+    let from = resolve_interfaces(from).context("cannot parse the older WIT")?;
+    let to = resolve_interfaces(to).context("cannot parse the newer WIT")?;
+    let mut names: Vec<&String> = from.keys().chain(to.keys()).collect();
+    names.sort();
+    names.dedup();
+
+    let interfaces = names
+        .into_iter()
+        .filter_map(|name| match (from.get(name), to.get(name)) {
+            (Some(from), Some(to)) => {
+                let functions = diff_items(&from.functions, &to.functions);
+                let types = diff_types(&from.types, &to.types);
+                (!functions.is_empty() || !types.is_empty()).then(|| InterfaceDiff {
+                    name: name.clone(),
+                    change: Change::Changed,
+                    functions,
+                    types,
+                })
+            }
+            (Some(from), None) => Some(InterfaceDiff {
+                name: name.clone(),
+                change: Change::Removed,
+                functions: diff_items(&from.functions, &BTreeMap::new()),
+                types: diff_types(&from.types, &BTreeMap::new()),
+            }),
+            (None, Some(to)) => Some(InterfaceDiff {
+                name: name.clone(),
+                change: Change::Added,
+                functions: diff_items(&BTreeMap::new(), &to.functions),
+                types: diff_types(&BTreeMap::new(), &to.types),
+            }),
+            (None, None) => unreachable!("name comes from one of the maps"),
+        })
+        .collect();
+    Ok(WitDiff { interfaces })
+}
+
+fn diff_items(from: &BTreeMap<String, String>, to: &BTreeMap<String, String>) -> Vec<ItemDiff> {
+    let mut names: Vec<&String> = from.keys().chain(to.keys()).collect();
+    names.sort();
+    names.dedup();
+    names
+        .into_iter()
+        .filter_map(|name| {
+            let (from, to) = (from.get(name), to.get(name));
+            let change = match (from, to) {
+                (Some(from), Some(to)) if from == to => return None,
+                (Some(_), Some(_)) => Change::Changed,
+                (Some(_), None) => Change::Removed,
+                (None, Some(_)) => Change::Added,
+                (None, None) => unreachable!("name comes from one of the maps"),
+            };
+            Some(ItemDiff {
+                name: name.clone(),
+                change,
+                from: from.cloned(),
+                to: to.cloned(),
+            })
+        })
+        .collect()
+}
+
+fn diff_types(
+    from: &BTreeMap<String, ResolvedType>,
+    to: &BTreeMap<String, ResolvedType>,
+) -> Vec<TypeDiff> {
+    let mut names: Vec<&String> = from.keys().chain(to.keys()).collect();
+    names.sort();
+    names.dedup();
+    names
+        .into_iter()
+        .filter_map(|name| match (from.get(name), to.get(name)) {
+            (Some(from), Some(to)) => {
+                let definition = (from.definition != to.definition).then(|| ItemDiff {
+                    name: name.clone(),
+                    change: Change::Changed,
+                    from: Some(from.definition.clone()),
+                    to: Some(to.definition.clone()),
+                });
+                let members = diff_items(&from.members, &to.members);
+                (definition.is_some() || !members.is_empty()).then(|| TypeDiff {
+                    name: name.clone(),
+                    kind: to.kind,
+                    change: Change::Changed,
+                    definition,
+                    members,
+                })
+            }
+            (Some(from), None) => Some(TypeDiff {
+                name: name.clone(),
+                kind: from.kind,
+                change: Change::Removed,
+                definition: Some(ItemDiff {
+                    name: name.clone(),
+                    change: Change::Removed,
+                    from: Some(from.definition.clone()),
+                    to: None,
+                }),
+                members: diff_items(&from.members, &BTreeMap::new()),
+            }),
+            (None, Some(to)) => Some(TypeDiff {
+                name: name.clone(),
+                kind: to.kind,
+                change: Change::Added,
+                definition: Some(ItemDiff {
+                    name: name.clone(),
+                    change: Change::Added,
+                    from: None,
+                    to: Some(to.definition.clone()),
+                }),
+                members: diff_items(&BTreeMap::new(), &to.members),
+            }),
+            (None, None) => unreachable!("name comes from one of the maps"),
+        })
+        .collect()
+}
+
+fn resolve_interfaces(wit: &str) -> Result<BTreeMap<String, ResolvedInterface>, anyhow::Error> {
+    let group = UnresolvedPackageGroup::parse(PathBuf::new(), wit)?;
+    let mut resolve = Resolve::new();
+    resolve.push_group(group)?;
+
+    let mut interfaces = BTreeMap::new();
+    for (_pkg_id, package) in resolve.packages.iter() {
+        for (ifc_name, ifc_id) in &package.interfaces {
+            let interface = &resolve.interfaces[*ifc_id];
+            let name = match &package.name.version {
+                Some(version) => format!(
+                    "{}:{}/{ifc_name}@{version}",
+                    package.name.namespace, package.name.name
+                ),
+                None => format!(
+                    "{}:{}/{ifc_name}",
+                    package.name.namespace, package.name.name
+                ),
+            };
+            let functions = interface
+                .functions
+                .iter()
+                .map(|(fn_name, function)| {
+                    (fn_name.clone(), function_signature(&resolve, function))
+                })
+                .collect();
+            let types = interface
+                .types
+                .iter()
+                .map(|(ty_name, ty_id)| (ty_name.clone(), resolve_type(&resolve, *ty_id)))
+                .collect();
+            interfaces.insert(name, ResolvedInterface { functions, types });
+        }
+    }
+    Ok(interfaces)
+}
+
+fn function_signature(resolve: &Resolve, function: &Function) -> String {
+    let params = function
+        .params
+        .iter()
+        .map(|param| format!("{}: {}", param.name, type_name(resolve, &param.ty)))
+        .collect::<Vec<_>>()
+        .join(", ");
+    match &function.result {
+        Some(result) => format!("func({params}) -> {}", type_name(resolve, result)),
+        None => format!("func({params})"),
+    }
+}
+
+fn resolve_type(resolve: &Resolve, ty_id: TypeId) -> ResolvedType {
+    let kind = &resolve.types[ty_id].kind;
+    let members = match kind {
+        TypeDefKind::Record(record) => record
+            .fields
+            .iter()
+            .map(|field| (field.name.clone(), type_name(resolve, &field.ty)))
+            .collect(),
+        TypeDefKind::Variant(variant) => variant
+            .cases
+            .iter()
+            .map(|case| {
+                let payload = case
+                    .ty
+                    .as_ref()
+                    .map(|ty| format!("({})", type_name(resolve, ty)))
+                    .unwrap_or_default();
+                (case.name.clone(), format!("{}{payload}", case.name))
+            })
+            .collect(),
+        TypeDefKind::Enum(enum_) => enum_
+            .cases
+            .iter()
+            .map(|case| (case.name.clone(), case.name.clone()))
+            .collect(),
+        TypeDefKind::Flags(flags) => flags
+            .flags
+            .iter()
+            .map(|flag| (flag.name.clone(), flag.name.clone()))
+            .collect(),
+        _ => BTreeMap::new(),
+    };
+    let definition = match kind {
+        // Aliases and anonymous shapes are compared as a whole, e.g. `type id = u64`.
+        TypeDefKind::Record(_)
+        | TypeDefKind::Variant(_)
+        | TypeDefKind::Enum(_)
+        | TypeDefKind::Flags(_)
+        | TypeDefKind::Resource => kind.as_str().to_string(),
+        TypeDefKind::Type(ty) => type_name(resolve, ty),
+        _ => anonymous_type_name(resolve, ty_id),
+    };
+    ResolvedType {
+        kind: kind.as_str(),
+        definition,
+        members,
+    }
+}
+
+/// Renders a type reference as it appears in a signature: named types by their name,
+/// anonymous ones by their structure.
+fn type_name(resolve: &Resolve, ty: &Type) -> String {
+    match ty {
+        Type::Id(id) => match &resolve.types[*id].name {
+            Some(name) => name.clone(),
+            None => anonymous_type_name(resolve, *id),
+        },
+        Type::Bool => "bool".to_string(),
+        Type::U8 => "u8".to_string(),
+        Type::U16 => "u16".to_string(),
+        Type::U32 => "u32".to_string(),
+        Type::U64 => "u64".to_string(),
+        Type::S8 => "s8".to_string(),
+        Type::S16 => "s16".to_string(),
+        Type::S32 => "s32".to_string(),
+        Type::S64 => "s64".to_string(),
+        Type::F32 => "f32".to_string(),
+        Type::F64 => "f64".to_string(),
+        Type::Char => "char".to_string(),
+        Type::String => "string".to_string(),
+        Type::ErrorContext => "error-context".to_string(),
+    }
+}
+
+fn anonymous_type_name(resolve: &Resolve, ty_id: TypeId) -> String {
+    let optional =
+        |ty: Option<&Type>| ty.map_or_else(|| "_".to_string(), |ty| type_name(resolve, ty));
+    match &resolve.types[ty_id].kind {
+        TypeDefKind::Type(ty) => type_name(resolve, ty),
+        TypeDefKind::List(ty) => format!("list<{}>", type_name(resolve, ty)),
+        TypeDefKind::Option(ty) => format!("option<{}>", type_name(resolve, ty)),
+        TypeDefKind::Result(result) => match (&result.ok, &result.err) {
+            (None, None) => "result".to_string(),
+            (ok, err) => format!(
+                "result<{}, {}>",
+                optional(ok.as_ref()),
+                optional(err.as_ref())
+            ),
+        },
+        TypeDefKind::Tuple(tuple) => format!(
+            "tuple<{}>",
+            tuple
+                .types
+                .iter()
+                .map(|ty| type_name(resolve, ty))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        TypeDefKind::Handle(Handle::Own(id)) => type_name(resolve, &Type::Id(*id)),
+        TypeDefKind::Handle(Handle::Borrow(id)) => {
+            format!("borrow<{}>", type_name(resolve, &Type::Id(*id)))
+        }
+        TypeDefKind::Future(ty) => format!("future<{}>", optional(ty.as_ref())),
+        TypeDefKind::Stream(ty) => format!("stream<{}>", optional(ty.as_ref())),
+        other => other.as_str().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FROM: &str = r"
+package test:pkg;
+
+interface ifc {
+    record order {
+        id: u64,
+        note: string,
+    }
+    variant outcome {
+        ok,
+        failed(string),
+    }
+    place: func(order: order) -> outcome;
+    cancel: func(id: u64);
+}
+";
+
+    const TO: &str = r"
+package test:pkg;
+
+interface ifc {
+    record order {
+        id: u64,
+        note: option<string>,
+        priority: u8,
+    }
+    variant outcome {
+        ok,
+        failed(string),
+        retry,
+    }
+    place: func(order: order) -> outcome;
+    query: func(id: u64) -> option<order>;
+}
+";
+
+    #[test]
+    fn reports_functions_fields_and_cases() {
+        let diff = diff_wit(FROM, TO).unwrap();
+
+        assert_eq!(diff.interfaces.len(), 1);
+        let ifc = &diff.interfaces[0];
+        assert_eq!(ifc.name, "test:pkg/ifc");
+        let functions = ifc
+            .functions
+            .iter()
+            .map(|function| (function.name.as_str(), function.change))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            functions,
+            vec![("cancel", Change::Removed), ("query", Change::Added)]
+        );
+
+        let order = ifc.types.iter().find(|ty| ty.name == "order").unwrap();
+        assert_eq!(order.kind, "record");
+        assert_eq!(
+            order.members,
+            vec![
+                ItemDiff {
+                    name: "note".to_string(),
+                    change: Change::Changed,
+                    from: Some("string".to_string()),
+                    to: Some("option<string>".to_string()),
+                },
+                ItemDiff {
+                    name: "priority".to_string(),
+                    change: Change::Added,
+                    from: None,
+                    to: Some("u8".to_string()),
+                },
+            ]
+        );
+        let outcome = ifc.types.iter().find(|ty| ty.name == "outcome").unwrap();
+        assert_eq!(outcome.members.len(), 1);
+        assert_eq!(outcome.members[0].change, Change::Added);
+        assert_eq!(diff.breaking_count(), 3);
+    }
+
+    #[test]
+    fn identical_documents_have_no_diff() {
+        assert!(diff_wit(FROM, FROM).unwrap().is_empty());
+    }
+
+    #[test]
+    fn primitive_types_are_named_as_in_wit() {
+        let resolve = Resolve::default();
+        assert_eq!(type_name(&resolve, &Type::S64), "s64");
+        assert_eq!(type_name(&resolve, &Type::String), "string");
+        assert_eq!(type_name(&resolve, &Type::ErrorContext), "error-context");
+    }
+}
@@ -0,0 +1,370 @@
+//! Rendering of pages against the fake gRPC server, run with `just test-browser`.
+#![cfg(target_arch = "wasm32")]
+
+mod support;
+
+use std::{cell::RefCell, rc::Rc, str::FromStr};
+use support::*;
+use wasm_bindgen_test::{wasm_bindgen_test, wasm_bindgen_test_configure};
+use webui::{
+    app::{AppState, Route, query::BacktraceVersionsPath},
+    grpc::{grpc_client, ifc_fqn::IfcFqn},
+    loader::LoadedComponents,
+};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+const EXECUTION_ID: &str = "E_01KAJY1W5V4RZ8H3QC6PX2N9TD";
+
+fn checkout_ffqn() -> grpc_client::FunctionName {
+    function_name("shop:orders/checkout", "run")
+}
+
+/// Created, a join set and a successful finish, as answered to the trace and the debugger.
+fn list_events_and_responses(
+    _request: grpc_client::ListExecutionEventsAndResponsesRequest,
+) -> grpc_client::ListExecutionEventsAndResponsesResponse {
+    let join_set_created = grpc_client::execution_event::Event::HistoryVariant(
+        grpc_client::execution_event::HistoryEvent {
+            event: Some(
+                grpc_client::execution_event::history_event::Event::JoinSetCreated(
+                    grpc_client::execution_event::history_event::JoinSetCreated {
+                        join_set_id: Some(grpc_client::JoinSetId {
+                            kind: grpc_client::join_set_id::JoinSetKind::Named as i32,
+                            name: "batch".to_string(),
+                        }),
+                    },
+                ),
+            ),
+        },
+    );
+    grpc_client::ListExecutionEventsAndResponsesResponse {
+        events: vec![
+            created_event(&checkout_ffqn(), r#"["order-1"]"#, 1_700_000_000),
+            event(1, 1_700_000_001, join_set_created),
+            finished_event(2, 1_700_000_002),
+        ],
+        responses: Vec::new(),
+        current_status: Some(finished_ok(1_700_000_002)),
+        max_version: 2,
+        max_cursor: 0,
+    }
+}
+
+fn execution_server() -> FakeServer {
+    FakeServer::default()
+        .streaming(
+            "obelisk.ExecutionRepository/GetStatus",
+            get_status_summary(summary(EXECUTION_ID, &checkout_ffqn(), 1_700_000_000)),
+        )
+        .unary(
+            "obelisk.ExecutionRepository/ListExecutionEventsAndResponses",
+            list_events_and_responses,
+        )
+}
+
+#[wasm_bindgen_test]
+async fn execution_list_renders_rows() {
+    FakeServer::default()
+        .unary(
+            "obelisk.ExecutionRepository/ListExecutions",
+            |_: grpc_client::ListExecutionsRequest| grpc_client::ListExecutionsResponse {
+                executions: vec![
+                    summary(EXECUTION_ID, &checkout_ffqn(), 1_700_000_000),
+                    summary(
+                        "E_01KAJY1W5V4RZ8H3QC6PX2N9TE",
+                        &function_name("shop:orders/refund", "run"),
+                        1_700_000_100,
+                    ),
+                ],
+            },
+        )
+        .install();
+    let page = mount(Route::ExecutionList);
+
+    page.wait_for(".execution-list-item .execution-id", EXECUTION_ID)
+        .await;
+    assert_eq!(
+        vec![EXECUTION_ID, "E_01KAJY1W5V4RZ8H3QC6PX2N9TE"],
+        page.texts(".execution-id")
+    );
+    page.wait_for(".execution-function", "refund").await;
+    assert_eq!(Vec::<String>::new(), page.notifications());
+}
+
+#[wasm_bindgen_test]
+async fn execution_list_without_executions() {
+    FakeServer::default()
+        .unary(
+            "obelisk.ExecutionRepository/ListExecutions",
+            |_: grpc_client::ListExecutionsRequest| grpc_client::ListExecutionsResponse::default(),
+        )
+        .install();
+    let page = mount(Route::ExecutionList);
+
+    page.wait_for(".execution-list-empty", "No executions")
+        .await;
+    assert!(page.texts(".execution-list-item").is_empty());
+}
+
+#[wasm_bindgen_test]
+async fn trace_renders_tree_and_history() {
+    execution_server().install();
+    let page = mount(Route::ExecutionTrace {
+        execution_id: execution_id(EXECUTION_ID),
+    });
+
+    page.wait_for(".step-ffqn", "checkout").await;
+    page.wait_for(".trace-detail", "Join Set Created").await;
+    page.wait_for(".trace-detail", "Succeeded").await;
+    assert_eq!(Vec::<String>::new(), page.notifications());
+}
+
+#[wasm_bindgen_test]
+async fn debugger_renders_source_of_frame() {
+    execution_server()
+        .unary(
+            "obelisk.ExecutionRepository/GetBacktrace",
+            |_: grpc_client::GetBacktraceRequest| grpc_client::GetBacktraceResponse {
+                component_id: Some(component_id("checkout")),
+                wasm_backtrace: Some(grpc_client::WasmBacktrace {
+                    frames: vec![grpc_client::FrameInfo {
+                        module: "checkout".to_string(),
+                        func_name: "run".to_string(),
+                        symbols: vec![grpc_client::FrameSymbol {
+                            func_name: Some("run".to_string()),
+                            file: Some("src/lib.rs".to_string()),
+                            line: Some(2),
+                            col: None,
+                        }],
+                    }],
+                    version_min_including: 1,
+                    version_max_excluding: 2,
+                }),
+            },
+        )
+        .unary(
+            "obelisk.ExecutionRepository/GetBacktraceSource",
+            |request: grpc_client::GetBacktraceSourceRequest| {
+                assert_eq!("src/lib.rs", request.file);
+                grpc_client::GetBacktraceSourceResponse {
+                    content: "fn run() {\n    charge_card();\n}\n".to_string(),
+                }
+            },
+        )
+        .install();
+    let page = mount(Route::ExecutionDebuggerWithVersions {
+        execution_id: execution_id(EXECUTION_ID),
+        versions: BacktraceVersionsPath::from(1),
+    });
+
+    page.wait_for(".frame-source", "charge_card();").await;
+}
+
+#[wasm_bindgen_test]
+async fn debugger_without_backtrace() {
+    execution_server()
+        .failing(
+            "obelisk.ExecutionRepository/GetBacktrace",
+            tonic::Code::NotFound,
+            "backtrace not found",
+        )
+        .install();
+    let page = mount(Route::ExecutionDebugger {
+        execution_id: execution_id(EXECUTION_ID),
+    });
+
+    page.wait_for("p", "Backtrace not found").await;
+    assert!(page.texts(".frame-source").is_empty());
+}
+
+#[wasm_bindgen_test]
+async fn logs_render_levels_and_streams() {
+    use grpc_client::list_logs_response::{LogEntry, log_entry};
+    execution_server()
+        .unary(
+            "obelisk.ExecutionRepository/ListLogs",
+            |_: grpc_client::ListLogsRequest| grpc_client::ListLogsResponse {
+                logs: vec![
+                    LogEntry {
+                        created_at: Some(timestamp(1_700_000_001)),
+                        run_id: None,
+                        execution_id: Some(execution_id(EXECUTION_ID)),
+                        entry: Some(log_entry::Entry::Log(log_entry::LogVariant {
+                            level: 3,
+                            message: "charging card".to_string(),
+                        })),
+                    },
+                    LogEntry {
+                        created_at: Some(timestamp(1_700_000_002)),
+                        run_id: None,
+                        execution_id: Some(execution_id(EXECUTION_ID)),
+                        entry: Some(log_entry::Entry::Stream(log_entry::StreamVariant {
+                            payload: b"card charged".to_vec(),
+                            stream_type: grpc_client::LogStreamType::Stdout as i32,
+                        })),
+                    },
+                ],
+                next_page_token: String::new(),
+                prev_page_token: None,
+            },
+        )
+        .install();
+    let page = mount(Route::Logs {
+        execution_id: execution_id(EXECUTION_ID),
+    });
+
+    page.wait_for(".log-row .payload", "card charged").await;
+    assert_eq!(
+        vec!["charging card", "card charged"],
+        page.texts(".log-row .payload")
+    );
+    assert_eq!(vec!["[INFO]", "[STDOUT]"], page.texts(".log-row .kind"));
+}
+
+#[wasm_bindgen_test]
+async fn deployment_diff_renders_added_component() {
+    FakeServer::default()
+        .unary(
+            "obelisk.DeploymentRepository/GetDeployment",
+            |request: grpc_client::GetDeploymentRequest| {
+                let deployment_id = request.deployment_id.expect("sent by the page");
+                let deployment_toml = if deployment_id.id == "Dep_1" {
+                    "[[activity_stub]]\nname = \"payments\"\n"
+                } else {
+                    "[[activity_stub]]\nname = \"payments\"\n\n\
+                     [[activity_stub]]\nname = \"shipping\"\n"
+                };
+                grpc_client::GetDeploymentResponse {
+                    deployment: Some(grpc_client::Deployment {
+                        deployment_id: Some(deployment_id),
+                        deployment_toml: Some(deployment_toml.to_string()),
+                        ..Default::default()
+                    }),
+                }
+            },
+        )
+        .unary(
+            "obelisk.FunctionRepository/ListComponents",
+            |_: grpc_client::ListComponentsRequest| grpc_client::ListComponentsResponse::default(),
+        )
+        .install();
+    let page = mount(Route::DeploymentDiff {
+        from: deployment_id("Dep_1"),
+        to: deployment_id("Dep_2"),
+    });
+
+    page.wait_for(".diff-added summary", "shipping").await;
+    page.wait_for(".unchanged-count", "(1 unchanged)").await;
+    assert!(page.texts(".diff-removed").is_empty());
+}
+
+/// A deployment with the `shop:approvals/review` activity stub.
+fn approvals_state() -> AppState {
+    let wit_type = |wit_type: &str| grpc_client::WitType {
+        wit_type: wit_type.to_string(),
+        wit_type_inline: wit_type.to_string(),
+        ..Default::default()
+    };
+    let component_id = grpc_client::ComponentId {
+        component_type: grpc_client::ComponentType::ActivityStub as i32,
+        ..component_id("approvals")
+    };
+    let component = Rc::new(grpc_client::Component {
+        component_id: Some(component_id.clone()),
+        exports: vec![grpc_client::FunctionDetail {
+            function_name: Some(function_name("shop:approvals/review", "approve")),
+            params: vec![grpc_client::FunctionParameter {
+                r#type: Some(wit_type("string")),
+                name: "order".to_string(),
+            }],
+            return_type: Some(wit_type("bool")),
+            extension: None,
+            submittable: true,
+        }],
+        ..Default::default()
+    });
+    let loaded = LoadedComponents {
+        components_by_id: [(component_id, component.clone())].into_iter().collect(),
+        components_by_exported_ifc: [(
+            IfcFqn::from_str("shop:approvals/review").expect("valid interface"),
+            component,
+        )]
+        .into_iter()
+        .collect(),
+    };
+    AppState::from_loaded(&loaded, Some(deployment_id("Dep_1")))
+}
+
+#[wasm_bindgen_test]
+async fn stub_inbox_answers_pending_execution() {
+    const STUB_ID: &str = "E_01KAJY1W5V4RZ8H3QC6PX2N9TD.o:1-approval_1";
+    let stubbed = Rc::new(RefCell::new(None::<grpc_client::StubRequest>));
+    FakeServer::default()
+        .unary(
+            "obelisk.ExecutionRepository/ListExecutions",
+            |request: grpc_client::ListExecutionsRequest| {
+                assert_eq!(
+                    vec![
+                        grpc_client::list_executions_request::ExecutionStateFilter::Pending as i32
+                    ],
+                    request.state_filters
+                );
+                grpc_client::ListExecutionsResponse {
+                    executions: vec![summary(
+                        STUB_ID,
+                        &function_name("shop:approvals/review", "approve"),
+                        1_700_000_000,
+                    )],
+                }
+            },
+        )
+        .unary(
+            "obelisk.ExecutionRepository/ListExecutionEvents",
+            |_: grpc_client::ListExecutionEventsRequest| {
+                let mut created = created_event(
+                    &function_name("shop:approvals/review", "approve"),
+                    r#"["order-1"]"#,
+                    1_700_000_000,
+                );
+                if let Some(grpc_client::execution_event::Event::Created(created)) =
+                    &mut created.event
+                {
+                    created.parent_execution_id = Some(execution_id(EXECUTION_ID));
+                }
+                grpc_client::ListExecutionEventsResponse {
+                    events: vec![created],
+                    max_version: 0,
+                }
+            },
+        )
+        .unary("obelisk.ExecutionRepository/Stub", {
+            let stubbed = stubbed.clone();
+            move |request: grpc_client::StubRequest| {
+                *stubbed.borrow_mut() = Some(request);
+                grpc_client::StubResponse {}
+            }
+        })
+        .install();
+    let page = mount_with_state(Route::StubInbox, approvals_state());
+
+    page.wait_for(".stub-inbox-caller", EXECUTION_ID).await;
+    assert_eq!(
+        vec![r#"order: "order-1""#],
+        page.texts(".stub-inbox-params li")
+    );
+    page.fill(".stub-inbox-answer textarea", "1");
+    page.wait_for(".stub-inbox-answer .validation-error", "Typecheck error")
+        .await;
+    page.fill(".stub-inbox-answer textarea", "true");
+    page.click(".stub-inbox-answer button[type=submit]");
+
+    page.wait_for(".stub-inbox-empty", "No executions are waiting")
+        .await;
+    let stubbed = stubbed.borrow_mut().take().expect("`Stub` was called");
+    assert_eq!(Some(execution_id(STUB_ID)), stubbed.execution_id);
+    assert_eq!(
+        b"true".as_slice(),
+        stubbed.return_value.expect("sent").value.as_slice()
+    );
+}
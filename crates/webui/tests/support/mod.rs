@@ -0,0 +1,409 @@
+//! Mounting pages in the browser against an in-process fake of the Obelisk gRPC-web API.
+//!
+//! The generated clients send their requests through the global `fetch`, which
+//! [`FakeServer::install`] replaces with a function answering gRPC-web frames, so no server
+//! is needed.
+
+use gloo::timers::future::TimeoutFuture;
+use prost::Message;
+use std::{collections::HashMap, rc::Rc};
+use wasm_bindgen::{JsCast as _, JsValue, prelude::Closure};
+use wasm_bindgen_futures::{JsFuture, future_to_promise};
+use web_sys::{
+    Element, Event, EventInit, Headers, HtmlElement, HtmlTextAreaElement, Request, Response,
+    ResponseInit,
+};
+use webui::{
+    app::{AppState, Route},
+    auth::AuthProvider,
+    components::notification::NotificationProvider,
+    grpc::grpc_client,
+    loader::LoadedComponents,
+};
+use yew::prelude::*;
+use yew_router::{
+    history::{AnyHistory, MemoryHistory},
+    prelude::*,
+};
+
+/// How long [`Mounted::wait_for`] polls before failing the test.
+const WAIT_TIMEOUT_MILLIS: u32 = 5000;
+const WAIT_POLL_MILLIS: u32 = 20;
+/// Flag of the gRPC-web frame carrying the trailers.
+const TRAILER_FRAME_FLAG: u8 = 0x80;
+
+/// Encoded response messages or the status of a failed call.
+type Reply = Result<Vec<Vec<u8>>, (tonic::Code, String)>;
+type Handler = Box<dyn Fn(&[u8]) -> Reply>;
+
+/// Handlers keyed by the gRPC method, e.g. `obelisk.ExecutionRepository/ListExecutions`.
+/// Methods without a handler fail with `UNIMPLEMENTED`.
+#[derive(Default)]
+pub struct FakeServer {
+    handlers: HashMap<String, Handler>,
+}
+
+impl FakeServer {
+    pub fn unary<Req: Message + Default, Resp: Message>(
+        self,
+        method: &str,
+        handler: impl Fn(Req) -> Resp + 'static,
+    ) -> Self {
+        self.streaming(method, move |req| vec![handler(req)])
+    }
+
+    /// Server streaming method, the stream ends after the returned messages.
+    pub fn streaming<Req: Message + Default, Resp: Message>(
+        mut self,
+        method: &str,
+        handler: impl Fn(Req) -> Vec<Resp> + 'static,
+    ) -> Self {
+        self.handlers.insert(
+            method.to_string(),
+            Box::new(move |body| {
+                let req = Req::decode(first_message(body))
+                    .map_err(|err| (tonic::Code::InvalidArgument, err.to_string()))?;
+                Ok(handler(req).iter().map(Message::encode_to_vec).collect())
+            }),
+        );
+        self
+    }
+
+    /// The method fails with the given status.
+    pub fn failing(mut self, method: &str, code: tonic::Code, message: &str) -> Self {
+        let message = message.to_string();
+        self.handlers.insert(
+            method.to_string(),
+            Box::new(move |_| Err((code, message.clone()))),
+        );
+        self
+    }
+
+    fn reply(&self, method: &str, body: &[u8]) -> Reply {
+        match self.handlers.get(method) {
+            Some(handler) => handler(body),
+            None => Err((
+                tonic::Code::Unimplemented,
+                format!("no fake handler of {method}"),
+            )),
+        }
+    }
+
+    /// Replace the global `fetch`, the previously installed server stops answering.
+    pub fn install(self) {
+        let server = Rc::new(self);
+        let fetch = Closure::<dyn Fn(Request) -> js_sys::Promise>::new(move |request: Request| {
+            let server = server.clone();
+            future_to_promise(async move {
+                let body = JsFuture::from(request.array_buffer()?).await?;
+                let body = js_sys::Uint8Array::new(&body).to_vec();
+                let url = request.url();
+                let method = url
+                    .split_once("/api/")
+                    .map_or(url.as_str(), |(_, method)| method);
+                grpc_web_response(server.reply(method, &body)).map(JsValue::from)
+            })
+        });
+        js_sys::Reflect::set(&js_sys::global(), &"fetch".into(), fetch.as_ref())
+            .expect("`fetch` is writable");
+        fetch.forget();
+    }
+}
+
+/// Payload of the first length-prefixed message of a request body.
+fn first_message(body: &[u8]) -> &[u8] {
+    let Some((header, rest)) = body.split_at_checked(5) else {
+        return &[];
+    };
+    let length = u32::from_be_bytes(header[1..5].try_into().expect("4 bytes")) as usize;
+    &rest[..length.min(rest.len())]
+}
+
+fn frame(flag: u8, payload: &[u8]) -> Vec<u8> {
+    let length = u32::try_from(payload.len()).expect("frames are small");
+    let mut frame = vec![flag];
+    frame.extend_from_slice(&length.to_be_bytes());
+    frame.extend_from_slice(payload);
+    frame
+}
+
+/// Data frames followed by the trailer frame with the `grpc-status`.
+fn grpc_web_response(reply: Reply) -> Result<Response, JsValue> {
+    let (messages, code, message) = match reply {
+        Ok(messages) => (messages, tonic::Code::Ok, String::new()),
+        Err((code, message)) => (Vec::new(), code, message),
+    };
+    let mut body: Vec<u8> = messages
+        .iter()
+        .flat_map(|message| frame(0, message))
+        .collect();
+    // Only printable ASCII is sent unescaped, see the gRPC HTTP/2 protocol.
+    let trailers = format!("grpc-status:{}\r\ngrpc-message:{message}\r\n", code as i32);
+    body.extend(frame(TRAILER_FRAME_FLAG, trailers.as_bytes()));
+
+    let headers = Headers::new()?;
+    headers.set("content-type", "application/grpc-web+proto")?;
+    let init = ResponseInit::new();
+    init.set_status(200);
+    init.set_headers(&headers);
+    Response::new_with_opt_u8_array_and_init(Some(&mut body), &init)
+}
+
+#[derive(Properties, PartialEq)]
+struct HarnessProps {
+    route: Route,
+    app_state: AppState,
+}
+
+/// The providers of `App` around a router starting at the route.
+#[component(Harness)]
+fn harness(HarnessProps { route, app_state }: &HarnessProps) -> Html {
+    let history = use_memo(route.clone(), |route| {
+        AnyHistory::from(MemoryHistory::with_entries([route.to_path()]))
+    });
+    html! {
+        <AuthProvider>
+            <NotificationProvider>
+                <ContextProvider<AppState> context={app_state.clone()}>
+                    <Router history={(*history).clone()}>
+                        <Switch<Route> render={Route::render} />
+                    </Router>
+                </ContextProvider<AppState>>
+            </NotificationProvider>
+        </AuthProvider>
+    }
+}
+
+/// A rendered page, unmounted when dropped.
+pub struct Mounted {
+    root: Element,
+    app: Option<AppHandle<Harness>>,
+}
+
+/// Render the page of the route without any loaded components.
+pub fn mount(route: Route) -> Mounted {
+    mount_with_state(
+        route,
+        AppState::from_loaded(&LoadedComponents::default(), None),
+    )
+}
+
+pub fn mount_with_state(route: Route, app_state: AppState) -> Mounted {
+    let root = gloo::utils::document()
+        .create_element("div")
+        .expect("can create a div");
+    gloo::utils::body()
+        .append_child(&root)
+        .expect("can append to body");
+    let app = yew::Renderer::<Harness>::with_root_and_props(
+        root.clone(),
+        HarnessProps { route, app_state },
+    )
+    .render();
+    Mounted {
+        root,
+        app: Some(app),
+    }
+}
+
+impl Mounted {
+    /// Text of the elements matching the selector.
+    pub fn texts(&self, selector: &str) -> Vec<String> {
+        let elements = self
+            .root
+            .query_selector_all(selector)
+            .expect("selector must be valid");
+        (0..elements.length())
+            .filter_map(|idx| elements.item(idx))
+            .filter_map(|node| node.text_content())
+            .collect()
+    }
+
+    /// Wait until an element matching the selector contains the text, fail with the rendered
+    /// HTML otherwise.
+    pub async fn wait_for(&self, selector: &str, text: &str) -> String {
+        for _ in 0..WAIT_TIMEOUT_MILLIS / WAIT_POLL_MILLIS {
+            if let Some(found) = self
+                .texts(selector)
+                .into_iter()
+                .find(|found| found.contains(text))
+            {
+                return found;
+            }
+            TimeoutFuture::new(WAIT_POLL_MILLIS).await;
+        }
+        panic!(
+            "no `{selector}` containing `{text}` within {WAIT_TIMEOUT_MILLIS} ms, rendered:\n{}",
+            self.root.inner_html()
+        )
+    }
+
+    /// Type into the text area, notifying the page like the browser would.
+    pub fn fill(&self, selector: &str, value: &str) {
+        let textarea: HtmlTextAreaElement = self.element(selector).unchecked_into();
+        textarea.set_value(value);
+        let init = EventInit::new();
+        init.set_bubbles(true);
+        let event = Event::new_with_event_init_dict("input", &init).expect("can create an event");
+        textarea
+            .dispatch_event(&event)
+            .expect("can dispatch the event");
+    }
+
+    pub fn click(&self, selector: &str) {
+        let element: HtmlElement = self.element(selector).unchecked_into();
+        element.click();
+    }
+
+    fn element(&self, selector: &str) -> Element {
+        self.root
+            .query_selector(selector)
+            .expect("selector must be valid")
+            .unwrap_or_else(|| panic!("no `{selector}`, rendered:\n{}", self.root.inner_html()))
+    }
+
+    /// Messages of the notifications shown so far.
+    pub fn notifications(&self) -> Vec<String> {
+        self.texts(".notification-message")
+    }
+}
+
+impl Drop for Mounted {
+    fn drop(&mut self) {
+        if let Some(app) = self.app.take() {
+            app.destroy();
+        }
+        self.root.remove();
+    }
+}
+
+pub fn execution_id(id: &str) -> grpc_client::ExecutionId {
+    grpc_client::ExecutionId { id: id.to_string() }
+}
+
+pub fn timestamp(seconds: i64) -> prost_wkt_types::Timestamp {
+    prost_wkt_types::Timestamp { seconds, nanos: 0 }
+}
+
+pub fn function_name(interface_name: &str, function_name: &str) -> grpc_client::FunctionName {
+    grpc_client::FunctionName {
+        interface_name: interface_name.to_string(),
+        function_name: function_name.to_string(),
+    }
+}
+
+pub fn component_id(name: &str) -> grpc_client::ComponentId {
+    grpc_client::ComponentId {
+        component_type: grpc_client::ComponentType::Workflow as i32,
+        name: name.to_string(),
+        digest: Some(content_digest()),
+    }
+}
+
+pub fn content_digest() -> grpc_client::ContentDigest {
+    grpc_client::ContentDigest {
+        digest: format!("sha256:{}", "ab".repeat(32)),
+    }
+}
+
+pub fn deployment_id(id: &str) -> grpc_client::DeploymentId {
+    grpc_client::DeploymentId { id: id.to_string() }
+}
+
+/// Status of an execution that finished successfully at the given second.
+pub fn finished_ok(finished_at: i64) -> grpc_client::ExecutionStatus {
+    grpc_client::ExecutionStatus {
+        component_digest: Some(content_digest()),
+        status: Some(grpc_client::execution_status::Status::Finished(
+            grpc_client::execution_status::Finished {
+                finished_at: Some(timestamp(finished_at)),
+                result_kind: Some(grpc_client::ResultKind {
+                    value: Some(grpc_client::result_kind::Value::Ok(
+                        grpc_client::result_kind::Ok {},
+                    )),
+                }),
+            },
+        )),
+    }
+}
+
+pub fn summary(
+    id: &str,
+    ffqn: &grpc_client::FunctionName,
+    created_at: i64,
+) -> grpc_client::ExecutionSummary {
+    grpc_client::ExecutionSummary {
+        execution_id: Some(execution_id(id)),
+        function_name: Some(ffqn.clone()),
+        current_status: Some(finished_ok(created_at + 1)),
+        created_at: Some(timestamp(created_at)),
+        first_scheduled_at: Some(timestamp(created_at)),
+        component_digest: Some(content_digest()),
+        deployment_id: Some(deployment_id("Dep_1")),
+        component_type: grpc_client::ComponentType::Workflow as i32,
+    }
+}
+
+/// `GetStatus` sending the summary, as used by the execution header.
+pub fn get_status_summary(
+    summary: grpc_client::ExecutionSummary,
+) -> impl Fn(grpc_client::GetStatusRequest) -> Vec<grpc_client::GetStatusResponse> {
+    move |_| {
+        vec![grpc_client::GetStatusResponse {
+            message: Some(grpc_client::get_status_response::Message::Summary(
+                summary.clone(),
+            )),
+        }]
+    }
+}
+
+pub fn event(
+    version: u32,
+    created_at: i64,
+    event: grpc_client::execution_event::Event,
+) -> grpc_client::ExecutionEvent {
+    grpc_client::ExecutionEvent {
+        created_at: Some(timestamp(created_at)),
+        version,
+        backtrace_id: None,
+        event: Some(event),
+    }
+}
+
+pub fn created_event(
+    ffqn: &grpc_client::FunctionName,
+    params: &str,
+    scheduled_at: i64,
+) -> grpc_client::ExecutionEvent {
+    event(
+        0,
+        scheduled_at,
+        grpc_client::execution_event::Event::Created(grpc_client::execution_event::Created {
+            function_name: Some(ffqn.clone()),
+            params: Some(prost_wkt_types::Any {
+                type_url: "urn:obelisk:json:params".to_string(),
+                value: params.as_bytes().to_vec(),
+            }),
+            scheduled_at: Some(timestamp(scheduled_at)),
+            component_id: Some(component_id("checkout")),
+            deployment_id: Some(deployment_id("Dep_1")),
+            ..Default::default()
+        }),
+    )
+}
+
+pub fn finished_event(version: u32, finished_at: i64) -> grpc_client::ExecutionEvent {
+    event(
+        version,
+        finished_at,
+        grpc_client::execution_event::Event::Finished(grpc_client::execution_event::Finished {
+            value: Some(grpc_client::SupportedFunctionResult {
+                wit_type_inline: None,
+                value: Some(grpc_client::supported_function_result::Value::Ok(
+                    grpc_client::supported_function_result::OkPayload { return_value: None },
+                )),
+            }),
+            http_client_traces: Vec::new(),
+        }),
+    )
+}
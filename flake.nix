@@ -31,6 +31,7 @@
           rustToolchain = pkgs.pkgsBuildHost.rust-bin.fromRustupToolchainFile ./rust-toolchain.toml;
           commonDeps = with pkgs; [
             binaryen # wasm-opt
+            brotli # precompressed static assets of webui-proxy
             cargo-edit
             cargo-expand
             just
@@ -0,0 +1,98 @@
+#!/usr/bin/env python3
+
+# Stand-in OpenID Connect provider for testing the `AUTH_MODE=oidc` of webui-proxy locally.
+# Every authorization request is approved immediately as the configured user.
+#
+# Usage: scripts/fake-oidc-provider.py [port] [user email]
+# Then run webui-proxy with
+#   OIDC_ISSUER_URL=http://127.0.0.1:9000 OIDC_CLIENT_ID=webui OIDC_CLIENT_SECRET=secret
+
+import base64
+import json
+import secrets
+import sys
+import time
+from http.server import BaseHTTPRequestHandler, HTTPServer
+from urllib.parse import parse_qs, urlencode, urlparse
+
+PORT = int(sys.argv[1]) if len(sys.argv) > 1 else 9000
+USER = sys.argv[2] if len(sys.argv) > 2 else "dev@example.com"
+ISSUER = f"http://127.0.0.1:{PORT}"
+
+# Authorization code -> (client_id, nonce)
+codes = {}
+
+
+def b64url(data):
+    return base64.urlsafe_b64encode(data).rstrip(b"=").decode()
+
+
+def id_token(client_id, nonce):
+    header = {"alg": "none", "typ": "JWT"}
+    now = int(time.time())
+    claims = {
+        "iss": ISSUER,
+        "aud": client_id,
+        "sub": USER,
+        "email": USER,
+        "iat": now,
+        "exp": now + 3600,
+        "nonce": nonce,
+    }
+    return f"{b64url(json.dumps(header).encode())}.{b64url(json.dumps(claims).encode())}."
+
+
+class Handler(BaseHTTPRequestHandler):
+    def send_json(self, status, body):
+        payload = json.dumps(body).encode()
+        self.send_response(status)
+        self.send_header("content-type", "application/json")
+        self.send_header("content-length", str(len(payload)))
+        self.end_headers()
+        self.wfile.write(payload)
+
+    def do_GET(self):
+        url = urlparse(self.path)
+        if url.path == "/.well-known/openid-configuration":
+            self.send_json(200, {
+                "issuer": ISSUER,
+                "authorization_endpoint": f"{ISSUER}/authorize",
+                "token_endpoint": f"{ISSUER}/token",
+                "response_types_supported": ["code"],
+                "subject_types_supported": ["public"],
+                "id_token_signing_alg_values_supported": ["none"],
+            })
+        elif url.path == "/authorize":
+            query = {key: values[0] for key, values in parse_qs(url.query).items()}
+            code = secrets.token_urlsafe(16)
+            codes[code] = (query["client_id"], query.get("nonce"))
+            self.send_response(302)
+            self.send_header("location", f"{query['redirect_uri']}?" + urlencode({
+                "code": code,
+                "state": query.get("state", ""),
+            }))
+            self.end_headers()
+        else:
+            self.send_json(404, {"error": "not_found"})
+
+    def do_POST(self):
+        if urlparse(self.path).path != "/token":
+            self.send_json(404, {"error": "not_found"})
+            return
+        length = int(self.headers.get("content-length", 0))
+        form = {key: values[0] for key, values in parse_qs(self.rfile.read(length).decode()).items()}
+        grant = codes.pop(form.get("code"), None)
+        if grant is None:
+            self.send_json(400, {"error": "invalid_grant"})
+            return
+        client_id, nonce = grant
+        self.send_json(200, {
+            "access_token": secrets.token_urlsafe(16),
+            "token_type": "Bearer",
+            "expires_in": 3600,
+            "id_token": id_token(client_id, nonce),
+        })
+
+
+print(f"Fake OpenID Connect provider of {USER} listening on {ISSUER}")
+HTTPServer(("127.0.0.1", PORT), Handler).serve_forever()